@@ -298,7 +298,11 @@ async function test8_MemoryStats() {
         .toString()
         .padStart(8)} | Total: ${tier.totalAllocated
         .toString()
-        .padStart(8)} | Saved: ${tier.memorySaved.toString().padStart(8)}`,
+        .padStart(8)} | Saved: ${tier.memorySaved
+        .toString()
+        .padStart(8)} | Largest free: ${tier.largestFreeBlock
+        .toString()
+        .padStart(8)} | Frag: ${(tier.fragmentation * 100).toFixed(1)}%`,
       'stats'
     );
   }
@@ -433,6 +437,78 @@ async function test12_TierReset() {
   log('✓ Tier reset test passed', 'success');
 }
 
+async function test13_IncrementalCompaction() {
+  log('Test 13: Deferred eviction with incremental compaction...');
+
+  walloc.set_deferred_compaction(true);
+
+  for (let i = 0; i < 8; i++) {
+    const data = new Uint8Array(4096).fill(i);
+    const handle = walloc.allocate(data.length, TIER.TOP);
+    walloc.write_memory(handle, data);
+    walloc.register_asset(
+      `compact_${i}`,
+      ASSET_TYPE.BINARY,
+      data.length,
+      handle,
+      TIER.TOP
+    );
+  }
+
+  // Evictions only plan the slide; the relocation happens in budgeted steps
+  for (let i = 1; i < 8; i += 2) {
+    assert(walloc.evict_asset(`compact_${i}`) === true, `Should evict compact_${i}`);
+  }
+
+  let steps = 0;
+  while (walloc.compact_step(4096)) {
+    steps++;
+  }
+  assert(steps > 0, 'Deferred eviction should leave compaction work');
+
+  for (let i = 0; i < 8; i += 2) {
+    const data = walloc.get_asset_data(`compact_${i}`);
+    assert(
+      data.every((b) => b === i),
+      `compact_${i} corrupted by compaction`
+    );
+  }
+
+  walloc.set_deferred_compaction(false);
+  log('✓ Incremental compaction test passed', 'success');
+}
+
+async function test14_CheckpointRestore() {
+  log('Test 14: Checkpoint and restore of temp usage...');
+
+  const usageOf = () =>
+    walloc.memory_stats().tiers.find((t) => t.name === 'entity').used;
+
+  const marker = walloc.checkpoint(TIER.BOTTOM);
+  assert(marker.length === 8, 'Checkpoint should be an opaque marker');
+  const usageBefore = usageOf();
+
+  for (let i = 0; i < 16; i++) {
+    walloc.write_memory(walloc.allocate(256, TIER.BOTTOM), new Uint8Array(256));
+  }
+  const usageAfter = usageOf();
+  assert(usageAfter > usageBefore, 'Temp allocations should use the tier');
+
+  const returned = walloc.restore(marker);
+  assert(returned === usageAfter - usageBefore, 'Restore should report the bytes handed back');
+  assert(usageOf() === usageBefore, 'Usage should unwind to the checkpoint');
+
+  let refused = false;
+  try {
+    walloc.restore(new Float64Array(3));
+  } catch (e) {
+    refused = true;
+  }
+  assert(refused, 'Malformed markers should be refused');
+
+  log('✓ Checkpoint/restore test passed', 'success');
+}
+
 async function runAllTests() {
   try {
     const startTime = performance.now();
@@ -464,6 +540,8 @@ async function runAllTests() {
     await test10_ErrorHandling();
     await test11_ZeroCopyOperations();
     await test12_TierReset();
+    await test13_IncrementalCompaction();
+    await test14_CheckpointRestore();
 
     const totalTime = performance.now() - startTime;
     log(`\nAll tests completed in ${totalTime.toFixed(2)}ms`, 'success');
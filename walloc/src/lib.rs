@@ -2,11 +2,20 @@
 //! 
 //! Enhanced with WASM-inspired optimizations for better memory management
 
-use std::sync::atomic::{AtomicUsize, AtomicPtr, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock, Weak};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex, RwLock};
+use std::marker::PhantomData;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use bytemuck::Pod;
 use reqwest::Client;
 use futures::stream::{self, StreamExt};
+use serde::Serialize;
 
 // SIMD imports
 #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
@@ -22,6 +31,107 @@ use wasm_bindgen_futures::future_to_promise;
 #[cfg(target_arch = "wasm32")]
 use js_sys::Promise;
 
+// ================================
+// === TRACING ===
+// ================================
+
+// Forwards to `tracing` when the feature is on and compiles to nothing otherwise, so call
+// sites must only pass values that are already in scope.
+#[cfg(feature = "tracing")]
+macro_rules! walloc_event {
+    ($level:ident, $($arg:tt)+) => { tracing::$level!(target: "walloc", $($arg)+) };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! walloc_event {
+    ($level:ident, $($arg:tt)+) => {};
+}
+
+// Milliseconds on a monotonic clock; std::time::Instant is unavailable in the browser
+fn clock_ms() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START.get_or_init(std::time::Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+// The same clock in nanoseconds, for latency profiling. Browsers only tick in milliseconds.
+#[inline(always)]
+fn clock_ns() -> u64 {
+    (clock_ms() * 1_000_000.0) as u64
+}
+
+// ================================
+// === ADDRESS SANITIZER ===
+// ================================
+
+// With the `asan` feature, arena blocks are poisoned while free (freelist links excepted) and
+// unpoisoned when handed out, so ASan builds catch stray accesses into walloc memory that the
+// sanitizer would otherwise see as one live allocation. The symbols come from the ASan
+// runtime, so the feature needs a nightly build with -Zsanitizer=address.
+#[cfg(feature = "asan")]
+unsafe extern "C" {
+    fn __asan_poison_memory_region(addr: *const u8, size: usize);
+    fn __asan_unpoison_memory_region(addr: *const u8, size: usize);
+    fn __asan_region_is_poisoned(addr: *const u8, size: usize) -> *const u8;
+}
+
+#[inline(always)]
+fn asan_poison(ptr: *const u8, len: usize) {
+    #[cfg(feature = "asan")]
+    unsafe { __asan_poison_memory_region(ptr, len) }
+    #[cfg(not(feature = "asan"))]
+    let _ = (ptr, len);
+}
+
+#[inline(always)]
+fn asan_unpoison(ptr: *const u8, len: usize) {
+    #[cfg(feature = "asan")]
+    unsafe { __asan_unpoison_memory_region(ptr, len) }
+    #[cfg(not(feature = "asan"))]
+    let _ = (ptr, len);
+}
+
+// First poisoned byte in the range, if any
+#[inline(always)]
+fn asan_first_poisoned(ptr: *const u8, len: usize) -> Option<usize> {
+    #[cfg(feature = "asan")]
+    {
+        let first = unsafe { __asan_region_is_poisoned(ptr, len) };
+        (!first.is_null()).then(|| first as usize - ptr as usize)
+    }
+    #[cfg(not(feature = "asan"))]
+    {
+        let _ = (ptr, len);
+        None
+    }
+}
+
+// Copy a range of arena memory that may contain free blocks, e.g. for a tier snapshot. Under
+// ASan only the unpoisoned runs are copied (and unpoisoned at `dst`); free bytes hold nothing
+// worth keeping, and reading them would be reported.
+unsafe fn copy_arena_bytes(src: *const u8, dst: *mut u8, len: usize) {
+    let mut copied = 0;
+    while copied < len {
+        let run = asan_first_poisoned(unsafe { src.add(copied) }, len - copied).unwrap_or(len - copied);
+        asan_unpoison(unsafe { dst.add(copied) }, run);
+        unsafe {
+            SIMDOps::fast_copy(src.add(copied), dst.add(copied), run);
+        }
+        copied += run;
+        // Skip the poisoned run a byte at a time; only reachable with the feature on
+        while copied < len && asan_first_poisoned(unsafe { src.add(copied) }, 1).is_some() {
+            copied += 1;
+        }
+    }
+}
+
 // ================================
 // === CORE CONSTANTS ===
 // ================================
@@ -30,6 +140,9 @@ const CACHE_LINE_SIZE: usize = 64;
 const SIMD_ALIGNMENT: usize = 32;
 const PARALLEL_LOAD_FACTOR: usize = 8;
 
+// Percent of memory given to Top, Middle and Bottom unless a WallocBuilder says otherwise
+const DEFAULT_TIER_SPLIT: [usize; 3] = [50, 30, 20];
+
 // Platform-specific memory limits
 #[cfg(target_arch = "wasm32")]
 const MAX_MEMORY_LIMIT: usize = usize::MAX; // Maximum addressable on 32-bit
@@ -37,9 +150,6 @@ const MAX_MEMORY_LIMIT: usize = usize::MAX; // Maximum addressable on 32-bit
 #[cfg(not(target_arch = "wasm32"))]
 const MAX_MEMORY_LIMIT: usize = 4 * 1024 * 1024 * 1024; // 4GB limit
 
-#[cfg(not(target_arch = "wasm32"))]
-static mut GLOBAL_MEMORY_BASE: *mut u8 = std::ptr::null_mut();
-
 // ================================
 // === CORE TYPES ===
 // ================================
@@ -80,6 +190,15 @@ impl Tier {
             Tier::Bottom => 20,
         }
     }
+    
+    // Name used in stats output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tier::Top => "render",
+            Tier::Middle => "scene",
+            Tier::Bottom => "entity",
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -90,47 +209,64 @@ pub enum AssetType {
     Binary = 2,
 }
 
+impl AssetType {
+    pub fn from_u8(value: u8) -> Option<AssetType> {
+        match value {
+            0 => Some(AssetType::Image),
+            1 => Some(AssetType::Json),
+            2 => Some(AssetType::Binary),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct MemoryHandle(usize);
 
 impl MemoryHandle {
+    // Handles are offsets from the owning Walloc's memory base on native, and addresses in
+    // wasm linear memory, whose base is null
     #[inline(always)]
-    pub fn to_ptr(self) -> *mut u8 {
+    fn resolve(self, base: *mut u8) -> *mut u8 {
         if self.is_null() {
             return std::ptr::null_mut();
         }
         
         #[cfg(target_arch = "wasm32")]
         { 
+            let _ = base;
             self.0 as *mut u8 
         }
         
         #[cfg(not(target_arch = "wasm32"))]
         { 
-            unsafe { 
-                if GLOBAL_MEMORY_BASE.is_null() {
-                    return std::ptr::null_mut();
-                }
-                GLOBAL_MEMORY_BASE.add(self.0) 
-            } 
+            base.wrapping_add(self.0)
         }
     }
     
     #[inline(always)]
-    pub fn from_ptr(ptr: *mut u8) -> Self {
+    fn from_ptr(ptr: *mut u8, base: *mut u8) -> Self {
         if ptr.is_null() {
             return MemoryHandle::null();
         }
-        
+        MemoryHandle((ptr as usize).wrapping_sub(base as usize))
+    }
+    
+    // Resolves against the most recently created Walloc, as when walloc kept one global
+    // memory base, and to null once that instance is dropped. Only right while a single
+    // instance exists; handles from any other instance resolve to wild pointers.
+    #[deprecated(note = "handles are per instance; use Walloc::handle_ptr")]
+    pub fn to_ptr(self) -> *mut u8 {
         #[cfg(target_arch = "wasm32")]
-        { 
-            MemoryHandle(ptr as usize) 
-        }
+        { self.resolve(std::ptr::null_mut()) }
         
         #[cfg(not(target_arch = "wasm32"))]
-        { 
-            let offset = unsafe { ptr.offset_from(GLOBAL_MEMORY_BASE) as usize };
-            MemoryHandle(offset)
+        {
+            let base = LATEST_HANDLE_BASE.load(Ordering::Acquire);
+            if base.is_null() {
+                return std::ptr::null_mut();
+            }
+            self.resolve(base)
         }
     }
     
@@ -149,37 +285,280 @@ impl MemoryHandle {
     }
 }
 
+// Gives every Walloc its own id for InstanceHandle and its handle space; 0 is never handed out
+static NEXT_INSTANCE_ID: AtomicU32 = AtomicU32::new(1);
+
+// Handle base of the newest live Walloc, kept only for the deprecated MemoryHandle::to_ptr
+#[cfg(not(target_arch = "wasm32"))]
+static LATEST_HANDLE_BASE: std::sync::atomic::AtomicPtr<u8> = std::sync::atomic::AtomicPtr::new(std::ptr::null_mut());
+
+// On 64-bit native targets each instance's handles start at its id times 4GB, the most
+// memory one can have, so a handle passed to another instance lies outside all of its tiers
+// and checked operations there fail with WrongInstance. wasm handles are linear-memory
+// addresses and 32-bit targets have no bits to spare, so both keep a single space.
+#[cfg(all(target_pointer_width = "64", not(target_arch = "wasm32")))]
+const HANDLE_SPACE_SHIFT: u32 = 32;
+
+fn handle_space(instance_id: u32) -> usize {
+    #[cfg(all(target_pointer_width = "64", not(target_arch = "wasm32")))]
+    { (instance_id as usize) << HANDLE_SPACE_SHIFT }
+    
+    #[cfg(not(all(target_pointer_width = "64", not(target_arch = "wasm32"))))]
+    { let _ = instance_id; 0 }
+}
+
+fn in_handle_space(offset: usize, space: usize) -> bool {
+    #[cfg(all(target_pointer_width = "64", not(target_arch = "wasm32")))]
+    { offset.wrapping_sub(space) >> HANDLE_SPACE_SHIFT == 0 }
+    
+    #[cfg(not(all(target_pointer_width = "64", not(target_arch = "wasm32"))))]
+    { let _ = (offset, space); true }
+}
+
+// A handle that remembers which Walloc it came from. Plain handles are refused by other
+// instances on 64-bit native targets (see handle_space), but not by a duplicate, which shares
+// its original's space, nor anywhere on wasm or 32-bit targets; Walloc::resolve_handle
+// refuses all of these.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InstanceHandle {
+    handle: MemoryHandle,
+    instance: u32,
+}
+
+impl InstanceHandle {
+    pub fn instance(self) -> u32 {
+        self.instance
+    }
+    
+    // The bare offset, without the instance check
+    pub fn unscoped(self) -> MemoryHandle {
+        self.handle
+    }
+}
+
 // ================================
 // === MEMORY OWNER TRACKING ===
 // ================================
 
-pub struct MemoryOwner {
+// Passed to a budget's callback when an owner allocation would take it past its limit
+#[derive(Clone, Debug)]
+pub struct QuotaExceeded {
+    pub budget: String,
+    pub limit: usize,
+    pub used: usize,
+    pub requested: usize,
+}
+
+type QuotaCallback = Box<dyn Fn(&QuotaExceeded) + Send + Sync>;
+
+struct BudgetInner {
+    name: String,
+    limit: usize,
+    used: AtomicUsize,
+    on_exceeded: RwLock<Option<QuotaCallback>>,
+}
+
+// A named byte cap shared by any number of owners, so one subsystem can't starve another
+// inside a shared tier. Clones refer to the same budget.
+#[derive(Clone)]
+pub struct MemoryBudget {
+    inner: Arc<BudgetInner>,
+}
+
+impl MemoryBudget {
+    pub fn new(name: impl Into<String>, limit: usize) -> Self {
+        Self {
+            inner: Arc::new(BudgetInner {
+                name: name.into(),
+                limit,
+                used: AtomicUsize::new(0),
+                on_exceeded: RwLock::new(None),
+            }),
+        }
+    }
+    
+    // Called on every refused allocation, before it returns None
+    pub fn on_exceeded(&self, callback: impl Fn(&QuotaExceeded) + Send + Sync + 'static) {
+        *self.inner.on_exceeded.write().unwrap() = Some(Box::new(callback));
+    }
+    
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+    
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+    
+    pub fn used(&self) -> usize {
+        self.inner.used.load(Ordering::Acquire)
+    }
+    
+    pub fn remaining(&self) -> usize {
+        self.limit().saturating_sub(self.used())
+    }
+    
+    fn try_charge(&self, size: usize) -> bool {
+        let limit = self.inner.limit;
+        let charged = self.inner.used.fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+            used.checked_add(size).filter(|&total| total <= limit)
+        });
+        
+        if let Err(used) = charged {
+            walloc_event!(warn, budget = %self.inner.name, limit, used, requested = size, "quota exceeded");
+            if let Some(callback) = self.inner.on_exceeded.read().unwrap().as_ref() {
+                callback(&QuotaExceeded { budget: self.inner.name.clone(), limit, used, requested: size });
+            }
+            return false;
+        }
+        true
+    }
+    
+    fn release(&self, size: usize) {
+        self.inner.used.fetch_sub(size, Ordering::AcqRel);
+    }
+    
+    fn same_as(&self, other: &MemoryBudget) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+// Borrows the Walloc it allocates from, so everything still owned is always freed on drop
+pub struct MemoryOwner<'a> {
     arena_index: usize,
     allocations: Vec<(MemoryHandle, usize)>, // (handle, size) pairs
-    walloc: Weak<Walloc>,
+    walloc: &'a Walloc,
+    budget: Option<MemoryBudget>,
 }
 
-impl MemoryOwner {
-    fn new(arena_index: usize, walloc: Weak<Walloc>) -> Self {
+impl<'a> MemoryOwner<'a> {
+    fn new(arena_index: usize, walloc: &'a Walloc) -> Self {
         Self {
             arena_index,
             allocations: Vec::new(),
             walloc,
+            budget: None,
+        }
+    }
+    
+    // Cap this owner alone at `limit` bytes
+    pub fn with_quota(self, limit: usize) -> Self {
+        self.with_budget(MemoryBudget::new("owner", limit))
+    }
+    
+    // Charge this owner's allocations to a budget shared with other owners. Allocations the
+    // owner already holds are charged too, even if that takes the budget over its limit.
+    pub fn with_budget(mut self, budget: MemoryBudget) -> Self {
+        if let Some(previous) = self.budget.take() {
+            previous.release(self.total_size());
         }
+        budget.inner.used.fetch_add(self.total_size(), Ordering::AcqRel);
+        self.budget = Some(budget);
+        self
+    }
+    
+    pub fn budget(&self) -> Option<&MemoryBudget> {
+        self.budget.as_ref()
     }
     
     fn add_allocation(&mut self, handle: MemoryHandle, size: usize) {
         self.allocations.push((handle, size));
     }
     
+    pub fn tier(&self) -> Tier {
+        Tier::from_u8(self.arena_index as u8).unwrap_or(Tier::Bottom)
+    }
+    
     pub fn total_size(&self) -> usize {
         self.allocations.iter().map(|(_, size)| size).sum()
     }
+    
+    pub fn len(&self) -> usize {
+        self.allocations.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.allocations.is_empty()
+    }
+    
+    pub fn contains(&self, handle: MemoryHandle) -> bool {
+        self.allocations.iter().any(|&(owned, _)| owned == handle)
+    }
+    
+    // (handle, size) for every live allocation, in no particular order
+    pub fn handles(&self) -> impl Iterator<Item = (MemoryHandle, usize)> + '_ {
+        self.allocations.iter().copied()
+    }
+    
+    // Allocate from the owner's tier; the block is freed with the owner unless freed earlier.
+    // Returns None without touching the tier if the owner's budget can't cover `size`.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate(&mut self, size: usize) -> Option<MemoryHandle> {
+        if let Some(budget) = &self.budget
+            && !budget.try_charge(size)
+        {
+            return None;
+        }
+        
+        let Some(handle) = self.walloc.allocate(size, self.tier()) else {
+            if let Some(budget) = &self.budget {
+                budget.release(size);
+            }
+            return None;
+        };
+        self.add_allocation(handle, size);
+        Some(handle)
+    }
+    
+    // Release one owned allocation early. Returns false if the owner does not hold it.
+    pub fn free(&mut self, handle: MemoryHandle) -> bool {
+        let Some(index) = self.allocations.iter().position(|&(owned, _)| owned == handle) else {
+            return false;
+        };
+        let (handle, size) = self.allocations.swap_remove(index);
+        if let Some(budget) = &self.budget {
+            budget.release(size);
+        }
+        
+        self.walloc.arenas[self.arena_index].deallocate(handle, size);
+        self.walloc.check_compaction_policy(self.tier());
+        true
+    }
+    
+    // Give up ownership without freeing anything; the caller becomes responsible for the
+    // returned (handle, size) pairs. The budget stops being charged for them.
+    pub fn into_raw(mut self) -> Vec<(MemoryHandle, usize)> {
+        if let Some(budget) = &self.budget {
+            budget.release(self.total_size());
+        }
+        self.budget = None;
+        std::mem::take(&mut self.allocations)
+    }
+    
+    // Take over another owner's allocations. Owners of a different tier, Walloc or budget
+    // are handed back untouched.
+    pub fn merge(&mut self, mut other: MemoryOwner<'a>) -> Result<(), MemoryOwner<'a>> {
+        let same_budget = match (&self.budget, &other.budget) {
+            (Some(ours), Some(theirs)) => ours.same_as(theirs),
+            (None, None) => true,
+            _ => false,
+        };
+        if other.arena_index != self.arena_index || !std::ptr::eq(other.walloc, self.walloc) || !same_budget {
+            return Err(other);
+        }
+        self.allocations.append(&mut other.allocations);
+        Ok(())
+    }
 }
 
-impl Drop for MemoryOwner {
+impl Drop for MemoryOwner<'_> {
     fn drop(&mut self) {
-        if let Some(walloc) = self.walloc.upgrade() {
+        if let Some(budget) = &self.budget {
+            budget.release(self.total_size());
+        }
+        
+        if !self.allocations.is_empty() {
+            let walloc = self.walloc;
             let arena = &walloc.arenas[self.arena_index];
             
             // Deallocate all owned allocations
@@ -187,6 +566,10 @@ impl Drop for MemoryOwner {
                 arena.deallocate(handle, size);
             }
             
+            if let Some(tier) = Tier::from_u8(self.arena_index as u8) {
+                walloc.check_compaction_policy(tier);
+            }
+            
             #[cfg(target_arch = "wasm32")]
             {
                 // On WASM, trigger a compaction if we freed significant memory
@@ -220,6 +603,168 @@ pub struct AssetMetadata {
     pub offset: usize,
     pub tier: Tier,
     pub handle: MemoryHandle,
+    pub tag: Option<String>,  // Free-form grouping label for attribution reports
+    pub priority: u8,         // Lower priorities are evicted first by allocate_or_evict
+    pub pinned: bool,         // Never chosen as an eviction victim
+    pub freshness: Option<Freshness>,  // Set for assets fetched over HTTP
+}
+
+impl AssetMetadata {
+    pub fn new(asset_type: AssetType, handle: MemoryHandle, size: usize, tier: Tier) -> Self {
+        Self {
+            asset_type,
+            size,
+            offset: handle.offset(),
+            tier,
+            handle,
+            tag: None,
+            priority: 0,
+            pinned: false,
+            freshness: None,
+        }
+    }
+    
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+    
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+    
+    pub fn pinned(mut self) -> Self {
+        self.pinned = true;
+        self
+    }
+    
+    // Fetched over HTTP and past its freshness lifetime, so due for revalidation
+    pub fn is_stale(&self) -> bool {
+        self.freshness.as_ref().is_some_and(|freshness| !freshness.is_fresh())
+    }
+}
+
+// Per-call overrides for Walloc::allocate_with_opts. An `align` of 0 (or anything below the
+// tier's own alignment) keeps the tier default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocOpts {
+    pub align: usize,
+    pub zeroed: bool,
+    pub tag: Option<String>,  // Counted under this tag in attribution reports until freed
+}
+
+impl AllocOpts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_align(mut self, align: usize) -> Self {
+        self.align = align;
+        self
+    }
+    
+    pub fn zeroed(mut self) -> Self {
+        self.zeroed = true;
+        self
+    }
+    
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+}
+
+// ================================
+// === TYPED HANDLES ===
+// ================================
+
+// A handle to `len` consecutive `T`s. Construction checks alignment, and `Pod` guarantees any
+// bytes in the arena are a valid `T`, so reads and writes need no unsafe at the call site.
+// Like MemoryHandle it does not keep the allocation alive: every access is checked against the
+// owning block again and borrowed like with_slice, so one made through a stale handle fails.
+pub struct TypedHandle<'a, T: Pod> {
+    walloc: &'a Walloc,
+    handle: MemoryHandle,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Clone for TypedHandle<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Pod> Copy for TypedHandle<'_, T> {}
+
+impl<T: Pod> std::fmt::Debug for TypedHandle<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedHandle")
+            .field("type", &std::any::type_name::<T>())
+            .field("handle", &self.handle)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<T: Pod> TypedHandle<'_, T> {
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn byte_len(&self) -> usize {
+        self.len * std::mem::size_of::<T>()
+    }
+    
+    // Run `f` on elements `index..index + count` once they are known to lie in the handle and
+    // in the live block it points into, holding a borrow of them for the duration
+    fn access<R>(&self, index: usize, count: usize, exclusive: bool, f: impl FnOnce(*mut T) -> R) -> Result<R, AccessError> {
+        if index.checked_add(count).is_none_or(|end| end > self.len) {
+            return Err(AccessError::OutOfBounds);
+        }
+        let element = std::mem::size_of::<T>();
+        let handle = self.handle.advance(index * element);
+        let (start, end) = self.walloc.check_range(handle, count * element)?;
+        let _borrow = self.walloc.borrows.acquire(start, end, exclusive)?;
+        Ok(f(self.walloc.handle_ptr(handle) as *mut T))
+    }
+    
+    // First element
+    pub fn read(&self) -> Option<T> {
+        self.get(0)
+    }
+    
+    pub fn write(&self, value: &T) -> Result<(), AccessError> {
+        self.set(0, value)
+    }
+    
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.access(index, 1, false, |ptr| unsafe { ptr.read() }).ok()
+    }
+    
+    pub fn set(&self, index: usize, value: &T) -> Result<(), AccessError> {
+        self.access(index, 1, true, |ptr| unsafe { ptr.write(*value) })
+    }
+    
+    pub fn read_slice(&self) -> Result<Vec<T>, AccessError> {
+        self.access(0, self.len, false, |ptr| unsafe { std::slice::from_raw_parts(ptr, self.len) }.to_vec())
+    }
+    
+    // Writes `values` from the start; shorter slices leave the tail untouched
+    pub fn write_slice(&self, values: &[T]) -> Result<(), AccessError> {
+        self.access(0, values.len(), true, |ptr| unsafe {
+            SIMDOps::fast_copy(values.as_ptr() as *const u8, ptr as *mut u8, std::mem::size_of_val(values));
+        })
+    }
 }
 
 // ================================
@@ -229,41 +774,54 @@ pub struct AssetMetadata {
 pub struct SIMDOps;
 
 impl SIMDOps {
+    /// # Safety
+    /// `src` must be valid for `len` bytes of reads, `dst` valid for `len` bytes of
+    /// writes, and the two ranges must not overlap (use `fast_move` otherwise).
     #[inline(always)]
     pub unsafe fn fast_copy(src: *const u8, dst: *mut u8, len: usize) {
+        // Miri checks the plain copy; the wide unaligned loads below only add noise there
+        #[cfg(miri)]
+        unsafe {
+            std::ptr::copy_nonoverlapping(src, dst, len);
+        }
+        
         // Optimize for common sizes first
-        match len {
-            0 => return,
-            1..=8 => {
-                if len >= 4 {
-                    let v = (src as *const u32).read_unaligned();
-                    (dst as *mut u32).write_unaligned(v);
-                    let v = (src.add(len - 4) as *const u32).read_unaligned();
-                    (dst.add(len - 4) as *mut u32).write_unaligned(v);
-                } else {
-                    std::ptr::copy_nonoverlapping(src, dst, len);
+        #[cfg(not(miri))]
+        unsafe {
+            match len {
+                0 => (),
+                1..=8 => {
+                    if len >= 4 {
+                        let v = (src as *const u32).read_unaligned();
+                        (dst as *mut u32).write_unaligned(v);
+                        let v = (src.add(len - 4) as *const u32).read_unaligned();
+                        (dst.add(len - 4) as *mut u32).write_unaligned(v);
+                    } else {
+                        std::ptr::copy_nonoverlapping(src, dst, len);
+                    }
                 }
+                9..=16 => {
+                    let v = (src as *const u64).read_unaligned();
+                    (dst as *mut u64).write_unaligned(v);
+                    let v = (src.add(len - 8) as *const u64).read_unaligned();
+                    (dst.add(len - 8) as *mut u64).write_unaligned(v);
+                }
+                17..=32 => {
+                    let v1 = (src as *const u128).read_unaligned();
+                    let v2 = (src.add(len - 16) as *const u128).read_unaligned();
+                    (dst as *mut u128).write_unaligned(v1);
+                    (dst.add(len - 16) as *mut u128).write_unaligned(v2);
+                }
+                _ => Self::copy_vectorized(src, dst, len),
             }
-            9..=16 => {
-                let v = (src as *const u64).read_unaligned();
-                (dst as *mut u64).write_unaligned(v);
-                let v = (src.add(len - 8) as *const u64).read_unaligned();
-                (dst.add(len - 8) as *mut u64).write_unaligned(v);
-            }
-            17..=32 => {
-                let v1 = (src as *const u128).read_unaligned();
-                let v2 = (src.add(len - 16) as *const u128).read_unaligned();
-                (dst as *mut u128).write_unaligned(v1);
-                (dst.add(len - 16) as *mut u128).write_unaligned(v2);
-            }
-            _ => Self::copy_vectorized(src, dst, len),
         }
     }
 
     #[inline(never)]
+    #[cfg_attr(miri, allow(dead_code))]
     unsafe fn copy_vectorized(src: *const u8, dst: *mut u8, len: usize) {
         #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
-        {
+        unsafe {
             if len > 4096 {
                 _mm_prefetch(src as *const i8, _MM_HINT_T0);
                 _mm_prefetch(src.add(64) as *const i8, _MM_HINT_T0);
@@ -301,7 +859,7 @@ impl SIMDOps {
         }
         
         #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
-        {
+        unsafe {
             let mut offset = 0;
             while offset + 64 <= len {
                 let v0 = v128_load(src.add(offset) as *const v128);
@@ -332,12 +890,219 @@ impl SIMDOps {
             all(target_arch = "x86_64", target_feature = "avx2"),
             all(target_arch = "wasm32", target_feature = "simd128")
         )))]
-        {
+        unsafe {
             std::ptr::copy_nonoverlapping(src, dst, len);
         }
     }
-    
-    pub unsafe fn bulk_copy_optimized(operations: &[(MemoryHandle, MemoryHandle, usize)]) {
+
+    /// Overlap-safe counterpart to `fast_copy` (memmove semantics).
+    ///
+    /// # Safety
+    /// `src` must be valid for `len` bytes of reads and `dst` valid for `len` bytes of writes.
+    /// The ranges may overlap.
+    #[inline(always)]
+    pub unsafe fn fast_move(src: *const u8, dst: *mut u8, len: usize) {
+        let (src_addr, dst_addr) = (src as usize, dst as usize);
+        if len == 0 || src_addr == dst_addr {
+            return;
+        }
+        
+        #[cfg(miri)]
+        unsafe {
+            std::ptr::copy(src, dst, len);
+        }
+        
+        #[cfg(not(miri))]
+        unsafe {
+            // Disjoint ranges can take the regular copy path
+            if src_addr.abs_diff(dst_addr) >= len {
+                Self::fast_copy(src, dst, len);
+            } else if len <= 32 {
+                Self::move_small(src, dst, len);
+            } else if dst_addr < src_addr {
+                // Sliding down: a forward walk only ever overwrites bytes already read
+                Self::move_forward(src, dst, len);
+            } else {
+                // Sliding up: walk backwards so the tail is read before it's clobbered
+                Self::move_backward(src, dst, len);
+            }
+        }
+    }
+
+    // Loads everything before storing anything, so any overlap is fine
+    #[inline(always)]
+    #[cfg_attr(miri, allow(dead_code))]
+    unsafe fn move_small(src: *const u8, dst: *mut u8, len: usize) {
+        unsafe {
+            match len {
+                0 => (),
+                1..=3 => std::ptr::copy(src, dst, len),
+                4..=8 => {
+                    let v1 = (src as *const u32).read_unaligned();
+                    let v2 = (src.add(len - 4) as *const u32).read_unaligned();
+                    (dst as *mut u32).write_unaligned(v1);
+                    (dst.add(len - 4) as *mut u32).write_unaligned(v2);
+                }
+                9..=16 => {
+                    let v1 = (src as *const u64).read_unaligned();
+                    let v2 = (src.add(len - 8) as *const u64).read_unaligned();
+                    (dst as *mut u64).write_unaligned(v1);
+                    (dst.add(len - 8) as *mut u64).write_unaligned(v2);
+                }
+                _ => {
+                    let v1 = (src as *const u128).read_unaligned();
+                    let v2 = (src.add(len - 16) as *const u128).read_unaligned();
+                    (dst as *mut u128).write_unaligned(v1);
+                    (dst.add(len - 16) as *mut u128).write_unaligned(v2);
+                }
+            }
+        }
+    }
+
+    #[inline(never)]
+    #[cfg_attr(miri, allow(dead_code))]
+    unsafe fn move_forward(src: *const u8, dst: *mut u8, len: usize) {
+        #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+        unsafe {
+            let mut offset = 0;
+            while offset + 128 <= len {
+                let v0 = _mm256_loadu_si256(src.add(offset) as *const __m256i);
+                let v1 = _mm256_loadu_si256(src.add(offset + 32) as *const __m256i);
+                let v2 = _mm256_loadu_si256(src.add(offset + 64) as *const __m256i);
+                let v3 = _mm256_loadu_si256(src.add(offset + 96) as *const __m256i);
+                
+                _mm256_storeu_si256(dst.add(offset) as *mut __m256i, v0);
+                _mm256_storeu_si256(dst.add(offset + 32) as *mut __m256i, v1);
+                _mm256_storeu_si256(dst.add(offset + 64) as *mut __m256i, v2);
+                _mm256_storeu_si256(dst.add(offset + 96) as *mut __m256i, v3);
+                
+                offset += 128;
+            }
+            
+            while offset + 32 <= len {
+                let v = _mm256_loadu_si256(src.add(offset) as *const __m256i);
+                _mm256_storeu_si256(dst.add(offset) as *mut __m256i, v);
+                offset += 32;
+            }
+            
+            if offset < len {
+                Self::move_small(src.add(offset), dst.add(offset), len - offset);
+            }
+        }
+        
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        unsafe {
+            let mut offset = 0;
+            while offset + 64 <= len {
+                let v0 = v128_load(src.add(offset) as *const v128);
+                let v1 = v128_load(src.add(offset + 16) as *const v128);
+                let v2 = v128_load(src.add(offset + 32) as *const v128);
+                let v3 = v128_load(src.add(offset + 48) as *const v128);
+                
+                v128_store(dst.add(offset) as *mut v128, v0);
+                v128_store(dst.add(offset + 16) as *mut v128, v1);
+                v128_store(dst.add(offset + 32) as *mut v128, v2);
+                v128_store(dst.add(offset + 48) as *mut v128, v3);
+                
+                offset += 64;
+            }
+            
+            while offset + 16 <= len {
+                let v = v128_load(src.add(offset) as *const v128);
+                v128_store(dst.add(offset) as *mut v128, v);
+                offset += 16;
+            }
+            
+            if offset < len {
+                Self::move_small(src.add(offset), dst.add(offset), len - offset);
+            }
+        }
+        
+        #[cfg(not(any(
+            all(target_arch = "x86_64", target_feature = "avx2"),
+            all(target_arch = "wasm32", target_feature = "simd128")
+        )))]
+        unsafe {
+            std::ptr::copy(src, dst, len);
+        }
+    }
+
+    #[inline(never)]
+    #[cfg_attr(miri, allow(dead_code))]
+    unsafe fn move_backward(src: *const u8, dst: *mut u8, len: usize) {
+        #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+        unsafe {
+            let mut remaining = len;
+            while remaining >= 128 {
+                let base = remaining - 128;
+                let v0 = _mm256_loadu_si256(src.add(base) as *const __m256i);
+                let v1 = _mm256_loadu_si256(src.add(base + 32) as *const __m256i);
+                let v2 = _mm256_loadu_si256(src.add(base + 64) as *const __m256i);
+                let v3 = _mm256_loadu_si256(src.add(base + 96) as *const __m256i);
+                
+                _mm256_storeu_si256(dst.add(base) as *mut __m256i, v0);
+                _mm256_storeu_si256(dst.add(base + 32) as *mut __m256i, v1);
+                _mm256_storeu_si256(dst.add(base + 64) as *mut __m256i, v2);
+                _mm256_storeu_si256(dst.add(base + 96) as *mut __m256i, v3);
+                
+                remaining = base;
+            }
+            
+            while remaining >= 32 {
+                let base = remaining - 32;
+                let v = _mm256_loadu_si256(src.add(base) as *const __m256i);
+                _mm256_storeu_si256(dst.add(base) as *mut __m256i, v);
+                remaining = base;
+            }
+            
+            if remaining > 0 {
+                Self::move_small(src, dst, remaining);
+            }
+        }
+        
+        #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+        unsafe {
+            let mut remaining = len;
+            while remaining >= 64 {
+                let base = remaining - 64;
+                let v0 = v128_load(src.add(base) as *const v128);
+                let v1 = v128_load(src.add(base + 16) as *const v128);
+                let v2 = v128_load(src.add(base + 32) as *const v128);
+                let v3 = v128_load(src.add(base + 48) as *const v128);
+                
+                v128_store(dst.add(base) as *mut v128, v0);
+                v128_store(dst.add(base + 16) as *mut v128, v1);
+                v128_store(dst.add(base + 32) as *mut v128, v2);
+                v128_store(dst.add(base + 48) as *mut v128, v3);
+                
+                remaining = base;
+            }
+            
+            while remaining >= 16 {
+                let base = remaining - 16;
+                let v = v128_load(src.add(base) as *const v128);
+                v128_store(dst.add(base) as *mut v128, v);
+                remaining = base;
+            }
+            
+            if remaining > 0 {
+                Self::move_small(src, dst, remaining);
+            }
+        }
+        
+        #[cfg(not(any(
+            all(target_arch = "x86_64", target_feature = "avx2"),
+            all(target_arch = "wasm32", target_feature = "simd128")
+        )))]
+        unsafe {
+            std::ptr::copy(src, dst, len);
+        }
+    }
+    
+    /// # Safety
+    /// Every non-null handle must resolve against `base` to memory valid for `len` bytes, and
+    /// each source/destination pair must not overlap.
+    pub unsafe fn bulk_copy_optimized(base: *mut u8, operations: &[(MemoryHandle, MemoryHandle, usize)]) {
         if operations.is_empty() {
             return;
         }
@@ -347,102 +1112,450 @@ impl SIMDOps {
                 continue;
             }
             
-            let src_ptr = src.to_ptr();
-            let dst_ptr = dst.to_ptr();
+            let src_ptr = src.resolve(base);
+            let dst_ptr = dst.resolve(base);
             
             if !src_ptr.is_null() && !dst_ptr.is_null() {
-                Self::fast_copy(src_ptr, dst_ptr, len);
+                unsafe { Self::fast_copy(src_ptr, dst_ptr, len); }
             }
         }
     }
 }
 
+// ================================
+// === CACHE LINE PADDING ===
+// ================================
+
+// Gives a hot atomic a cache line of its own, so writes to it stop invalidating the
+// neighbours it would otherwise share the line with (64 = CACHE_LINE_SIZE)
+#[repr(align(64))]
+#[derive(Default)]
+struct CachePadded<T>(T);
+
+impl<T> CachePadded<T> {
+    const fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> std::ops::Deref for CachePadded<T> {
+    type Target = T;
+    
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+// Counters bumped by every allocation or free are split over this many padded shards
+const STAT_SHARDS: usize = 8;
+
+static NEXT_STAT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Threads are spread over the shards round-robin, standing in for per-core shards
+    static STAT_SHARD: usize = NEXT_STAT_SHARD.fetch_add(1, Ordering::Relaxed) % STAT_SHARDS;
+}
+
+// One thread's slice of a tier's hot counters, all on one cache line so an allocation or a
+// free dirties a single line. Lives in TierCounters; the arena's live counts are read
+// through LockFreeArena::allocated() and friends.
+#[derive(Default)]
+struct StatShard {
+    allocated: AtomicUsize,
+    allocation_count: AtomicUsize,
+    total_allocated: AtomicUsize,
+    allocations: AtomicUsize,
+    frees: AtomicUsize,
+    bytes_allocated: AtomicUsize,
+    bytes_freed: AtomicUsize,
+}
+
+type StatField = fn(&StatShard) -> &AtomicUsize;
+
+// One counter across all shards, used like an AtomicUsize. A shard wraps below zero when a
+// free lands on another shard than its allocation did; the wrapping sum is still exact.
+// `load` is not a snapshot while other threads are writing. Without a field the counter
+// ignores writes and reads 0 (see report_only).
+#[derive(Clone, Copy)]
+struct ShardedCounter<'a> {
+    shards: &'a [CachePadded<StatShard>; STAT_SHARDS],
+    field: Option<StatField>,
+}
+
+impl ShardedCounter<'_> {
+    #[inline(always)]
+    fn fetch_add(&self, value: usize, order: Ordering) {
+        if let Some(field) = self.field {
+            field(&self.shards[STAT_SHARD.with(|shard| *shard)]).fetch_add(value, order);
+        }
+    }
+    
+    #[inline(always)]
+    fn fetch_sub(&self, value: usize, order: Ordering) {
+        if let Some(field) = self.field {
+            field(&self.shards[STAT_SHARD.with(|shard| *shard)]).fetch_sub(value, order);
+        }
+    }
+    
+    fn load(&self, order: Ordering) -> usize {
+        self.field.map_or(0, |field| self.shards.iter().fold(0, |sum, shard| sum.wrapping_add(field(shard).load(order))))
+    }
+    
+    // For resets and restores, which run without concurrent writers
+    fn store(&self, value: usize, order: Ordering) {
+        let Some(field) = self.field else { return };
+        for (index, shard) in self.shards.iter().enumerate() {
+            field(shard).store(if index == 0 { value } else { 0 }, order);
+        }
+    }
+}
+
+// Counters nothing but stats reports read. The minimal-stats feature compiles them out,
+// leaving allocations to update only the live byte count and the allocation clock, which
+// pressure watermarks, marks and the quarantine depend on.
+#[inline(always)]
+fn report_only(field: StatField) -> Option<StatField> {
+    (!cfg!(feature = "minimal-stats")).then_some(field)
+}
+
 // ================================
 // === LOCK-FREE ARENA ALLOCATOR ===
 // ================================
 
 #[repr(C)]
 struct FreeNode {
-    next: *mut FreeNode,
+    next: u32,  // Slot of the next node (see TaggedHead), so a stale read is just a number
     size: usize,
 }
 
+// Freelist head packed as `tag << 32 | slot`. The slot is the node's arena-local offset in
+// SIMD_ALIGNMENT units plus one (0 = empty), which fits in 32 bits under the 4GB memory
+// limit. Every successful update bumps the tag, so a pop whose head was popped and pushed
+// back in the meantime fails its CAS instead of installing a stale `next` (ABA).
+#[derive(Default)]
+struct TaggedHead(AtomicU64);
+
+impl TaggedHead {
+    const EMPTY: u32 = 0;
+    
+    #[inline(always)]
+    fn pack(tag: u32, slot: u32) -> u64 {
+        ((tag as u64) << 32) | slot as u64
+    }
+    
+    #[inline(always)]
+    fn tag(word: u64) -> u32 {
+        (word >> 32) as u32
+    }
+    
+    #[inline(always)]
+    fn slot(word: u64) -> u32 {
+        word as u32
+    }
+}
+
+// A size class's freelist head and occupancy, which every push and pop updates together.
+// One cache line per class, so traffic on one class doesn't stall the others.
+#[repr(align(64))]
+#[derive(Default)]
+struct FreeClass {
+    head: TaggedHead,
+    bytes: AtomicUsize,
+    blocks: AtomicUsize,
+    largest: AtomicUsize,
+}
+
+// A run of nodes linked up privately by deallocate_batch, spliced onto a freelist in one CAS
+#[derive(Clone, Copy)]
+struct FreeChain {
+    head: *mut FreeNode,
+    tail: *mut FreeNode,
+    bytes: usize,
+    blocks: usize,
+    largest: usize,
+}
+
+impl FreeChain {
+    const EMPTY: Self = Self {
+        head: std::ptr::null_mut(),
+        tail: std::ptr::null_mut(),
+        bytes: 0,
+        blocks: 0,
+        largest: 0,
+    };
+}
+
+// Aligned so the three arenas in a Walloc never share a cache line. Fields written on every
+// allocation or free get lines of their own: the bump head, each size class's freelist and
+// request counters, and the per-thread stat shards. The read-mostly ones pack together.
 #[repr(C, align(64))]
 pub struct LockFreeArena {
+    id: usize,  // Process-unique, keys this arena's thread-local slices
+    handle_base: *mut u8,  // The owning Walloc's handle origin (see Walloc::handle_base)
     base_offset: usize,
     size: AtomicUsize,
-    allocation_head: AtomicUsize,
-    freelists: [AtomicPtr<FreeNode>; 8],
+    allocation_head: CachePadded<AtomicUsize>,
+    reserved: AtomicUsize,  // Headroom at the end of the tier only allocate_reserved may bump into
+    freelists: [FreeClass; SIZE_CLASS_COUNT],
     tier: Tier,
-    allocated: AtomicUsize,
     peak_usage: AtomicUsize,
-    allocation_count: AtomicUsize,
     // Enhanced tracking from WASM version
     high_water_mark: AtomicUsize,
-    total_allocated: AtomicUsize,
+    resident_end: AtomicUsize,  // Furthest bump since the last trim; pages past it are decommitted
+    trim_head: AtomicUsize,  // Head saved by a running trim, which holds the real one at capacity; usize::MAX otherwise
+    // Incremental compaction bookkeeping
+    compaction_limit: AtomicUsize,
+    epoch: AtomicUsize,
+    histogram: SizeHistogram,
+    latency: LatencyRecorder,
+    size_classes: [usize; SIZE_CLASS_COUNT],
+    class_counters: SizeClassCounters,
+    counters: TierCounters,  // Also holds the sharded live counts behind allocated() and friends
+    // Tasks parked in allocate_async until space comes back
+    waiters: Mutex<Vec<Waker>>,
+    has_waiters: AtomicBool,
+    thread_slices: ThreadSlices,
+    free_queues: FreeQueues,
+    combining: Combining,
+    slabs: ClassSlabs,
+    restores: Mutex<RestoreLog>,
+    // Debug aid: freed blocks stay poisoned and out of circulation for this many allocations
+    quarantine_allocations: AtomicUsize,
+    quarantine: Mutex<Quarantine>,
+    #[cfg(feature = "track-callers")]
+    call_sites: Mutex<HashMap<usize, CallSite>>,
+    // Attribution labels from allocate_with_opts: global offset -> (aligned size, tag)
+    tags: Mutex<HashMap<usize, (usize, String)>>,
+    // Requested size of every live block by global offset, which holds checked accesses to
+    // the block they start in (see Walloc::check_range)
+    blocks: Mutex<std::collections::BTreeMap<usize, usize>>,
+    // Local end -> size of blocks put on the freelists, so trim_tail can find those ending at
+    // the head without walking every list. Blocks taken off by other paths leave stale
+    // entries, which trim_tail checks against the lists before trusting.
+    parked: Mutex<std::collections::BTreeMap<usize, usize>>,
+    // Known-free (start, end) local ranges under compaction_limit while a compaction runs
+    compaction_free: Mutex<Vec<(usize, usize)>>,
+    has_tags: AtomicBool,
 }
 
 unsafe impl Send for LockFreeArena {}
 unsafe impl Sync for LockFreeArena {}
 
-#[inline(always)]
-fn size_class_for(size: usize) -> usize {
-    (size.max(32).trailing_zeros() as usize).saturating_sub(5).min(7)
+#[derive(Clone, Debug, PartialEq)]
+pub struct FragmentationStats {
+    pub freelist_bytes: Vec<usize>,  // Bytes parked in each size-class freelist
+    pub freelist_blocks: Vec<usize>, // Blocks parked in each size-class freelist
+    pub free_bytes: usize,           // Sum of freelist_bytes
+    pub tail_free: usize,            // Never-allocated space past the bump head
+    pub largest_free_block: usize,
+    pub fragmentation: f64,          // free_bytes / bump head
+}
+
+// Freed bytes are filled with this while quarantined
+pub const QUARANTINE_POISON: u8 = 0xDD;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuarantineStats {
+    pub blocks: usize,
+    pub bytes: usize,
+    pub violations: usize,  // Released blocks whose poison had been overwritten, i.e. writes after free
+}
+
+#[derive(Clone, Copy)]
+struct QuarantinedBlock {
+    local_offset: usize,
+    size: usize,
+    release_at: usize,  // Allocation count from which the block may be recycled
+    epoch: usize,
+}
+
+#[derive(Clone, Default)]
+struct Quarantine {
+    blocks: std::collections::VecDeque<QuarantinedBlock>,
+    bytes: usize,
+    violations: usize,
 }
 
 impl LockFreeArena {
-    pub fn new(base: *mut u8, size: usize, tier: Tier, memory_base: *mut u8) -> Self {
+    pub fn new(base: *mut u8, size: usize, tier: Tier, handle_base: *mut u8) -> Self {
         let aligned_base = {
             let offset = (base as usize + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
             offset as *mut u8
         };
         let adj_size = size.saturating_sub((aligned_base as usize) - (base as usize));
 
-        let base_offset = unsafe { aligned_base.offset_from(memory_base) as usize };
+        let base_offset = (aligned_base as usize).wrapping_sub(handle_base as usize);
+        asan_poison(aligned_base, adj_size);
 
         Self {
+            id: NEXT_ARENA_ID.fetch_add(1, Ordering::Relaxed),
+            handle_base,
             base_offset,
             size: AtomicUsize::new(adj_size),
-            allocation_head: AtomicUsize::new(0),
+            allocation_head: CachePadded::new(AtomicUsize::new(0)),
+            reserved: AtomicUsize::new(0),
             freelists: Default::default(),
             tier,
-            allocated: AtomicUsize::new(0),
             peak_usage: AtomicUsize::new(0),
-            allocation_count: AtomicUsize::new(0),
             high_water_mark: AtomicUsize::new(0),
-            total_allocated: AtomicUsize::new(0),
+            resident_end: AtomicUsize::new(0),
+            trim_head: AtomicUsize::new(usize::MAX),
+            compaction_limit: AtomicUsize::new(0),
+            epoch: AtomicUsize::new(0),
+            histogram: SizeHistogram::new(),
+            latency: LatencyRecorder::new(),
+            size_classes: DEFAULT_SIZE_CLASSES,
+            class_counters: SizeClassCounters::default(),
+            counters: TierCounters::default(),
+            waiters: Mutex::new(Vec::new()),
+            has_waiters: AtomicBool::new(false),
+            thread_slices: ThreadSlices::default(),
+            free_queues: FreeQueues::default(),
+            combining: Combining::new(),
+            slabs: ClassSlabs::default(),
+            restores: Mutex::new(RestoreLog::default()),
+            quarantine_allocations: AtomicUsize::new(0),
+            quarantine: Mutex::new(Quarantine::default()),
+            #[cfg(feature = "track-callers")]
+            call_sites: Mutex::new(HashMap::new()),
+            tags: Mutex::new(HashMap::new()),
+            blocks: Mutex::new(Default::default()),
+            parked: Mutex::new(Default::default()),
+            has_tags: AtomicBool::new(false),
+            compaction_free: Mutex::new(Vec::new()),
         }
     }
     
     #[inline(always)]
     pub fn allocate(&self, size: usize) -> Option<usize> {
+        let offset = if self.latency.enabled() {
+            let start = clock_ns();
+            let (offset, path) = self.allocate_by_path(size)?;
+            self.latency.record(path, clock_ns().saturating_sub(start));
+            offset
+        } else {
+            self.allocate_by_path(size)?.0
+        };
+        self.record_block(offset, size);
+        Some(offset)
+    }
+    
+    #[inline(always)]
+    fn allocate_by_path(&self, size: usize) -> Option<(usize, LatencyPath)> {
         let aligned_size = self.align_size(size);
         
-        let size_class = size_class_for(aligned_size);
-        if size_class < 8 {
-            let freelist = &self.freelists[size_class];
-            let head = freelist.load(Ordering::Acquire);
-            
-            if !head.is_null() {
-                let next = unsafe { (*head).next };
-                if freelist.compare_exchange_weak(
-                    head, next, Ordering::Release, Ordering::Acquire
-                ).is_ok() {
-                    #[cfg(target_arch = "wasm32")]
-                    return Some(head as usize);
-                    
-                    #[cfg(not(target_arch = "wasm32"))]
-                    return Some(unsafe { (head as *const u8).offset_from(GLOBAL_MEMORY_BASE) as usize });
-                }
+        let slice_size = self.thread_slices.slice_size.load(Ordering::Relaxed);
+        if slice_size > 0 && aligned_size <= slice_size / 2
+            && let Some(offset) = self.allocate_from_slices(size, aligned_size, slice_size)
+        {
+            self.histogram.record(size);
+            return Some((offset, LatencyPath::Slice));
+        }
+        
+        if self.free_queues.has_pending.load(Ordering::Relaxed) {
+            self.drain_free_queues();
+        }
+        
+        // The request's own class first, then the first larger class with a fitting head.
+        // Blocks are split so only `aligned_size` leaves the freelists. Slab classes stick
+        // to their own freelist so they don't pull blocks out of other classes' slabs.
+        let own_class = self.size_class_for(aligned_size);
+        let slab = self.uses_slab(own_class, aligned_size);
+        let searched = if slab { own_class + 1 } else { SIZE_CLASS_COUNT };
+        let recycled = (own_class..searched).find_map(|size_class| {
+            self.pop_free(size_class, aligned_size).map(|(head, node_size)| (size_class, head, node_size))
+        });
+        self.class_counters.record(own_class, recycled.map(|(size_class, _, _)| size_class));
+        
+        if let Some((size_class, head, node_size)) = recycled {
+            self.parked.lock().unwrap().remove(&(head as usize - self.base_ptr() as usize + node_size));
+            self.freelists[size_class].bytes.fetch_sub(node_size, Ordering::Relaxed);
+            if self.freelists[size_class].blocks.fetch_sub(1, Ordering::Relaxed) == 1 {
+                self.freelists[size_class].largest.store(0, Ordering::Relaxed);
             }
+            
+            // Block sizes are multiples of the tier alignment, so the tail is a valid block
+            let remainder = node_size - aligned_size;
+            if remainder > 0 {
+                self.park_block(unsafe { (head as *mut u8).add(aligned_size) } as *mut FreeNode, remainder);
+            }
+            
+            self.allocated().fetch_add(aligned_size, Ordering::Relaxed);
+            self.allocation_count().fetch_add(1, Ordering::Relaxed);
+            self.histogram.record(size);
+            self.counters.record_allocation(size);
+            asan_unpoison(head as *const u8, size);
+            
+            return Some((MemoryHandle::from_ptr(head as *mut u8, self.handle_base).offset(), LatencyPath::Freelist));
         }
         
+        if slab && let Some(offset) = self.allocate_from_slab(size, aligned_size, own_class) {
+            return Some((offset, LatencyPath::Slab));
+        }
+        
+        self.bump(size, aligned_size, self.regular_limit()).map(|offset| (offset, LatencyPath::Bump))
+    }
+    
+    // Live bytes and blocks, and bytes ever handed out (see StatShard)
+    #[inline(always)]
+    fn allocated(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.counters.shards, field: Some(|shard| &shard.allocated) }
+    }
+    
+    #[inline(always)]
+    fn allocation_count(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.counters.shards, field: report_only(|shard| &shard.allocation_count) }
+    }
+    
+    #[inline(always)]
+    fn total_allocated(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.counters.shards, field: report_only(|shard| &shard.total_allocated) }
+    }
+    
+    // The marks only rise, and rarely, so look before the read-modify-write takes the line
+    #[inline(always)]
+    fn raise_watermarks(&self, head: usize) {
+        for mark in [&self.peak_usage, &self.high_water_mark, &self.resident_end] {
+            if mark.load(Ordering::Relaxed) < head {
+                mark.fetch_max(head, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    // Regular allocations may bump up to here; the rest of the tier is the emergency reserve
+    #[inline(always)]
+    fn regular_limit(&self) -> usize {
+        self.size.load(Ordering::Relaxed).saturating_sub(self.reserved.load(Ordering::Relaxed))
+    }
+    
+    // Serves from the reserve only once regular allocation has failed
+    pub fn allocate_reserved(&self, size: usize) -> Option<usize> {
+        self.allocate(size).or_else(|| {
+            let offset = self.bump(size, self.align_size(size), self.capacity())?;
+            self.record_block(offset, size);
+            Some(offset)
+        })
+    }
+    
+    // Reserve bytes not yet handed out by allocate_reserved
+    pub fn reserve_remaining(&self) -> usize {
+        let capacity = self.capacity();
+        self.reserved.load(Ordering::Relaxed).min(capacity.saturating_sub(self.usage()))
+    }
+    
+    #[inline(always)]
+    fn bump(&self, size: usize, aligned_size: usize, limit: usize) -> Option<usize> {
         let mut arena_offset = self.allocation_head.load(Ordering::Relaxed);
-        let arena_size = self.size.load(Ordering::Relaxed);
+        let mut retries = 0;
         
         loop {
             let new_offset = arena_offset + aligned_size;
-            if new_offset > arena_size {
+            if new_offset > limit {
+                if self.wait_for_trim() {
+                    arena_offset = self.allocation_head.load(Ordering::Relaxed);
+                    continue;
+                }
                 return None;
             }
             
@@ -453,34 +1566,59 @@ impl LockFreeArena {
                 Ordering::Relaxed
             ) {
                 Ok(_) => {
-                    self.allocated.fetch_add(aligned_size, Ordering::Relaxed);
-                    self.allocation_count.fetch_add(1, Ordering::Relaxed);
-                    self.total_allocated.fetch_add(aligned_size, Ordering::Relaxed);
-                    
-                    let current_peak = self.peak_usage.load(Ordering::Relaxed);
-                    if new_offset > current_peak {
-                        let _ = self.peak_usage.compare_exchange_weak(
-                            current_peak, new_offset, 
-                            Ordering::Relaxed, Ordering::Relaxed
-                        );
-                    }
+                    self.allocated().fetch_add(aligned_size, Ordering::Relaxed);
+                    self.allocation_count().fetch_add(1, Ordering::Relaxed);
+                    self.total_allocated().fetch_add(aligned_size, Ordering::Relaxed);
                     
-                    let hwm = self.high_water_mark.load(Ordering::Relaxed);
-                    if new_offset > hwm {
-                        self.high_water_mark.store(new_offset, Ordering::Relaxed);
-                    }
+                    self.raise_watermarks(new_offset);
                     
+                    self.histogram.record(size);
+                    self.counters.record_allocation(size);
+                    self.latency.record_retries(retries);
+                    asan_unpoison(unsafe { self.base_ptr().add(arena_offset) }, size);
                     return Some(self.base_offset + arena_offset);
                 }
-                Err(current) => arena_offset = current,
+                Err(current) => {
+                    arena_offset = current;
+                    retries += 1;
+                }
             }
         }
     }
     
+    // Extend the block at global `offset` to `new_size` if it ends at the bump head, so
+    // nothing has been allocated after it
+    fn grow_in_place(&self, offset: usize, size: usize, new_size: usize) -> bool {
+        let local_offset = offset - self.base_offset;
+        let (aligned_size, new_aligned) = (self.align_size(size), self.align_size(new_size));
+        if new_aligned <= aligned_size {
+            return true;
+        }
+        
+        let new_end = local_offset + new_aligned;
+        if new_end > self.regular_limit() || self.allocation_head.compare_exchange(
+            local_offset + aligned_size, new_end, Ordering::Relaxed, Ordering::Relaxed
+        ).is_err() {
+            return false;
+        }
+        
+        let grown = new_aligned - aligned_size;
+        asan_unpoison(unsafe { self.base_ptr().add(local_offset) }, new_size);
+        self.allocated().fetch_add(grown, Ordering::Relaxed);
+        self.total_allocated().fetch_add(grown, Ordering::Relaxed);
+        self.raise_watermarks(new_end);
+        self.record_block(offset, new_size);
+        #[cfg(feature = "track-callers")]
+        if let Some(site) = self.call_sites.lock().unwrap().get_mut(&offset) {
+            site.size = new_size;
+        }
+        true
+    }
+    
     #[inline(always)]
     fn align_size(&self, size: usize) -> usize {
         let alignment = self.tier.alignment().max(SIMD_ALIGNMENT);
-        (size + alignment - 1) & !(alignment - 1)
+        size.saturating_add(alignment - 1) & !(alignment - 1)
     }
     
     pub fn capacity(&self) -> usize {
@@ -488,19 +1626,281 @@ impl LockFreeArena {
     }
     
     pub fn usage(&self) -> usize {
-        self.allocation_head.load(Ordering::Relaxed)
+        let head = self.allocation_head.load(Ordering::Relaxed);
+        match self.trim_head.load(Ordering::Relaxed) {
+            saved if saved != usize::MAX && head >= saved => saved,
+            _ => head,
+        }
     }
     
-    pub fn base_ptr(&self) -> *mut u8 {
-        #[cfg(target_arch = "wasm32")]
-        { self.base_offset as *mut u8 }
-        
-        #[cfg(not(target_arch = "wasm32"))]
-        { unsafe { GLOBAL_MEMORY_BASE.add(self.base_offset) } }
+    // A trim holds the head at capacity for its decommit call only, so bump paths that hit
+    // the limit meanwhile wait and retry instead of failing. True if there was one to wait for.
+    #[cold]
+    fn wait_for_trim(&self) -> bool {
+        let mut waited = false;
+        while self.trim_head.load(Ordering::Acquire) != usize::MAX {
+            std::thread::yield_now();
+            waited = true;
+        }
+        waited
     }
-
+    
+    #[inline(always)]
+    pub fn contains(&self, handle: MemoryHandle) -> bool {
+        let offset = handle.offset();
+        !handle.is_null() && offset >= self.base_offset &&
+        offset < self.base_offset + self.size.load(Ordering::Relaxed)
+    }
+    
+    #[inline(always)]
+    fn node_at(&self, slot: u32) -> *mut FreeNode {
+        if slot == TaggedHead::EMPTY {
+            return std::ptr::null_mut();
+        }
+        unsafe { self.base_ptr().add((slot as usize - 1) * SIMD_ALIGNMENT) as *mut FreeNode }
+    }
+    
+    #[inline(always)]
+    fn slot_of(&self, node: *mut FreeNode) -> u32 {
+        if node.is_null() {
+            return TaggedHead::EMPTY;
+        }
+        let local_offset = node as usize - self.base_ptr() as usize;
+        debug_assert!(local_offset.is_multiple_of(SIMD_ALIGNMENT), "freelist node is not block aligned");
+        (local_offset / SIMD_ALIGNMENT + 1) as u32
+    }
+    
+    // Pop the head of a class if it is at least `min_size`; retries only on contention
+    fn pop_free(&self, size_class: usize, min_size: usize) -> Option<(*mut FreeNode, usize)> {
+        if self.combining.is_hot(size_class)
+            && let Some(node) = self.combine(size_class, SLOT_POP, min_size)
+        {
+            let node = node as *mut FreeNode;
+            return (!node.is_null()).then(|| (node, unsafe { (*node).size }));
+        }
+        self.pop_free_direct(size_class, min_size)
+    }
+    
+    fn pop_free_direct(&self, size_class: usize, min_size: usize) -> Option<(*mut FreeNode, usize)> {
+        let freelist = &self.freelists[size_class].head.0;
+        let mut word = freelist.load(Ordering::Acquire);
+        let mut retries = 0;
+        loop {
+            let head = self.node_at(TaggedHead::slot(word));
+            if head.is_null() {
+                return None;
+            }
+            
+            // The node may be popped and reused by another thread before the CAS. It stays
+            // mapped arena memory, and the tag makes the CAS fail if that happened.
+            let (next, node_size) = unsafe {
+                (std::ptr::read_volatile(&(*head).next), std::ptr::read_volatile(&(*head).size))
+            };
+            // Classes mix block sizes, so the head may be too small for this request
+            if node_size < min_size {
+                return None;
+            }
+            
+            let new_word = TaggedHead::pack(TaggedHead::tag(word).wrapping_add(1), next);
+            match freelist.compare_exchange_weak(word, new_word, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {
+                    self.latency.record_retries(retries);
+                    self.combining.note_retries(size_class, retries);
+                    return Some((head, node_size));
+                }
+                Err(current) => {
+                    word = current;
+                    retries += 1;
+                }
+            }
+        }
+    }
+    
+    // One CAS per size class with anything to add
+    fn splice_chains(&self, chains: &[FreeChain; SIZE_CLASS_COUNT]) {
+        let base = self.base_ptr() as usize;
+        let mut parked = self.parked.lock().unwrap();
+        for chain in chains {
+            let mut node = chain.head;
+            while !node.is_null() {
+                let size = unsafe { (*node).size };
+                parked.insert(node as usize - base + size, size);
+                node = if node == chain.tail { std::ptr::null_mut() } else { self.node_at(unsafe { (*node).next }) };
+            }
+        }
+        drop(parked);
+        
+        for (size_class, chain) in chains.iter().enumerate() {
+            if chain.head.is_null() {
+                continue;
+            }
+            
+            // Counted before the chain is published, so a pop can never subtract it first
+            self.freelists[size_class].bytes.fetch_add(chain.bytes, Ordering::Relaxed);
+            self.freelists[size_class].blocks.fetch_add(chain.blocks, Ordering::Relaxed);
+            self.freelists[size_class].largest.fetch_max(chain.largest, Ordering::Relaxed);
+            self.push_free(size_class, chain.head, chain.tail);
+        }
+    }
+    
+    // Link `head..=tail` (already chained through `next`) onto a class in one CAS
+    fn push_free(&self, size_class: usize, head: *mut FreeNode, tail: *mut FreeNode) {
+        let freelist = &self.freelists[size_class].head.0;
+        let slot = self.slot_of(head);
+        let mut word = freelist.load(Ordering::Acquire);
+        let mut retries = 0;
+        loop {
+            unsafe { (*tail).next = TaggedHead::slot(word); }
+            let new_word = TaggedHead::pack(TaggedHead::tag(word).wrapping_add(1), slot);
+            match freelist.compare_exchange_weak(word, new_word, Ordering::Release, Ordering::Acquire) {
+                Ok(_) => {
+                    self.combining.note_retries(size_class, retries);
+                    return;
+                }
+                Err(current) => {
+                    word = current;
+                    retries += 1;
+                }
+            }
+        }
+    }
+    
+    #[inline(always)]
+    fn size_class_for(&self, size: usize) -> usize {
+        self.size_classes.iter().position(|&bound| size <= bound).unwrap_or(SIZE_CLASS_COUNT - 1)
+    }
+    
+    // Put a block on the freelist for its size class. The class must match allocate's, or
+    // the block lands on a list that is never searched for its size.
+    fn park_block(&self, node: *mut FreeNode, size: usize) {
+        let size_class = self.size_class_for(size);
+        asan_unpoison(node as *const u8, std::mem::size_of::<FreeNode>());
+        unsafe {
+            std::ptr::write(node, FreeNode {
+                next: TaggedHead::EMPTY,
+                size,
+            });
+        }
+        
+        // Counted before the node is published, so a pop can never subtract it first
+        self.freelists[size_class].bytes.fetch_add(size, Ordering::Relaxed);
+        self.freelists[size_class].blocks.fetch_add(1, Ordering::Relaxed);
+        self.freelists[size_class].largest.fetch_max(size, Ordering::Relaxed);
+        let local_offset = node as usize - self.base_ptr() as usize;
+        self.parked.lock().unwrap().insert(local_offset + size, size);
+        if !(self.combining.is_hot(size_class) && self.combine(size_class, SLOT_PUSH, node as usize).is_some()) {
+            self.push_free(size_class, node, node);
+        }
+    }
+    
+    fn clear_freelists(&self) {
+        for freelist in &self.freelists {
+            // Keep the tag moving so in-flight pops from before the clear cannot succeed
+            let word = freelist.head.0.load(Ordering::SeqCst);
+            freelist.head.0.store(TaggedHead::pack(TaggedHead::tag(word).wrapping_add(1), TaggedHead::EMPTY), Ordering::SeqCst);
+        }
+        for class in 0..self.freelists.len() {
+            self.freelists[class].bytes.store(0, Ordering::SeqCst);
+            self.freelists[class].blocks.store(0, Ordering::SeqCst);
+            self.freelists[class].largest.store(0, Ordering::SeqCst);
+        }
+        for shard in &self.free_queues.shards {
+            shard.0.store(TaggedHead::EMPTY, Ordering::SeqCst);
+        }
+        self.parked.lock().unwrap().clear();
+        for cursor in &self.slabs.cursors {
+            cursor.store(0, Ordering::SeqCst);
+        }
+        self.free_queues.has_pending.store(false, Ordering::SeqCst);
+    }
+    
+    // (size class, handle, block size) for every parked block. The walk follows live node
+    // links, so it is only exact while no other thread is allocating from or freeing to
+    // this arena.
+    fn free_blocks(&self) -> Vec<(usize, MemoryHandle, usize)> {
+        let mut blocks = Vec::new();
+        for (class, freelist) in self.freelists.iter().enumerate() {
+            let mut node = self.node_at(TaggedHead::slot(freelist.head.0.load(Ordering::Acquire)));
+            let mut remaining = self.freelists[class].blocks.load(Ordering::Acquire);
+            while !node.is_null() && remaining > 0 {
+                let (next, size) = unsafe { ((*node).next, (*node).size) };
+                blocks.push((class, MemoryHandle::from_ptr(node as *mut u8, self.handle_base), size));
+                node = self.node_at(next);
+                remaining -= 1;
+            }
+        }
+        blocks
+    }
+    
+    // One relaxed load per class head, cheap enough for every trimmed free
+    #[inline(always)]
+    fn has_parked_blocks(&self) -> bool {
+        self.freelists.iter().any(|class| TaggedHead::slot(class.head.0.load(Ordering::Relaxed)) != TaggedHead::EMPTY)
+    }
+    
+    pub fn free_bytes(&self) -> usize {
+        // A pop that raced clear_freelists can leave a class briefly below zero
+        let bytes = self.freelists.iter().fold(0isize, |sum, class| sum.wrapping_add(class.bytes.load(Ordering::Relaxed) as isize));
+        bytes.max(0) as usize
+    }
+    
+    // Share of the bump region sitting on freelists rather than in live allocations
+    pub fn fragmentation(&self) -> f64 {
+        let usage = self.usage();
+        if usage == 0 {
+            return 0.0;
+        }
+        self.free_bytes().min(usage) as f64 / usage as f64
+    }
+    
+    // Freelist blocks are never coalesced, so the largest contiguous run is either the
+    // untouched tail past the bump head or the biggest single freed block.
+    pub fn fragmentation_stats(&self) -> FragmentationStats {
+        let load = |counter: fn(&FreeClass) -> &AtomicUsize| -> Vec<usize> {
+            self.freelists.iter().map(|class| counter(class).load(Ordering::Relaxed)).collect()
+        };
+        
+        let freelist_bytes = load(|class| &class.bytes);
+        let tail_free = self.capacity().saturating_sub(self.usage());
+        let largest_freed = self.freelists.iter().map(|class| &class.largest)
+            .map(|largest| largest.load(Ordering::Relaxed))
+            .max()
+            .unwrap_or(0);
+        
+        FragmentationStats {
+            free_bytes: self.free_bytes(),
+            freelist_bytes,
+            freelist_blocks: load(|class| &class.blocks),
+            tail_free,
+            largest_free_block: tail_free.max(largest_freed),
+            fragmentation: self.fragmentation(),
+        }
+    }
+    
+    pub fn base_ptr(&self) -> *mut u8 {
+        #[cfg(target_arch = "wasm32")]
+        { self.base_offset as *mut u8 }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        { self.handle_base.wrapping_add(self.base_offset) }
+    }
+
     #[inline(always)]
     pub fn deallocate(&self, handle: MemoryHandle, size: usize) -> bool {
+        if !self.latency.enabled() {
+            return self.free_block(handle, size);
+        }
+        
+        let start = clock_ns();
+        let freed = self.free_block(handle, size);
+        if freed {
+            self.latency.record(LatencyPath::Free, clock_ns().saturating_sub(start));
+        }
+        freed
+    }
+    
+    #[inline(always)]
+    fn free_block(&self, handle: MemoryHandle, size: usize) -> bool {
         if handle.is_null() {
             return false;
         }
@@ -514,43 +1914,407 @@ impl LockFreeArena {
         let local_offset = handle_offset - self.base_offset;
         let aligned_size = self.align_size(size);
         
-        if aligned_size < std::mem::size_of::<FreeNode>() {
-            self.allocated.fetch_sub(aligned_size, Ordering::Relaxed);
-            self.allocation_count.fetch_sub(1, Ordering::Relaxed);
+        #[cfg(feature = "track-callers")]
+        self.call_sites.lock().unwrap().remove(&handle_offset);
+        self.forget_tag(handle_offset);
+        self.forget_block(handle_offset, size);
+        asan_poison(handle.resolve(self.handle_base), aligned_size);
+        
+        // Blocks below an in-flight compaction become room for the slide instead. Putting them
+        // on a freelist would hand them out while assets are being moved over them.
+        if aligned_size < std::mem::size_of::<FreeNode>() ||
+        (local_offset < self.compaction_limit.load(Ordering::Acquire) && self.hold_for_compaction(local_offset, aligned_size)) {
+            self.allocated().fetch_sub(aligned_size, Ordering::Relaxed);
+            self.allocation_count().fetch_sub(1, Ordering::Relaxed);
+            self.counters.record_free(size);
+            walloc_event!(trace, tier = ?self.tier, size, offset = handle_offset, recycled = false, "deallocate");
+            self.wake_waiters();
+            return true;
+        }
+        
+        if self.quarantine_allocations.load(Ordering::Relaxed) > 0 {
+            self.quarantine_block(local_offset, aligned_size);
+            self.allocated().fetch_sub(aligned_size, Ordering::Relaxed);
+            self.allocation_count().fetch_sub(1, Ordering::Relaxed);
+            self.counters.record_free(size);
+            walloc_event!(trace, tier = ?self.tier, size, offset = handle_offset, quarantined = true, "deallocate");
             return true;
         }
         
-        let node_ptr = handle.to_ptr() as *mut FreeNode;
+        if self.free_queues.enabled.load(Ordering::Relaxed) {
+            self.queue_free(handle.resolve(self.handle_base) as *mut FreeNode, aligned_size);
+            self.allocated().fetch_sub(aligned_size, Ordering::Relaxed);
+            self.allocation_count().fetch_sub(1, Ordering::Relaxed);
+            self.counters.record_free(size);
+            walloc_event!(trace, tier = ?self.tier, size, offset = handle_offset, queued = true, "deallocate");
+            return true;
+        }
+        
+        // The topmost block goes back to bump space, which may expose parked blocks below it
+        let trimmed = self.allocation_head.compare_exchange(
+            local_offset + aligned_size, local_offset, Ordering::Relaxed, Ordering::Relaxed
+        ).is_ok();
+        if !trimmed {
+            self.park_block(handle.resolve(self.handle_base) as *mut FreeNode, aligned_size);
+        }
+        self.allocated().fetch_sub(aligned_size, Ordering::Relaxed);
+        self.allocation_count().fetch_sub(1, Ordering::Relaxed);
+        self.counters.record_free(size);
+        walloc_event!(trace, tier = ?self.tier, size, offset = handle_offset, recycled = !trimmed, "deallocate");
+        if trimmed && self.has_parked_blocks() {
+            self.trim_tail();
+        }
+        self.wake_waiters();
+        true
+    }
+    
+    // Give back part of a block that was just allocated, such as allocate_with_opts'
+    // alignment slack. Only the live bytes shrink; the counters still see one allocation
+    // and no free.
+    fn release_slack(&self, handle: MemoryHandle, size: usize) {
+        let local_offset = handle.offset() - self.base_offset;
+        asan_poison(handle.resolve(self.handle_base), size);
+        self.allocated().fetch_sub(size, Ordering::Relaxed);
+        if size < std::mem::size_of::<FreeNode>() ||
+        (local_offset < self.compaction_limit.load(Ordering::Acquire) && self.hold_for_compaction(local_offset, size)) {
+            return;
+        }
         
-        let size_class = (aligned_size.max(8).trailing_zeros() as usize).min(7).saturating_sub(3);
+        let trimmed = self.allocation_head.compare_exchange(
+            local_offset + size, local_offset, Ordering::Relaxed, Ordering::Relaxed
+        ).is_ok();
+        if !trimmed {
+            self.park_block(handle.resolve(self.handle_base) as *mut FreeNode, size);
+        } else if self.has_parked_blocks() {
+            self.trim_tail();
+        }
+        self.wake_waiters();
+    }
+    
+    // Poison a freed block and hold it back instead of parking it. Blocks whose allocation
+    // count has come due are checked for writes after free and then parked.
+    fn quarantine_block(&self, local_offset: usize, size: usize) {
+        let block = unsafe { self.base_ptr().add(local_offset) };
+        asan_unpoison(block, size);
+        unsafe {
+            std::ptr::write_bytes(block, QUARANTINE_POISON, size);
+        }
+        asan_poison(block, size);
+        let allocations = self.counters.allocations().load(Ordering::Relaxed);
+        let mut quarantine = self.quarantine.lock().unwrap();
+        quarantine.blocks.push_back(QuarantinedBlock {
+            local_offset,
+            size,
+            release_at: allocations + self.quarantine_allocations.load(Ordering::Relaxed),
+            epoch: self.epoch.load(Ordering::SeqCst),
+        });
+        quarantine.bytes += size;
+        self.release_quarantine(&mut quarantine, allocations);
+    }
+    
+    // Park quarantined blocks due by `allocations`; usize::MAX releases all of them
+    fn release_quarantine(&self, quarantine: &mut Quarantine, allocations: usize) {
+        let epoch = self.epoch.load(Ordering::SeqCst);
+        while quarantine.blocks.front().is_some_and(|block| block.release_at <= allocations) {
+            let block = quarantine.blocks.pop_front().unwrap();
+            quarantine.bytes -= block.size;
+            // Blocks from before a reset are gone
+            if block.epoch != epoch {
+                continue;
+            }
+            
+            let start = unsafe { self.base_ptr().add(block.local_offset) };
+            asan_unpoison(start, block.size);
+            let bytes = unsafe { std::slice::from_raw_parts(start, block.size) };
+            let overwritten = bytes.iter().any(|&byte| byte != QUARANTINE_POISON);
+            asan_poison(start, block.size);
+            if overwritten {
+                quarantine.violations += 1;
+                walloc_event!(warn, tier = ?self.tier, offset = self.base_offset + block.local_offset, size = block.size, "write after free in quarantined block");
+            }
+            let held = block.local_offset < self.compaction_limit.load(Ordering::Acquire)
+                && self.hold_for_compaction(block.local_offset, block.size);
+            if block.size >= std::mem::size_of::<FreeNode>() && !held {
+                self.park_block(unsafe { self.base_ptr().add(block.local_offset) } as *mut FreeNode, block.size);
+            }
+        }
+    }
+    
+    // Zero disables the quarantine and recycles everything it holds
+    pub fn set_quarantine(&self, allocations: usize) {
+        self.quarantine_allocations.store(allocations, Ordering::Relaxed);
+        if allocations == 0 {
+            self.release_quarantine(&mut self.quarantine.lock().unwrap(), usize::MAX);
+            self.wake_waiters();
+        }
+    }
+    
+    pub fn quarantine_stats(&self) -> QuarantineStats {
+        let quarantine = self.quarantine.lock().unwrap();
+        QuarantineStats { blocks: quarantine.blocks.len(), bytes: quarantine.bytes, violations: quarantine.violations }
+    }
+    
+    // Take every parked block off the freelists, leaving them empty. Concurrent pops of the
+    // old heads fail their CAS on the bumped tag.
+    fn detach_freelists(&self) -> Vec<(*mut FreeNode, usize)> {
+        let mut detached = Vec::new();
+        for size_class in 0..SIZE_CLASS_COUNT {
+            self.detach_class(size_class, &mut detached);
+        }
+        self.parked.lock().unwrap().clear();
+        detached
+    }
+    
+    fn detach_class(&self, size_class: usize, detached: &mut Vec<(*mut FreeNode, usize)>) {
         let freelist = &self.freelists[size_class];
+        let mut word = freelist.head.0.load(Ordering::Acquire);
+        let first = loop {
+            if TaggedHead::slot(word) == TaggedHead::EMPTY {
+                break TaggedHead::EMPTY;
+            }
+            let new_word = TaggedHead::pack(TaggedHead::tag(word).wrapping_add(1), TaggedHead::EMPTY);
+            match freelist.head.0.compare_exchange_weak(word, new_word, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => break TaggedHead::slot(word),
+                Err(current) => word = current,
+            }
+        };
         
-        loop {
-            let current_head = freelist.load(Ordering::Acquire);
+        // The chain is ours now, so its links are stable
+        let mut node = self.node_at(first);
+        while !node.is_null() {
+            let (next, size) = unsafe { ((*node).next, (*node).size) };
+            freelist.bytes.fetch_sub(size, Ordering::Relaxed);
+            if freelist.blocks.fetch_sub(1, Ordering::Relaxed) == 1 {
+                freelist.largest.store(0, Ordering::Relaxed);
+            }
+            detached.push((node, size));
+            node = self.node_at(next);
+        }
+    }
+    
+    // Lower the bump head over parked blocks that end at it, so memory freed in LIFO order
+    // returns to bump space instead of sitting on freelists. The run below the head comes
+    // from the parked index and stops at the first gap; only the classes holding it are
+    // detached to confirm the blocks are still parked, and concurrent allocations just miss
+    // those classes meanwhile. Returns the bytes reclaimed.
+    pub fn trim_tail(&self) -> usize {
+        // Survivors sliding over the region below the head rely on it staying put
+        if self.compaction_limit.load(Ordering::Acquire) != 0 {
+            return 0;
+        }
+        
+        // (local offset, size), topmost first
+        let run: Vec<(usize, usize)> = {
+            let parked = self.parked.lock().unwrap();
+            let mut end = self.usage();
+            std::iter::from_fn(|| {
+                let size = *parked.get(&end)?;
+                end -= size;
+                Some((end, size))
+            }).collect()
+        };
+        if run.is_empty() {
+            return 0;
+        }
+        
+        let mut classes: Vec<_> = run.iter().map(|&(_, size)| self.size_class_for(size)).collect();
+        classes.sort_unstable();
+        classes.dedup();
+        let mut detached = Vec::new();
+        for size_class in classes {
+            self.detach_class(size_class, &mut detached);
+        }
+        let base = self.base_ptr() as usize;
+        let mut candidates: HashMap<usize, usize> = detached.iter()
+            .map(|&(node, size)| (node as usize - base, size))
+            .collect();
+        
+        let mut reclaimed = 0;
+        let mut peeled = 0;
+        let mut stale = None;
+        for &(local_offset, size) in &run {
+            if candidates.get(&local_offset) != Some(&size) {
+                stale = Some(local_offset + size);
+                break;
+            }
+            if self.allocation_head.compare_exchange(
+                local_offset + size, local_offset, Ordering::Relaxed, Ordering::Relaxed
+            ).is_err() {
+                break;
+            }
+            candidates.remove(&local_offset);
+            asan_poison(unsafe { self.base_ptr().add(local_offset) }, std::mem::size_of::<FreeNode>());
+            reclaimed += size;
+            peeled += 1;
+        }
+        
+        let mut parked = self.parked.lock().unwrap();
+        for &(local_offset, size) in &run[..peeled] {
+            parked.remove(&(local_offset + size));
+        }
+        if let Some(end) = stale {
+            parked.remove(&end);
+        }
+        drop(parked);
+        for (node, size) in detached {
+            if candidates.contains_key(&(node as usize - base)) {
+                self.park_block(node, size);
+            }
+        }
+        
+        if reclaimed > 0 {
+            walloc_event!(trace, tier = ?self.tier, reclaimed, "trim_tail");
+        }
+        reclaimed
+    }
+    
+    // Same bookkeeping as deallocate for each block, but nodes are linked into per-class
+    // chains first so every freelist and counter is touched once for the whole batch.
+    // Blocks outside this arena are skipped; returns how many were freed.
+    pub fn deallocate_batch(&self, blocks: &[(MemoryHandle, usize)]) -> usize {
+        if self.quarantine_allocations.load(Ordering::Relaxed) > 0 {
+            return blocks.iter().filter(|&&(handle, size)| self.contains(handle) && self.deallocate(handle, size)).count();
+        }
+        
+        let compaction_limit = self.compaction_limit.load(Ordering::Acquire);
+        let mut chains = [FreeChain::EMPTY; SIZE_CLASS_COUNT];
+        let mut freed = 0;
+        let mut aligned_total = 0;
+        let mut requested_total = 0;
+        let mut batch_end = 0;
+        
+        #[cfg(feature = "track-callers")]
+        let mut call_sites = self.call_sites.lock().unwrap();
+        let mut live_blocks = self.blocks.lock().unwrap();
+        
+        for &(handle, size) in blocks {
+            if !self.contains(handle) {
+                continue;
+            }
+            
+            let local_offset = handle.offset() - self.base_offset;
+            let aligned_size = self.align_size(size);
             
-            unsafe { 
+            #[cfg(feature = "track-callers")]
+            call_sites.remove(&handle.offset());
+            self.forget_tag(handle.offset());
+            if size > 0 {
+                live_blocks.remove(&handle.offset());
+            }
+            
+            freed += 1;
+            aligned_total += aligned_size;
+            requested_total += size;
+            batch_end = batch_end.max(local_offset + aligned_size);
+            asan_poison(handle.resolve(self.handle_base), aligned_size);
+            
+            if aligned_size < std::mem::size_of::<FreeNode>() ||
+            (local_offset < compaction_limit && self.hold_for_compaction(local_offset, aligned_size)) {
+                continue;
+            }
+            
+            let node_ptr = handle.resolve(self.handle_base) as *mut FreeNode;
+            let chain = &mut chains[self.size_class_for(aligned_size)];
+            asan_unpoison(node_ptr as *const u8, std::mem::size_of::<FreeNode>());
+            unsafe {
                 std::ptr::write(node_ptr, FreeNode {
-                    next: current_head,
+                    next: self.slot_of(chain.head),
                     size: aligned_size,
                 });
             }
-            
-            if freelist.compare_exchange_weak(
-                current_head, node_ptr, Ordering::Release, Ordering::Relaxed
-            ).is_ok() {
-                self.allocated.fetch_sub(aligned_size, Ordering::Relaxed);
-                self.allocation_count.fetch_sub(1, Ordering::Relaxed);
-                return true;
+            if chain.tail.is_null() {
+                chain.tail = node_ptr;
+            }
+            chain.head = node_ptr;
+            chain.bytes += aligned_size;
+            chain.blocks += 1;
+            chain.largest = chain.largest.max(aligned_size);
+        }
+        
+        #[cfg(feature = "track-callers")]
+        drop(call_sites);
+        drop(live_blocks);
+        
+        self.splice_chains(&chains);
+        
+        if freed > 0 {
+            self.allocated().fetch_sub(aligned_total, Ordering::Relaxed);
+            self.allocation_count().fetch_sub(freed, Ordering::Relaxed);
+            self.counters.record_frees(freed, requested_total);
+            walloc_event!(trace, tier = ?self.tier, freed, bytes = requested_total, "deallocate_batch");
+            if batch_end == self.usage() {
+                self.trim_tail();
             }
+            self.wake_waiters();
         }
+        
+        freed
     }
     
     pub fn reset(&self) {
+        self.retire_slices();
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        self.compaction_limit.store(0, Ordering::SeqCst);
+        self.compaction_free.lock().unwrap().clear();
         self.allocation_head.store(0, Ordering::SeqCst);
-        for freelist in &self.freelists {
-            freelist.store(std::ptr::null_mut(), Ordering::SeqCst);
+        self.clear_freelists();
+        self.allocated().store(0, Ordering::SeqCst);
+        asan_poison(self.base_ptr(), self.capacity());
+        let mut quarantine = self.quarantine.lock().unwrap();
+        quarantine.blocks.clear();
+        quarantine.bytes = 0;
+        drop(quarantine);
+        
+        #[cfg(feature = "track-callers")]
+        self.call_sites.lock().unwrap().clear();
+        self.retain_tags(|_| false);
+        self.blocks.lock().unwrap().clear();
+        
+        self.wake_waiters();
+    }
+    
+    // Take over another arena's bookkeeping, for Walloc::duplicate once the bytes are
+    // copied. Freelist links are tier-relative slots, so they stay valid in the copy.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn copy_state_from(&mut self, source: &LockFreeArena) {
+        for (ours, theirs) in [
+            (&*self.allocation_head, &*source.allocation_head),
+            (&self.reserved, &source.reserved),
+            (&self.peak_usage, &source.peak_usage),
+            (&self.high_water_mark, &source.high_water_mark),
+            (&self.compaction_limit, &source.compaction_limit),
+        ] {
+            ours.store(theirs.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+        for (ours, theirs) in [
+            (self.allocated(), source.allocated()),
+            (self.allocation_count(), source.allocation_count()),
+            (self.total_allocated(), source.total_allocated()),
+        ] {
+            ours.store(theirs.load(Ordering::SeqCst), Ordering::SeqCst);
+        }
+        self.resident_end.store(source.usage(), Ordering::SeqCst);
+        
+        for class in 0..SIZE_CLASS_COUNT {
+            self.freelists[class].head.0.store(source.freelists[class].head.0.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.freelists[class].bytes.store(source.freelists[class].bytes.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.freelists[class].blocks.store(source.freelists[class].blocks.load(Ordering::SeqCst), Ordering::SeqCst);
+            self.freelists[class].largest.store(source.freelists[class].largest.load(Ordering::SeqCst), Ordering::SeqCst);
         }
-        self.allocated.store(0, Ordering::SeqCst);
+        self.size_classes = source.size_classes;
+        self.quarantine_allocations.store(source.quarantine_allocations.load(Ordering::SeqCst), Ordering::SeqCst);
+        *self.quarantine.get_mut().unwrap() = source.quarantine.lock().unwrap().clone();
+        *self.tags.get_mut().unwrap() = source.tags.lock().unwrap().clone();
+        *self.blocks.get_mut().unwrap() = source.blocks.lock().unwrap().clone();
+        *self.parked.get_mut().unwrap() = source.parked.lock().unwrap().clone();
+        self.has_tags.store(source.has_tags.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+    
+    // Restart peak tracking from the current bump head
+    pub fn reset_peaks(&self) {
+        let usage = self.usage();
+        self.peak_usage.store(usage, Ordering::Relaxed);
+        self.high_water_mark.store(usage, Ordering::Relaxed);
     }
     
     pub fn stats(&self) -> (usize, usize, usize, usize) {
@@ -558,7 +2322,7 @@ impl LockFreeArena {
             self.usage(),
             self.capacity(),
             self.peak_usage.load(Ordering::Relaxed),
-            self.allocated.load(Ordering::Relaxed),
+            self.live_bytes(),
         )
     }
     
@@ -569,823 +2333,10463 @@ impl LockFreeArena {
     
     // Enhanced: Fast compact with preservation
     pub fn fast_compact(&self, preserve_bytes: usize) -> bool {
+        let preserve_bytes = self.align_size(preserve_bytes);
         let current_offset = self.allocation_head.load(Ordering::Relaxed);
         
         if preserve_bytes > current_offset {
             return false;
         }
         
+        self.retire_slices();
+        self.epoch.fetch_add(1, Ordering::SeqCst);
         self.allocation_head.store(preserve_bytes, Ordering::SeqCst);
         
         // Clear freelists as they may point to memory beyond preserve_bytes
-        for freelist in &self.freelists {
-            freelist.store(std::ptr::null_mut(), Ordering::SeqCst);
-        }
+        self.clear_freelists();
         
+        #[cfg(feature = "track-callers")]
+        self.call_sites.lock().unwrap().retain(|&offset, _| offset < self.base_offset + preserve_bytes);
+        self.retain_tags(|offset| offset < self.base_offset + preserve_bytes);
+        self.blocks.lock().unwrap().retain(|&offset, _| offset < self.base_offset + preserve_bytes);
+        
+        self.wake_waiters();
         true
     }
-}
-
-// ================================
-// === SIMPLE ASSET REGISTRY ===
-// ================================
-
-#[derive(Default)]
-pub struct SimpleAssetRegistry {
-    assets: RwLock<HashMap<String, AssetMetadata>>,
-}
-
-impl SimpleAssetRegistry {
-    pub fn new() -> Self {
-        Self {
-            assets: RwLock::new(HashMap::with_capacity(256)),
-        }
-    }
     
-    pub fn insert(&self, key: String, metadata: AssetMetadata) -> bool {
-        let mut assets = self.assets.write().unwrap();
-        assets.insert(key, metadata).is_none()
+    pub fn mark(&self) -> ArenaMark {
+        ArenaMark {
+            restores: self.restores.lock().unwrap().count,
+            tier: self.tier,
+            head: self.usage(),
+            epoch: self.epoch.load(Ordering::SeqCst),
+            frees: self.counters.frees().load(Ordering::SeqCst),
+            slice_carves: self.thread_slices.carves.load(Ordering::SeqCst),
+            allocated: self.allocated().load(Ordering::SeqCst),
+            allocation_count: self.allocation_count().load(Ordering::SeqCst),
+        }
     }
     
-    pub fn get(&self, key: &str) -> Option<AssetMetadata> {
-        let assets = self.assets.read().unwrap();
-        assets.get(key).cloned()
+    // Release everything allocated since `mark`. Refused if anything in the tier was freed
+    // in between (freelists could then hold blocks past the mark) or the arena was reset
+    // or compacted. Returns the number of bump bytes handed back.
+    pub fn rollback(&self, mark: &ArenaMark) -> Result<usize, &'static str> {
+        if mark.tier != self.tier {
+            return Err("Mark belongs to another tier");
+        }
+        if self.epoch.load(Ordering::SeqCst) != mark.epoch {
+            return Err("Arena was reset or compacted since the mark");
+        }
+        if self.counters.frees().load(Ordering::SeqCst) != mark.frees {
+            return Err("Blocks were freed since the mark");
+        }
+        if self.thread_slices.carves.load(Ordering::SeqCst) != mark.slice_carves {
+            return Err("Thread slices or size-class slabs were carved since the mark");
+        }
+        
+        let mut restores = self.restores.lock().unwrap();
+        if restores.unwound_below(mark) {
+            return Err("An older mark was rolled back to since this mark");
+        }
+        
+        let head = self.usage();
+        if head < mark.head {
+            return Err("Allocation head is below the mark");
+        }
+        if self.allocation_head.compare_exchange(head, mark.head, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            return Err("Concurrent allocation during rollback");
+        }
+        restores.record(mark.head);
+        drop(restores);
+        asan_poison(unsafe { self.base_ptr().add(mark.head) }, head - mark.head);
+        
+        // Freelist blocks recycled inside the scope stay out of circulation until the
+        // tier is reset or compacted
+        self.allocated().store(mark.allocated, Ordering::SeqCst);
+        self.allocation_count().store(mark.allocation_count, Ordering::SeqCst);
+        
+        #[cfg(feature = "track-callers")]
+        self.call_sites.lock().unwrap().retain(|&offset, _| offset < self.base_offset + mark.head);
+        self.retain_tags(|offset| offset < self.base_offset + mark.head);
+        self.blocks.lock().unwrap().retain(|&offset, _| offset < self.base_offset + mark.head);
+        
+        self.wake_waiters();
+        Ok(head - mark.head)
     }
     
-    pub fn remove(&self, key: &str) -> bool {
-        let mut assets = self.assets.write().unwrap();
-        assets.remove(key).is_some()
+    fn register_waiter(&self, waker: &Waker) {
+        let mut waiters = self.waiters.lock().unwrap();
+        if !waiters.iter().any(|waiter| waiter.will_wake(waker)) {
+            waiters.push(waker.clone());
+        }
+        self.has_waiters.store(true, Ordering::SeqCst);
     }
     
-    pub fn remove_batch(&self, keys: &[String]) -> usize {
-        let mut assets = self.assets.write().unwrap();
-        let mut count = 0;
-        
-        for key in keys {
-            if assets.remove(key).is_some() {
-                count += 1;
+    // Called wherever space may have been returned to the tier; one flag load when idle
+    fn wake_waiters(&self) {
+        if self.has_waiters.swap(false, Ordering::SeqCst) {
+            let waiters = std::mem::take(&mut *self.waiters.lock().unwrap());
+            for waker in waiters {
+                waker.wake();
             }
         }
-        
-        count
     }
     
-    pub fn clear(&self) {
-        let mut assets = self.assets.write().unwrap();
-        assets.clear();
+    #[cfg(feature = "track-callers")]
+    #[track_caller]
+    fn record_call_site(&self, offset: usize, size: usize) {
+        self.call_sites.lock().unwrap().insert(offset, CallSite {
+            size,
+            location: std::panic::Location::caller(),
+        });
     }
     
-    pub fn len(&self) -> usize {
-        let assets = self.assets.read().unwrap();
-        assets.len()
+    #[cfg(feature = "track-callers")]
+    fn move_call_site(&self, from: usize, to: usize) {
+        let mut sites = self.call_sites.lock().unwrap();
+        if let Some(site) = sites.remove(&from) {
+            sites.insert(to, site);
+        }
     }
     
-    pub fn is_empty(&self) -> bool {
-        let assets = self.assets.read().unwrap();
-        assets.is_empty()
+    fn set_tag(&self, offset: usize, size: usize, tag: String) {
+        self.tags.lock().unwrap().insert(offset, (self.align_size(size), tag));
+        self.has_tags.store(true, Ordering::Relaxed);
     }
     
-    // Enhanced: Get all assets for a tier
-    pub fn get_assets_by_tier(&self, tier: Tier) -> Vec<(String, AssetMetadata)> {
-        let assets = self.assets.read().unwrap();
-        assets.iter()
-            .filter(|(_, metadata)| metadata.tier == tier)
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect()
+    // Tags are rare, so untagged tiers pay one flag load per free
+    #[inline(always)]
+    fn forget_tag(&self, offset: usize) {
+        if self.has_tags.load(Ordering::Relaxed) {
+            self.tags.lock().unwrap().remove(&offset);
+        }
     }
-}
-
-unsafe impl Send for SimpleAssetRegistry {}
-unsafe impl Sync for SimpleAssetRegistry {}
-
-// ================================
-// === PLATFORM STRATEGIES ===
-// ================================
-
-#[cfg(target_arch = "wasm32")]
-pub struct WasmStrategy {
-    initial_pages: AtomicUsize,
-}
-
-#[cfg(target_arch = "wasm32")]
-impl WasmStrategy {
-    pub fn new() -> Self {
-        Self {
-            initial_pages: AtomicUsize::new(core::arch::wasm32::memory_size(0)),
+    
+    fn retain_tags(&self, keep: impl Fn(usize) -> bool) {
+        if self.has_tags.load(Ordering::Relaxed) {
+            self.tags.lock().unwrap().retain(|&offset, _| keep(offset));
         }
     }
     
-    pub fn try_grow(&self, arena: &LockFreeArena, size: usize) -> Option<usize> {
-        let current_usage = arena.usage();
-        let available = arena.capacity().saturating_sub(current_usage);
-        
-        if available >= size {
-            return None;
+    fn move_tag(&self, from: usize, to: usize) {
+        if self.has_tags.load(Ordering::Relaxed) {
+            let mut tags = self.tags.lock().unwrap();
+            if let Some(tag) = tags.remove(&from) {
+                tags.insert(to, tag);
+            }
         }
-        
-        let needed = size - available;
-        let pages_needed = (needed + 65535) / 65536;
-        let actual_pages = pages_needed.max(16);
-        
-        let old_pages = core::arch::wasm32::memory_grow(0, actual_pages);
-        if old_pages == usize::MAX {
-            return None;
+    }
+    
+    // Zero-size blocks share their offset with whatever is allocated next, so they are left
+    // out rather than clobbering (or, once freed, erasing) that block's entry
+    #[inline(always)]
+    fn record_block(&self, offset: usize, size: usize) {
+        if size > 0 {
+            self.blocks.lock().unwrap().insert(offset, size);
         }
-        
-        let new_total_pages = old_pages + actual_pages;
-        let new_total_size = new_total_pages * 65536;
-        let tier_percentage = arena.tier.memory_percentage();
-        let new_tier_size = (new_total_size * tier_percentage) / 100;
-        
+    }
+    
+    #[inline(always)]
+    fn forget_block(&self, offset: usize, size: usize) {
+        if size > 0 {
+            self.blocks.lock().unwrap().remove(&offset);
+        }
+    }
+    
+    fn move_block(&self, from: usize, to: usize) {
+        let mut blocks = self.blocks.lock().unwrap();
+        if let Some(size) = blocks.remove(&from) {
+            blocks.insert(to, size);
+        }
+    }
+    
+    // (start, requested size) of the live block whose bytes include global `offset`
+    fn block_at(&self, offset: usize) -> Option<(usize, usize)> {
+        let blocks = self.blocks.lock().unwrap();
+        let (&start, &size) = blocks.range(..=offset).next_back()?;
+        (offset < start + size).then_some((start, size))
+    }
+
+    // Slide the tier's allocated bytes to `new_base` and take `new_size` as its capacity, for
+    // Walloc::resize_tier. Freelist links and quarantine entries are tier-relative, so only
+    // call sites, tags and block sizes need rewriting.
+    fn relocate(&mut self, new_base: usize, new_size: usize) {
+        self.retire_slices();
+        let used = self.usage();
+        let source = self.base_ptr();
+        let old_base = self.base_offset;
+
+        #[cfg(feature = "track-callers")]
+        {
+            let sites = self.call_sites.get_mut().unwrap();
+            *sites = sites.drain().map(|(offset, site)| (offset - old_base + new_base, site)).collect();
+        }
+        let tags = self.tags.get_mut().unwrap();
+        *tags = tags.drain().map(|(offset, tag)| (offset - old_base + new_base, tag)).collect();
+        let blocks = self.blocks.get_mut().unwrap();
+        *blocks = std::mem::take(blocks).into_iter().map(|(offset, size)| (offset - old_base + new_base, size)).collect();
+
+        self.base_offset = new_base;
+        asan_unpoison(source, used);
+        asan_unpoison(self.base_ptr(), used);
         unsafe {
-            arena.extend_capacity(new_tier_size);
+            SIMDOps::fast_move(source, self.base_ptr(), used);
         }
-        
-        arena.allocate(size)
+        self.size.store(new_size, Ordering::SeqCst);
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        walloc_event!(debug, tier = ?self.tier, base = new_base, size = new_size, "tier relocated");
     }
 }
 
 // ================================
-// === MAIN WALLOC IMPLEMENTATION ===
+// === THREAD SLICES ===
 // ================================
 
-pub struct Walloc {
-    arenas: [LockFreeArena; 3],
-    pub assets: Arc<SimpleAssetRegistry>,
-    http_client: Client,
-    base_url: String,  // Removed RwLock - set before into_arc()
-    memory_base: *mut u8,
-    memory_size: usize,
-    // For MemoryOwner support - keeping RwLock as it's accessed after Arc conversion
-    self_ref: RwLock<Option<Arc<Walloc>>>,
+static NEXT_ARENA_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // (arena id, slice) for every arena this thread allocates from through slices
+    static LOCAL_SLICES: RefCell<Vec<(usize, Arc<ThreadSlice>)>> = const { RefCell::new(Vec::new()) };
+}
+
+// One thread's private run of a tier. Only the owner bumps it until the arena itself is
+// exhausted, after which other threads may steal what is left. The whole slice is counted
+// as allocated when carved; the unused tail is subtracted back out in the stats.
+#[repr(align(64))]
+struct ThreadSlice {
+    region: BumpRegion,
+    epoch: usize,
+    // Owner-side counts only, so the hot path stays off shared cache lines. Thieves count
+    // straight into the arena.
+    allocations: AtomicUsize,
+    requested: AtomicUsize,
+}
+
+impl ThreadSlice {
+    fn remaining(&self) -> usize {
+        self.region.size.saturating_sub(self.region.used())
+    }
     
-    #[cfg(target_arch = "wasm32")]
-    wasm_strategy: WasmStrategy,
+    // Claim whatever is left so nobody allocates from it again; returns the unused bytes
+    fn close(&self) -> usize {
+        self.region.size.saturating_sub(self.region.head.swap(self.region.size, Ordering::AcqRel))
+    }
 }
 
-impl Walloc {
-    pub fn new() -> Result<Self, &'static str> {
-        #[cfg(target_arch = "wasm32")]
-        {
-            let memory_pages = core::arch::wasm32::memory_size(0);
-            let memory_base = 0 as *mut u8;
-            let memory_size = memory_pages * 65536;
-            
-            Self::with_memory(memory_base, memory_size)
+#[derive(Default)]
+struct ThreadSlices {
+    slice_size: AtomicUsize,  // 0 while disabled
+    pool: Mutex<Vec<Arc<ThreadSlice>>>,
+    carves: AtomicUsize,
+}
+
+#[derive(Default)]
+struct SliceTotals {
+    allocations: usize,
+    requested: usize,
+    unused: usize,
+}
+
+impl LockFreeArena {
+    fn set_slice_size(&self, slice_size: usize) {
+        let slice_size = if slice_size == 0 { 0 } else { self.align_size(slice_size) };
+        self.thread_slices.slice_size.store(slice_size, Ordering::Relaxed);
+        if slice_size == 0 {
+            self.retire_slices();
         }
+    }
+    
+    // Own slice, then a freshly carved one, then stealing once the arena has no room left
+    fn allocate_from_slices(&self, size: usize, aligned_size: usize, slice_size: usize) -> Option<usize> {
+        let align = self.tier.alignment().max(SIMD_ALIGNMENT);
+        let epoch = self.epoch.load(Ordering::Acquire);
         
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let memory_size = 64 * 1024 * 1024;
-            let layout = std::alloc::Layout::from_size_align(memory_size, 4096)
-                .map_err(|_| "Invalid memory layout")?;
-            let memory_base = unsafe { std::alloc::alloc(layout) };
-            
-            if memory_base.is_null() {
-                return Err("Failed to allocate memory for Walloc");
+        let local = LOCAL_SLICES.with_borrow_mut(|slices| {
+            if let Some(index) = slices.iter().position(|(id, _)| *id == self.id) {
+                let slice = &slices[index].1;
+                if slice.epoch == epoch
+                    && let Some(handle) = slice.region.allocate(aligned_size, align)
+                {
+                    slice.allocations.fetch_add(1, Ordering::Relaxed);
+                    slice.requested.fetch_add(size, Ordering::Relaxed);
+                    return Some(handle.offset());
+                }
+                let (_, spent) = slices.swap_remove(index);
+                self.retire_slice(&spent);
             }
             
-            Self::with_memory(memory_base, memory_size)
-        }
+            let slice = self.carve_slice(slice_size, epoch)?;
+            let handle = slice.region.allocate(aligned_size, align)?;
+            slice.allocations.fetch_add(1, Ordering::Relaxed);
+            slice.requested.fetch_add(size, Ordering::Relaxed);
+            slices.push((self.id, slice));
+            Some(handle.offset())
+        });
+        
+        local.or_else(|| self.steal_from_slices(size, aligned_size, align))
     }
     
-    fn with_memory(memory_base: *mut u8, memory_size: usize) -> Result<Self, &'static str> {
-        #[cfg(not(target_arch = "wasm32"))]
-        unsafe {
-            GLOBAL_MEMORY_BASE = memory_base;
+    fn carve_slice(&self, slice_size: usize, epoch: usize) -> Option<Arc<ThreadSlice>> {
+        let capacity = self.regular_limit();
+        let mut head = self.allocation_head.load(Ordering::Relaxed);
+        loop {
+            let Some(new_head) = head.checked_add(slice_size).filter(|&end| end <= capacity) else {
+                if self.wait_for_trim() {
+                    head = self.allocation_head.load(Ordering::Relaxed);
+                    continue;
+                }
+                return None;
+            };
+            match self.allocation_head.compare_exchange_weak(head, new_head, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => {
+                    self.allocated().fetch_add(slice_size, Ordering::Relaxed);
+                    self.total_allocated().fetch_add(slice_size, Ordering::Relaxed);
+                    self.raise_watermarks(new_head);
+                    break;
+                }
+                Err(current) => head = current,
+            }
         }
         
-        let aligned_base = (memory_base as usize + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
-        let adjusted_size = memory_size.saturating_sub(aligned_base - memory_base as usize);
+        let slice = Arc::new(ThreadSlice {
+            region: BumpRegion::new(MemoryHandle(self.base_offset + head), slice_size),
+            epoch,
+            allocations: AtomicUsize::new(0),
+            requested: AtomicUsize::new(0),
+        });
+        self.thread_slices.pool.lock().unwrap().push(Arc::clone(&slice));
+        self.thread_slices.carves.fetch_add(1, Ordering::SeqCst);
+        Some(slice)
+    }
+    
+    fn steal_from_slices(&self, size: usize, aligned_size: usize, align: usize) -> Option<usize> {
+        let epoch = self.epoch.load(Ordering::Acquire);
+        let mut pool = self.thread_slices.pool.lock().unwrap();
         
-        let render_size = ((adjusted_size * 50 / 100) + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
-        let scene_size = ((adjusted_size * 30 / 100) + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
-        let entity_size = adjusted_size - render_size - scene_size;
+        let stolen = pool.iter()
+            .filter(|slice| slice.epoch == epoch)
+            .find_map(|slice| slice.region.allocate(aligned_size, align));
         
-        let render_base = aligned_base as *mut u8;
-        let scene_base = unsafe { render_base.add(render_size) };
-        let entity_base = unsafe { scene_base.add(scene_size) };
+        // Slices of exited threads are only referenced by the pool; drop the drained ones
+        let mut orphans = Vec::new();
+        pool.retain(|slice| {
+            let orphaned = Arc::strong_count(slice) == 1 && slice.remaining() < aligned_size;
+            if orphaned {
+                orphans.push(Arc::clone(slice));
+            }
+            !orphaned
+        });
+        drop(pool);
+        for orphan in &orphans {
+            self.fold_slice(orphan);
+        }
         
-        Ok(Self {
-            arenas: [
-                LockFreeArena::new(render_base, render_size, Tier::Top, memory_base),
-                LockFreeArena::new(scene_base, scene_size, Tier::Middle, memory_base),
-                LockFreeArena::new(entity_base, entity_size, Tier::Bottom, memory_base),
-            ],
-            assets: Arc::new(SimpleAssetRegistry::new()),
-            http_client: Client::new(),
-            base_url: String::new(),
-            memory_base,
-            memory_size,
-            self_ref: RwLock::new(None),
-            
-            #[cfg(target_arch = "wasm32")]
-            wasm_strategy: WasmStrategy::new(),
+        let handle = stolen?;
+        self.allocation_count().fetch_add(1, Ordering::Relaxed);
+        self.counters.record_allocation(size);
+        walloc_event!(trace, tier = ?self.tier, size, offset = handle.offset(), "stole from thread slice");
+        Some(handle.offset())
+    }
+    
+    // Move a slice's counts into the arena's own counters and give up its unused tail
+    fn fold_slice(&self, slice: &ThreadSlice) {
+        let unused = slice.close();
+        let allocations = slice.allocations.load(Ordering::Relaxed);
+        self.allocated().fetch_sub(unused, Ordering::Relaxed);
+        self.allocation_count().fetch_add(allocations, Ordering::Relaxed);
+        self.counters.allocations().fetch_add(allocations, Ordering::Relaxed);
+        self.counters.bytes_allocated().fetch_add(slice.requested.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+    
+    fn retire_slice(&self, slice: &Arc<ThreadSlice>) {
+        let mut pool = self.thread_slices.pool.lock().unwrap();
+        if let Some(index) = pool.iter().position(|pooled| Arc::ptr_eq(pooled, slice)) {
+            pool.swap_remove(index);
+            drop(pool);
+            self.fold_slice(slice);
+        }
+    }
+    
+    // Called before anything that invalidates slices wholesale (reset, compaction)
+    fn retire_slices(&self) {
+        let slices = std::mem::take(&mut *self.thread_slices.pool.lock().unwrap());
+        for slice in &slices {
+            self.fold_slice(slice);
+        }
+    }
+    
+    fn slice_totals(&self) -> SliceTotals {
+        let pool = self.thread_slices.pool.lock().unwrap();
+        pool.iter().fold(SliceTotals::default(), |mut totals, slice| {
+            totals.allocations += slice.allocations.load(Ordering::Relaxed);
+            totals.requested += slice.requested.load(Ordering::Relaxed);
+            totals.unused += slice.remaining();
+            totals
         })
     }
     
-    // Set self reference after construction for MemoryOwner support
-    pub fn into_arc(self) -> Arc<Self> {
-        let arc = Arc::new(self);
-        // Set self reference in a thread-safe way
-        {
-            let mut self_ref = arc.self_ref.write().unwrap();
-            *self_ref = Some(Arc::clone(&arc));
+    // Live bytes with the unused tails of open thread slices taken back out
+    fn live_bytes(&self) -> usize {
+        let allocated = self.allocated().load(Ordering::Relaxed);
+        if self.thread_slices.slice_size.load(Ordering::Relaxed) == 0 {
+            return allocated;
         }
-        arc
+        allocated.saturating_sub(self.slice_totals().unused)
     }
     
-    // Builder method to set base URL before converting to Arc
-    pub fn with_base_url(mut self, url: String) -> Self {
-        self.base_url = url;
-        self
+    fn live_allocations(&self) -> usize {
+        let count = self.allocation_count().load(Ordering::Relaxed);
+        if self.thread_slices.slice_size.load(Ordering::Relaxed) == 0 {
+            return count;
+        }
+        count.wrapping_add(self.slice_totals().allocations)
+    }
+}
+
+// ================================
+// === FREE QUEUES ===
+// ================================
+
+const FREE_QUEUE_SHARDS: usize = 8;
+
+static NEXT_FREE_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // Freeing threads are spread over the shards round-robin
+    static FREE_SHARD: usize = NEXT_FREE_SHARD.fetch_add(1, Ordering::Relaxed) % FREE_QUEUE_SHARDS;
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct FreeShard(AtomicU32);  // Slot of the newest queued FreeNode, TaggedHead::EMPTY if none
+
+// When enabled, deallocate pushes onto one of several per-arena shards instead of CAS-ing the
+// shared freelists, so threads freeing at once mostly touch different cache lines. Shards are
+// push-only stacks emptied by swapping out the whole chain, so there is no ABA to tag against.
+// Allocators drain every shard into the freelists with one CAS per size class.
+#[derive(Default)]
+struct FreeQueues {
+    enabled: AtomicBool,
+    shards: [FreeShard; FREE_QUEUE_SHARDS],
+    has_pending: AtomicBool,
+}
+
+impl LockFreeArena {
+    fn set_free_queues(&self, enabled: bool) {
+        self.free_queues.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            self.drain_free_queues();
+        }
     }
     
-    // ================================
-    // === ENHANCED ALLOCATION API ===
-    // ================================
+    fn queue_free(&self, node: *mut FreeNode, size: usize) {
+        let shard = &self.free_queues.shards[FREE_SHARD.with(|shard| *shard)].0;
+        let slot = self.slot_of(node);
+        asan_unpoison(node as *const u8, std::mem::size_of::<FreeNode>());
+        let mut head = shard.load(Ordering::SeqCst);
+        loop {
+            unsafe {
+                std::ptr::write(node, FreeNode { next: head, size });
+            }
+            match shard.compare_exchange_weak(head, slot, Ordering::SeqCst, Ordering::SeqCst) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+        
+        // Checked after the push: a drain that already cleared the flag swaps it before
+        // reading the shards, so it sees this block either way
+        if !self.free_queues.has_pending.load(Ordering::SeqCst) {
+            self.free_queues.has_pending.store(true, Ordering::SeqCst);
+        }
+    }
     
-    // Allocate with memory owner tracking
-    pub fn allocate_with_owner(&self, size: usize, tier: Tier) -> Option<(MemoryOwner, MemoryHandle)> {
-        let arena = &self.arenas[tier as usize];
+    // Move every queued block onto the freelists. Returns the blocks moved.
+    fn drain_free_queues(&self) -> usize {
+        if !self.free_queues.has_pending.swap(false, Ordering::SeqCst) {
+            return 0;
+        }
         
-        if let Some(global_offset) = arena.allocate(size) {
-            let handle = MemoryHandle(global_offset);
-            if let Ok(self_ref_guard) = self.self_ref.read() {
-                if let Some(ref self_arc) = *self_ref_guard {
-                    let mut owner = MemoryOwner::new(tier as usize, Arc::downgrade(self_arc));
-                    owner.add_allocation(handle, size);
-                    return Some((owner, handle));
+        let mut chains = [FreeChain::EMPTY; SIZE_CLASS_COUNT];
+        let mut drained = 0;
+        let mut end = 0;
+        for shard in &self.free_queues.shards {
+            let mut node = self.node_at(shard.0.swap(TaggedHead::EMPTY, Ordering::SeqCst));
+            while !node.is_null() {
+                let (next, size) = unsafe { ((*node).next, (*node).size) };
+                let chain = &mut chains[self.size_class_for(size)];
+                unsafe {
+                    (*node).next = self.slot_of(chain.head);
                 }
+                if chain.tail.is_null() {
+                    chain.tail = node;
+                }
+                chain.head = node;
+                chain.bytes += size;
+                chain.blocks += 1;
+                chain.largest = chain.largest.max(size);
+                
+                end = end.max(node as usize - self.base_ptr() as usize + size);
+                drained += 1;
+                node = self.node_at(next);
             }
         }
         
-        None
+        self.splice_chains(&chains);
+        if drained > 0 {
+            walloc_event!(trace, tier = ?self.tier, drained, "drained free queues");
+            if end == self.usage() {
+                self.trim_tail();
+            }
+            self.wake_waiters();
+        }
+        drained
+    }
+}
+
+// ================================
+// === FLAT COMBINING ===
+// ================================
+
+// Under heavy contention on one freelist, threads publish their pop or push in a slot and
+// whoever takes the class's combiner lock applies every published request in one pass: all
+// pushes spliced in with a single CAS, then the pops. The freelist head then stays in one
+// core's cache instead of bouncing between all of them. A class only combines for a while
+// after some pop or push needed many CAS retries, so uncontended tiers never pay for it.
+const COMBINING_SLOTS: usize = 16;
+const COMBINING_RETRY_THRESHOLD: usize = 4;
+// Combined operations a hot class stays in combining mode for before going lock-free again
+const COMBINING_WINDOW: usize = 4096;
+// Attempts at the lock (or at seeing our request done) before withdrawing it
+const COMBINING_SPINS: usize = 256;
+
+const SLOT_FREE: usize = 0;
+const SLOT_CLAIMED: usize = 1;
+const SLOT_POP: usize = 2;   // arg: min size; result: node address or 0
+const SLOT_PUSH: usize = 3;  // arg: node address, its FreeNode already written
+const SLOT_BUSY: usize = 4;
+const SLOT_DONE: usize = 5;
+// A published request carries its size class above the op, so a combiner takes it with one CAS
+// that can't match a request for another class
+const SLOT_CLASS_SHIFT: u32 = 8;
+const SLOT_OP_MASK: usize = (1 << SLOT_CLASS_SHIFT) - 1;
+
+static NEXT_COMBINING_SLOT: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COMBINING_SLOT: usize = NEXT_COMBINING_SLOT.fetch_add(1, Ordering::Relaxed) % COMBINING_SLOTS;
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct CombiningSlot {
+    state: AtomicUsize,
+    arg: AtomicUsize,
+    result: AtomicUsize,
+}
+
+struct Combining {
+    threshold: AtomicUsize,  // usize::MAX while disabled; 0 combines every class always
+    slots: [CombiningSlot; COMBINING_SLOTS],
+    locks: [AtomicBool; SIZE_CLASS_COUNT],
+    hot: [AtomicUsize; SIZE_CLASS_COUNT],  // Combined operations left in the window
+    combined: [AtomicUsize; SIZE_CLASS_COUNT],
+}
+
+impl Combining {
+    fn new() -> Self {
+        Self {
+            threshold: AtomicUsize::new(COMBINING_RETRY_THRESHOLD),
+            slots: Default::default(),
+            locks: Default::default(),
+            hot: Default::default(),
+            combined: Default::default(),
+        }
     }
     
     #[inline(always)]
-    pub fn allocate(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
-        let arena = &self.arenas[tier as usize];
-        
-        if let Some(global_offset) = arena.allocate(size) {
-            return Some(MemoryHandle(global_offset));
+    fn is_hot(&self, size_class: usize) -> bool {
+        self.hot[size_class].load(Ordering::Relaxed) > 0 || self.threshold.load(Ordering::Relaxed) == 0
+    }
+    
+    #[inline(always)]
+    fn note_retries(&self, size_class: usize, retries: usize) {
+        if retries > 0 && retries >= self.threshold.load(Ordering::Relaxed) {
+            self.hot[size_class].store(COMBINING_WINDOW, Ordering::Relaxed);
         }
-        
-        #[cfg(target_arch = "wasm32")]
-        {
-            if let Some(global_offset) = self.wasm_strategy.try_grow(arena, size) {
-                return Some(MemoryHandle(global_offset));
+    }
+}
+
+impl LockFreeArena {
+    fn set_combining_threshold(&self, threshold: Option<usize>) {
+        self.combining.threshold.store(threshold.unwrap_or(usize::MAX), Ordering::Relaxed);
+        if threshold.is_none() {
+            for hot in &self.combining.hot {
+                hot.store(0, Ordering::Relaxed);
             }
         }
-        
-        None
     }
     
-    pub fn allocate_batch(&self, requests: &[(usize, Tier)]) -> Vec<Option<MemoryHandle>> {
-        let mut results = Vec::with_capacity(requests.len());
-        
-        let mut tier_groups: [Vec<(usize, usize)>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+    // Publish a request for the class's combiner and wait for its result, combining on this
+    // thread whenever the lock is free. None if this thread's slot was taken or the request
+    // was withdrawn after spinning; the caller then goes lock-free instead.
+    fn combine(&self, size_class: usize, op: usize, arg: usize) -> Option<usize> {
+        let combining = &self.combining;
+        let slot = &combining.slots[COMBINING_SLOT.with(|slot| *slot)];
+        slot.state.compare_exchange(SLOT_FREE, SLOT_CLAIMED, Ordering::Acquire, Ordering::Relaxed).ok()?;
+        slot.arg.store(arg, Ordering::Relaxed);
+        let request = op | (size_class << SLOT_CLASS_SHIFT);
+        slot.state.store(request, Ordering::Release);
         
-        for (idx, &(size, tier)) in requests.iter().enumerate() {
-            tier_groups[tier as usize].push((idx, size));
+        let lock = &combining.locks[size_class];
+        for _ in 0..COMBINING_SPINS {
+            if lock.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                self.apply_combined(size_class);
+                lock.store(false, Ordering::Release);
+            }
+            if slot.state.load(Ordering::Acquire) == SLOT_DONE {
+                let result = slot.result.load(Ordering::Relaxed);
+                slot.state.store(SLOT_FREE, Ordering::Release);
+                return Some(result);
+            }
+            std::hint::spin_loop();
         }
         
-        results.resize(requests.len(), None);
+        // Withdraw unless a combiner has already taken the request
+        if slot.state.compare_exchange(request, SLOT_FREE, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            return None;
+        }
+        while slot.state.load(Ordering::Acquire) != SLOT_DONE {
+            std::hint::spin_loop();
+        }
+        let result = slot.result.load(Ordering::Relaxed);
+        slot.state.store(SLOT_FREE, Ordering::Release);
+        Some(result)
+    }
+    
+    // Called with the class's combiner lock held
+    fn apply_combined(&self, size_class: usize) {
+        let combining = &self.combining;
+        let mut taken = [SLOT_FREE; COMBINING_SLOTS];
+        let mut chain = FreeChain::EMPTY;
         
-        for (tier_idx, group) in tier_groups.iter().enumerate() {
-            let arena = &self.arenas[tier_idx];
+        for (index, slot) in combining.slots.iter().enumerate() {
+            let request = slot.state.load(Ordering::Acquire);
+            let state = request & SLOT_OP_MASK;
+            if !matches!(state, SLOT_POP | SLOT_PUSH) || request >> SLOT_CLASS_SHIFT != size_class {
+                continue;
+            }
+            // Never put back once taken, so an owner whose withdraw fails always sees SLOT_DONE
+            if slot.state.compare_exchange(request, SLOT_BUSY, Ordering::Acquire, Ordering::Relaxed).is_err() {
+                continue;
+            }
+            taken[index] = state;
             
-            for &(original_idx, size) in group {
-                if let Some(global_offset) = arena.allocate(size) {
-                    results[original_idx] = Some(MemoryHandle(global_offset));
+            if state == SLOT_PUSH {
+                let node = slot.arg.load(Ordering::Relaxed) as *mut FreeNode;
+                unsafe {
+                    (*node).next = self.slot_of(chain.head);
                 }
+                if chain.tail.is_null() {
+                    chain.tail = node;
+                }
+                chain.head = node;
             }
         }
         
-        results
+        if !chain.head.is_null() {
+            self.push_free(size_class, chain.head, chain.tail);
+        }
+        let mut applied = 0;
+        for (index, &state) in taken.iter().enumerate() {
+            let slot = &combining.slots[index];
+            match state {
+                SLOT_PUSH => slot.result.store(0, Ordering::Relaxed),
+                SLOT_POP => {
+                    let popped = self.pop_free_direct(size_class, slot.arg.load(Ordering::Relaxed));
+                    slot.result.store(popped.map_or(0, |(node, _)| node as usize), Ordering::Relaxed);
+                }
+                _ => continue,
+            }
+            slot.state.store(SLOT_DONE, Ordering::Release);
+            applied += 1;
+        }
+        
+        if applied > 0 {
+            combining.combined[size_class].fetch_add(applied, Ordering::Relaxed);
+            let _ = combining.hot[size_class].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |hot| Some(hot.saturating_sub(applied)));
+        }
     }
+}
 
-    // Enhanced: Fast compact tier with proper data preservation
-    pub fn fast_compact_tier(&self, tier: Tier, preserve_bytes: usize) -> bool {
-        let arena = &self.arenas[tier as usize];
-        
-        let current_usage = arena.usage();
-        let capacity = arena.capacity();
-        
-        // If we need more space than currently allocated
-        if preserve_bytes > current_usage {
-            // Check if the requested size exceeds our capacity
-            if preserve_bytes > capacity {
-                #[cfg(target_arch = "wasm32")]
-                {
-                    // Get current memory stats
-                    let current_memory = self.get_memory_limit();
-                    let additional_needed = preserve_bytes - capacity;
-                    
-                    // Check if growth would exceed limit
-                    if current_memory + additional_needed > MAX_MEMORY_LIMIT {
-                        return false;
-                    }
-                    
-                    // Preserve existing data if needed
-                    let preserve_data = if current_usage > 0 {
-                        let mut data = Vec::with_capacity(current_usage);
-                        unsafe {
-                            data.set_len(current_usage);
-                            SIMDOps::fast_copy(arena.base_ptr(), data.as_mut_ptr(), current_usage);
-                        }
-                        Some(data)
-                    } else {
-                        None
-                    };
-                    
-                    // Try to grow memory
-                    let pages_needed = (additional_needed + 65535) / 65536;
-                    let grow_result = core::arch::wasm32::memory_grow(0, pages_needed);
-                    
-                    if grow_result == usize::MAX {
-                        return false;
-                    }
-                    
-                    // Calculate new tier size
-                    let new_total_pages = grow_result + pages_needed;
-                    let new_total_size = new_total_pages * 65536;
-                    let tier_percentage = arena.tier.memory_percentage();
-                    let new_tier_size = (new_total_size * tier_percentage) / 100;
-                    
-                    // Extend arena capacity
-                    unsafe {
-                        arena.extend_capacity(new_tier_size);
-                    }
-                    
-                    // Restore preserved data
-                    if let Some(data) = preserve_data {
-                        unsafe {
-                            SIMDOps::fast_copy(data.as_ptr(), arena.base_ptr(), data.len());
-                        }
-                    }
-                    
-                    // Set allocation head to preserve_bytes
-                    arena.allocation_head.store(preserve_bytes, Ordering::SeqCst);
-                    arena.allocated.store(preserve_bytes, Ordering::SeqCst);
-                    
-                    // Clear freelists
-                    for freelist in &arena.freelists {
-                        freelist.store(std::ptr::null_mut(), Ordering::SeqCst);
+// ================================
+// === SIZE CLASS SLABS ===
+// ================================
+
+// With slabs on, every bounded size class bumps through a run of the tier of its own,
+// carved from the shared bump head `slab_size` bytes at a time, so objects of one class sit
+// densely next to each other instead of interleaved with every other size. A full slab is
+// replaced by a fresh carve and whatever it had left goes on the freelists.
+
+#[derive(Default)]
+struct ClassSlabs {
+    slab_size: AtomicUsize,  // 0 while disabled
+    // (next, end) of each class's current slab as tier-relative offsets in SIMD_ALIGNMENT
+    // units; 0 when the class has no slab
+    cursors: [AtomicU64; SIZE_CLASS_COUNT],
+    carved: [AtomicUsize; SIZE_CLASS_COUNT],
+}
+
+impl ClassSlabs {
+    #[inline(always)]
+    fn pack(next: usize, end: usize) -> u64 {
+        ((next / SIMD_ALIGNMENT) as u64) << 32 | (end / SIMD_ALIGNMENT) as u64
+    }
+    
+    #[inline(always)]
+    fn unpack(word: u64) -> (usize, usize) {
+        ((word >> 32) as usize * SIMD_ALIGNMENT, (word & 0xFFFF_FFFF) as usize * SIMD_ALIGNMENT)
+    }
+}
+
+impl LockFreeArena {
+    fn set_slab_size(&self, slab_size: usize) {
+        let slab_size = if slab_size == 0 { 0 } else { self.align_size(slab_size) };
+        self.slabs.slab_size.store(slab_size, Ordering::Relaxed);
+        self.retire_slabs();
+    }
+    
+    // Only bounded classes use slabs, and only for blocks that fit one several times over
+    #[inline(always)]
+    fn uses_slab(&self, size_class: usize, aligned_size: usize) -> bool {
+        let slab_size = self.slabs.slab_size.load(Ordering::Relaxed);
+        slab_size > 0 && self.size_classes[size_class] != usize::MAX && aligned_size <= slab_size / 4
+    }
+    
+    fn allocate_from_slab(&self, size: usize, aligned_size: usize, size_class: usize) -> Option<usize> {
+        let slab_size = self.slabs.slab_size.load(Ordering::Relaxed);
+        let cursor = &self.slabs.cursors[size_class];
+        let mut word = cursor.load(Ordering::Acquire);
+        
+        let local_offset = loop {
+            let (next, end) = ClassSlabs::unpack(word);
+            if end > 0 && next + aligned_size <= end {
+                match cursor.compare_exchange_weak(word, ClassSlabs::pack(next + aligned_size, end), Ordering::AcqRel, Ordering::Acquire) {
+                    Ok(_) => break next,
+                    Err(current) => {
+                        word = current;
+                        continue;
                     }
-                    
-                    return true;
-                }
-                
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    // For non-WASM, we can't grow arena in place
-                    // But we can still preserve data by creating a temporary buffer
-                    return false;
                 }
             }
             
-            // We have enough capacity, just update allocation head
-            arena.allocation_head.store(preserve_bytes, Ordering::SeqCst);
-            arena.allocated.store(preserve_bytes, Ordering::SeqCst);
-            
-            // Clear freelists
-            for freelist in &arena.freelists {
-                freelist.store(std::ptr::null_mut(), Ordering::SeqCst);
+            let start = self.carve_slab(slab_size)?;
+            match cursor.compare_exchange(word, ClassSlabs::pack(start + aligned_size, start + slab_size), Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {
+                    self.slabs.carved[size_class].fetch_add(1, Ordering::Relaxed);
+                    if end > next {
+                        self.park_block(unsafe { self.base_ptr().add(next) } as *mut FreeNode, end - next);
+                    }
+                    break start;
+                }
+                // Another thread replaced the slab first; this carve goes on the freelists whole
+                Err(current) => {
+                    self.park_block(unsafe { self.base_ptr().add(start) } as *mut FreeNode, slab_size);
+                    word = current;
+                }
             }
-            
-            return true;
-        }
+        };
         
-        // Standard case: preserve_bytes <= current_usage
-        // Use arena's fast compact
-        arena.fast_compact(preserve_bytes)
+        self.allocated().fetch_add(aligned_size, Ordering::Relaxed);
+        self.allocation_count().fetch_add(1, Ordering::Relaxed);
+        self.total_allocated().fetch_add(aligned_size, Ordering::Relaxed);
+        self.histogram.record(size);
+        self.counters.record_allocation(size);
+        asan_unpoison(unsafe { self.base_ptr().add(local_offset) }, size);
+        Some(self.base_offset + local_offset)
     }
     
-    // ================================
-    // === DATA OPERATIONS ===
-    // ================================
+    // Claim `slab_size` bytes off the bump head. They only count as allocated once handed out.
+    fn carve_slab(&self, slab_size: usize) -> Option<usize> {
+        let capacity = self.regular_limit();
+        let mut head = self.allocation_head.load(Ordering::Relaxed);
+        loop {
+            let Some(new_head) = head.checked_add(slab_size).filter(|&end| end <= capacity) else {
+                if self.wait_for_trim() {
+                    head = self.allocation_head.load(Ordering::Relaxed);
+                    continue;
+                }
+                return None;
+            };
+            match self.allocation_head.compare_exchange_weak(head, new_head, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => {
+                    self.raise_watermarks(new_head);
+                    // Shared with thread slices so rollback refuses marks taken before the carve
+                    self.thread_slices.carves.fetch_add(1, Ordering::SeqCst);
+                    return Some(head);
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
     
-    #[inline(always)]
-    fn get_memory_limit(&self) -> usize {
-        #[cfg(target_arch = "wasm32")]
-        {
-            core::arch::wasm32::memory_size(0) * 65536
+    // Give every current slab's unused tail back to the freelists
+    fn retire_slabs(&self) {
+        for cursor in &self.slabs.cursors {
+            let (next, end) = ClassSlabs::unpack(cursor.swap(0, Ordering::AcqRel));
+            if end > next {
+                self.park_block(unsafe { self.base_ptr().add(next) } as *mut FreeNode, end - next);
+            }
         }
+    }
+}
+
+// ================================
+// === SIZE CLASSES ===
+// ================================
+
+const SIZE_CLASS_COUNT: usize = 8;
+
+// Class i holds blocks of up to bounds[i] bytes; the last class takes everything larger
+const DEFAULT_SIZE_CLASSES: [usize; SIZE_CLASS_COUNT] = [32, 64, 128, 256, 512, 1024, 2048, usize::MAX];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeClassStats {
+    pub class: usize,
+    pub max_size: Option<usize>,  // None for the catch-all class
+    pub hits: usize,              // Served from this class's own freelist
+    pub borrowed: usize,          // Served by splitting a block from a larger class
+    pub misses: usize,            // No freelist could serve it, so it was bump allocated
+    pub hit_rate: f64,            // (hits + borrowed) / requests, 0 when unused
+    pub free_blocks: usize,
+    pub free_bytes: usize,
+    pub combined: usize,          // Freelist operations applied by a flat-combining pass
+    pub hot: bool,                // Currently routed through flat combining
+    pub slabs: usize,             // Slabs carved for this class (see set_size_class_slabs)
+}
+
+// Counted against the class of the request, not the class that served it. Each class has a
+// cache line to itself, so requests of different sizes don't contend.
+#[repr(align(64))]
+#[derive(Default)]
+struct ClassCounters {
+    hits: AtomicUsize,
+    borrowed: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+#[derive(Default)]
+struct SizeClassCounters {
+    classes: [ClassCounters; SIZE_CLASS_COUNT],
+}
+
+impl SizeClassCounters {
+    #[inline(always)]
+    fn record(&self, own_class: usize, served_by: Option<usize>) {
+        let counters = &self.classes[own_class];
+        let counter = match served_by {
+            Some(class) if class == own_class => &counters.hits,
+            Some(_) => &counters.borrowed,
+            None => &counters.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Validate a user table: 1..=8 ascending, non-zero bounds. Unused slots become catch-alls.
+fn size_class_table(bounds: &[usize]) -> Result<[usize; SIZE_CLASS_COUNT], &'static str> {
+    if bounds.is_empty() || bounds.len() > SIZE_CLASS_COUNT {
+        return Err("Size class table needs between 1 and 8 bounds");
+    }
+    if bounds[0] == 0 || bounds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("Size class bounds must be non-zero and strictly ascending");
+    }
+    
+    let mut table = [usize::MAX; SIZE_CLASS_COUNT];
+    table[..bounds.len()].copy_from_slice(bounds);
+    Ok(table)
+}
+
+impl LockFreeArena {
+    fn size_class_stats(&self) -> Vec<SizeClassStats> {
+        // Classes past the first catch-all can never be selected
+        let used = self.size_classes.iter().position(|&bound| bound == usize::MAX)
+            .map_or(SIZE_CLASS_COUNT, |index| index + 1);
         
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            self.memory_size
+        (0..used).map(|class| {
+            let counters = &self.class_counters.classes[class];
+            let hits = counters.hits.load(Ordering::Relaxed);
+            let borrowed = counters.borrowed.load(Ordering::Relaxed);
+            let misses = counters.misses.load(Ordering::Relaxed);
+            let requests = hits + borrowed + misses;
+            
+            SizeClassStats {
+                class,
+                max_size: Some(self.size_classes[class]).filter(|&bound| bound != usize::MAX),
+                hits,
+                borrowed,
+                misses,
+                hit_rate: if requests == 0 { 0.0 } else { (hits + borrowed) as f64 / requests as f64 },
+                free_blocks: self.freelists[class].blocks.load(Ordering::Relaxed),
+                free_bytes: self.freelists[class].bytes.load(Ordering::Relaxed),
+                combined: self.combining.combined[class].load(Ordering::Relaxed),
+                hot: self.combining.is_hot(class),
+                slabs: self.slabs.carved[class].load(Ordering::Relaxed),
+            }
+        }).collect()
+    }
+}
+
+// ================================
+// === ALLOCATION HISTOGRAM ===
+// ================================
+
+// Power-of-two buckets: bucket i counts requests in (2^(i-1), 2^i]
+const HISTOGRAM_BUCKETS: usize = 33;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HistogramBucket {
+    pub max_size: usize,
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllocationHistogram {
+    pub tier: Tier,
+    pub buckets: Vec<HistogramBucket>, // Non-empty buckets only, smallest first
+    pub total_count: usize,
+    pub total_bytes: usize,
+}
+
+// Opt-in because it adds two atomic adds to every allocation
+struct SizeHistogram {
+    enabled: AtomicBool,
+    counts: [AtomicUsize; HISTOGRAM_BUCKETS],
+    bytes: [AtomicUsize; HISTOGRAM_BUCKETS],
+}
+
+impl SizeHistogram {
+    fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            counts: std::array::from_fn(|_| AtomicUsize::new(0)),
+            bytes: std::array::from_fn(|_| AtomicUsize::new(0)),
         }
     }
     
-    pub fn write_data(&self, handle: MemoryHandle, data: &[u8]) -> Result<(), &'static str> {
-        if handle.is_null() {
-            return Err("Memory handle is null");
-        }
-        
-        let end_offset = handle.offset().saturating_add(data.len());
-        if end_offset > self.get_memory_limit() {
-            return Err("Memory access out of bounds");
+    #[inline(always)]
+    fn record(&self, size: usize) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return;
         }
         
-        unsafe {
-            SIMDOps::fast_copy(data.as_ptr(), handle.to_ptr(), data.len());
-        }
-        Ok(())
+        let bucket = if size <= 1 {
+            0
+        } else {
+            ((usize::BITS - (size - 1).leading_zeros()) as usize).min(HISTOGRAM_BUCKETS - 1)
+        };
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.bytes[bucket].fetch_add(size, Ordering::Relaxed);
     }
     
-    pub fn read_data(&self, handle: MemoryHandle, length: usize) -> Option<Vec<u8>> {
-        if handle.is_null() || handle.offset().saturating_add(length) > self.get_memory_limit() {
-            return None;
-        }
+    fn snapshot(&self, tier: Tier) -> AllocationHistogram {
+        let buckets: Vec<_> = (0..HISTOGRAM_BUCKETS)
+            .map(|bucket| HistogramBucket {
+                max_size: 1usize.checked_shl(bucket as u32).unwrap_or(usize::MAX),
+                count: self.counts[bucket].load(Ordering::Relaxed),
+                bytes: self.bytes[bucket].load(Ordering::Relaxed),
+            })
+            .filter(|bucket| bucket.count > 0)
+            .collect();
         
-        let mut buffer = Vec::with_capacity(length);
-        unsafe {
-            buffer.set_len(length);
-            SIMDOps::fast_copy(handle.to_ptr(), buffer.as_mut_ptr(), length);
+        AllocationHistogram {
+            tier,
+            total_count: buckets.iter().map(|bucket| bucket.count).sum(),
+            total_bytes: buckets.iter().map(|bucket| bucket.bytes).sum(),
+            buckets,
         }
-        Some(buffer)
     }
     
-    pub unsafe fn bulk_copy(&self, operations: &[(MemoryHandle, MemoryHandle, usize)]) {
-        unsafe { SIMDOps::bulk_copy_optimized(operations); }
+    fn clear(&self) {
+        for bucket in 0..HISTOGRAM_BUCKETS {
+            self.counts[bucket].store(0, Ordering::Relaxed);
+            self.bytes[bucket].store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+// ================================
+// === ALLOCATION LATENCY ===
+// ================================
+
+// Power-of-two buckets as in the size histogram: bucket i counts (2^(i-1), 2^i] nanoseconds
+const LATENCY_BUCKETS: usize = 40;
+
+// The route an allocation took through the tier, or a free
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum LatencyPath {
+    Slice,     // Carved from this thread's slice, no shared atomics
+    Bump,      // CAS loop on the tier's bump head
+    Slab,      // Bumped through the size class's own slab
+    Freelist,  // Popped (and possibly split) a freed block
+    Growth,    // WASM only: the tier was full and linear memory grew
+    Free,
+}
+
+const LATENCY_PATHS: [LatencyPath; 6] = [
+    LatencyPath::Slice,
+    LatencyPath::Bump,
+    LatencyPath::Slab,
+    LatencyPath::Freelist,
+    LatencyPath::Growth,
+    LatencyPath::Free,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyBucket {
+    pub max_ns: u64,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyHistogram {
+    pub path: LatencyPath,
+    pub count: usize,
+    pub total_ns: u64,
+    pub max_ns: u64,
+    pub buckets: Vec<LatencyBucket>,  // Non-empty buckets only, fastest first
+}
+
+impl LatencyHistogram {
+    pub fn mean_ns(&self) -> u64 {
+        self.total_ns / (self.count.max(1) as u64)
     }
     
-    // ================================
-    // === ENHANCED ASSET MANAGEMENT ===
-    // ================================
-    
-    pub fn set_base_url(&mut self, url: String) {
-        self.base_url = url;
+    // Upper bound of the bucket holding the `quantile` (0.0 to 1.0), e.g. 0.99 for p99
+    pub fn percentile_ns(&self, quantile: f64) -> u64 {
+        let target = ((self.count as f64 * quantile).ceil() as usize).max(1);
+        let mut seen = 0;
+        for bucket in &self.buckets {
+            seen += bucket.count;
+            if seen >= target {
+                return bucket.max_ns.min(self.max_ns);
+            }
+        }
+        self.max_ns
     }
+}
 
-    pub fn register_asset(&self, key: String, metadata: AssetMetadata) -> bool {
-        self.assets.insert(key, metadata)
+// CAS retries count failed compare-exchanges on the bump head and freelist heads; a high
+// `contended` share under load points at the retry loops rather than the allocator's work.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyProfile {
+    pub paths: Vec<LatencyHistogram>,  // Paths with samples, in LatencyPath order
+    pub cas_retries: usize,
+    pub contended: usize,    // Operations that retried at least once
+    pub max_retries: usize,  // Most retries any single operation needed
+}
+
+impl LatencyProfile {
+    pub fn path(&self, path: LatencyPath) -> Option<&LatencyHistogram> {
+        self.paths.iter().find(|histogram| histogram.path == path)
     }
+}
 
-    // Enhanced: Evict asset with automatic compaction on supported platforms
-    pub fn evict_asset(&self, path: &str) -> bool {
-        let metadata_opt = self.assets.get(path);
+// Opt-in because every profiled call reads the clock twice
+struct LatencyRecorder {
+    enabled: AtomicBool,
+    counts: [[AtomicUsize; LATENCY_BUCKETS]; LATENCY_PATHS.len()],
+    total_ns: [AtomicU64; LATENCY_PATHS.len()],
+    max_ns: [AtomicU64; LATENCY_PATHS.len()],
+    cas_retries: AtomicUsize,
+    contended: AtomicUsize,
+    max_retries: AtomicUsize,
+}
+
+impl LatencyRecorder {
+    fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            counts: std::array::from_fn(|_| std::array::from_fn(|_| AtomicUsize::new(0))),
+            total_ns: std::array::from_fn(|_| AtomicU64::new(0)),
+            max_ns: std::array::from_fn(|_| AtomicU64::new(0)),
+            cas_retries: AtomicUsize::new(0),
+            contended: AtomicUsize::new(0),
+            max_retries: AtomicUsize::new(0),
+        }
+    }
+    
+    #[inline(always)]
+    fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+    
+    fn record(&self, path: LatencyPath, nanos: u64) {
+        let bucket = if nanos <= 1 {
+            0
+        } else {
+            ((u64::BITS - (nanos - 1).leading_zeros()) as usize).min(LATENCY_BUCKETS - 1)
+        };
+        let path = path as usize;
+        self.counts[path][bucket].fetch_add(1, Ordering::Relaxed);
+        self.total_ns[path].fetch_add(nanos, Ordering::Relaxed);
+        self.max_ns[path].fetch_max(nanos, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    fn record_retries(&self, retries: usize) {
+        if retries > 0 && self.enabled() {
+            self.cas_retries.fetch_add(retries, Ordering::Relaxed);
+            self.contended.fetch_add(1, Ordering::Relaxed);
+            self.max_retries.fetch_max(retries, Ordering::Relaxed);
+        }
+    }
+    
+    fn snapshot(&self) -> LatencyProfile {
+        let paths = LATENCY_PATHS.iter().filter_map(|&path| {
+            let buckets: Vec<_> = (0..LATENCY_BUCKETS)
+                .map(|bucket| LatencyBucket {
+                    max_ns: 1u64 << bucket,
+                    count: self.counts[path as usize][bucket].load(Ordering::Relaxed),
+                })
+                .filter(|bucket| bucket.count > 0)
+                .collect();
+            if buckets.is_empty() {
+                return None;
+            }
+            Some(LatencyHistogram {
+                path,
+                count: buckets.iter().map(|bucket| bucket.count).sum(),
+                total_ns: self.total_ns[path as usize].load(Ordering::Relaxed),
+                max_ns: self.max_ns[path as usize].load(Ordering::Relaxed),
+                buckets,
+            })
+        }).collect();
         
-        if let Some(metadata) = metadata_opt {
-            let handle = metadata.handle;
-            let size = metadata.size;
-            let tier = metadata.tier;
-            
-            if handle.is_null() || tier as usize >= self.arenas.len() {
-                return self.assets.remove(path);
+        LatencyProfile {
+            paths,
+            cas_retries: self.cas_retries.load(Ordering::Relaxed),
+            contended: self.contended.load(Ordering::Relaxed),
+            max_retries: self.max_retries.load(Ordering::Relaxed),
+        }
+    }
+    
+    fn clear(&self) {
+        for path in 0..LATENCY_PATHS.len() {
+            for count in &self.counts[path] {
+                count.store(0, Ordering::Relaxed);
             }
-            
-            // On WASM, always compact to reduce fragmentation
-            #[cfg(target_arch = "wasm32")]
-            {
-                // Get all assets for this tier
-                let tier_assets = self.assets.get_assets_by_tier(tier);
-                
-                // Calculate total size needed (excluding the asset being evicted)
-                let mut preserve_size = 0;
-                let mut preserved_assets = Vec::new();
-                
-                for (asset_path, asset_meta) in tier_assets {
-                    if asset_path != path {
-                        preserve_size += asset_meta.size;
-                        preserved_assets.push((asset_path, asset_meta));
-                    }
-                }
-                
-                if preserve_size > 0 {
-                    // Create temporary buffer for preserved data
-                    let mut preserve_buffer = Vec::with_capacity(preserve_size);
-                    let mut new_offsets = Vec::new();
-                    
-                    // Copy all assets except the one being evicted
-                    for (asset_path, asset_meta) in &preserved_assets {
-                        let new_offset = preserve_buffer.len();
-                        
+            self.total_ns[path].store(0, Ordering::Relaxed);
+            self.max_ns[path].store(0, Ordering::Relaxed);
+        }
+        self.cas_retries.store(0, Ordering::Relaxed);
+        self.contended.store(0, Ordering::Relaxed);
+        self.max_retries.store(0, Ordering::Relaxed);
+    }
+}
+
+// ================================
+// === STATS EXPORT ===
+// ================================
+
+// Field names follow the WASM wrapper's memory_stats object so dashboards can share parsers
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierStats {
+    pub name: &'static str,
+    pub used: usize,
+    pub capacity: usize,
+    pub high_water_mark: usize,
+    pub total_allocated: usize,
+    pub memory_saved: usize,
+    pub freelist_bytes: Vec<usize>,
+    pub free_bytes: usize,
+    pub largest_free_block: usize,
+    pub fragmentation: f64,
+    pub asset_count: usize,
+    pub asset_bytes: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchStats {
+    pub requests: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub bytes_fetched: usize,
+    pub suppressed: usize,       // Loads refused because the path failed within the cooldown
+    pub cached_failures: usize,  // Paths currently cooling down
+    pub disk_hits: usize,        // Loads served from the disk cache, revalidated or not
+    pub throttled_ms: u64,       // Time loads spent waiting on the bandwidth limit
+    pub queued_ms: u64,          // Time loads spent waiting for a connection slot on their host
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryStats {
+    pub tiers: Vec<TierStats>,
+    pub total_used: usize,
+    pub raw_memory_size: usize,
+    pub allocator_type: &'static str,
+    pub memory_utilization: f64,
+    pub asset_count: usize,
+    pub fetch: FetchStats,
+}
+
+#[derive(Default)]
+struct FetchCounters {
+    requests: AtomicUsize,
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+    bytes_fetched: AtomicUsize,
+    disk_hits: AtomicUsize,
+    failures: FailureCache,
+    throttle: Throttle,
+    timings: LoadTimings,
+}
+
+impl FetchCounters {
+    fn record(&self, result: &Result<(MemoryHandle, usize), LoadError>) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        match result {
+            Ok((_, size)) => {
+                self.succeeded.fetch_add(1, Ordering::Relaxed);
+                self.bytes_fetched.fetch_add(*size, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    fn snapshot(&self) -> FetchStats {
+        FetchStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            succeeded: self.succeeded.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            bytes_fetched: self.bytes_fetched.load(Ordering::Relaxed),
+            suppressed: self.failures.suppressed.load(Ordering::Relaxed),
+            cached_failures: self.failures.cooling_down(),
+            disk_hits: self.disk_hits.load(Ordering::Relaxed),
+            throttled_ms: self.throttle.throttled_ms.load(Ordering::Relaxed),
+            queued_ms: self.throttle.queued_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedFetch {
+    pub path: String,
+    pub error: String,
+    pub failures: u32,  // Consecutive, reset by the next successful fetch of the path
+    pub retry_in_ms: u64,
+}
+
+struct FailureEntry {
+    error: String,
+    failures: u32,
+    until_ms: f64,
+}
+
+// Paths whose last fetch failed. Loads of them are refused without a request until the
+// cooldown has passed, so a missing asset polled every frame costs one round-trip per cooldown.
+#[derive(Default)]
+struct FailureCache {
+    cooldown_ms: AtomicU64,  // 0 disables the cache
+    entries: Mutex<HashMap<String, FailureEntry>>,
+    suppressed: AtomicUsize,
+}
+
+// Beyond this many paths, entries whose cooldown has passed are dropped
+const FAILURE_CACHE_LIMIT: usize = 1024;
+
+impl FailureCache {
+    fn check(&self, path: &str) -> Option<LoadError> {
+        if self.cooldown_ms.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(path)?;
+        let remaining = entry.until_ms - clock_ms();
+        if remaining <= 0.0 {
+            return None;
+        }
+        self.suppressed.fetch_add(1, Ordering::Relaxed);
+        Some(LoadError::CoolingDown {
+            error: entry.error.clone(),
+            failures: entry.failures,
+            retry_in_ms: remaining.ceil() as u64,
+        })
+    }
+    
+    fn record(&self, path: &str, error: &LoadError) {
+        let cooldown = self.cooldown_ms.load(Ordering::Relaxed);
+        if cooldown == 0 {
+            return;
+        }
+        let now = clock_ms();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= FAILURE_CACHE_LIMIT {
+            entries.retain(|_, entry| entry.until_ms > now);
+        }
+        let entry = entries.entry(path.to_string()).or_insert(FailureEntry { error: String::new(), failures: 0, until_ms: 0.0 });
+        entry.error = error.to_string();
+        entry.failures += 1;
+        entry.until_ms = now + cooldown as f64;
+    }
+    
+    fn forget(&self, path: &str) {
+        if self.cooldown_ms.load(Ordering::Relaxed) != 0 {
+            self.entries.lock().unwrap().remove(path);
+        }
+    }
+    
+    fn cooling_down(&self) -> usize {
+        let now = clock_ms();
+        self.entries.lock().unwrap().values().filter(|entry| entry.until_ms > now).count()
+    }
+    
+    fn list(&self) -> Vec<FailedFetch> {
+        let now = clock_ms();
+        let mut failed: Vec<_> = self.entries.lock().unwrap().iter()
+            .filter(|(_, entry)| entry.until_ms > now)
+            .map(|(path, entry)| FailedFetch {
+                path: path.clone(),
+                error: entry.error.clone(),
+                failures: entry.failures,
+                retry_in_ms: (entry.until_ms - now).ceil() as u64,
+            })
+            .collect();
+        failed.sort_by(|a, b| a.path.cmp(&b.path));
+        failed
+    }
+}
+
+// Where a load's bytes came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoadSource {
+    Network,      // A body was downloaded
+    DiskCache,    // Served from disk without a request
+    Revalidated,  // 304; the arena or disk copy was kept
+}
+
+// One load_asset_* call. reqwest doesn't report DNS and connect phases separately, so they
+// are part of ttfb_ms, which runs from sending the request to its response headers.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadTiming {
+    pub path: String,
+    pub source: Option<LoadSource>,  // None when the load failed
+    pub status: Option<u16>,
+    pub bytes: usize,                // Size of the asset delivered
+    pub attempts: u32,               // Requests sent, across middleware retries and mirrors
+    pub queued_ms: f64,              // Waiting for a connection slot on the host
+    pub ttfb_ms: Option<f64>,
+    pub total_ms: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Percentiles {
+    // Nearest-rank, over whatever samples there are
+    fn of(mut samples: Vec<f64>) -> Percentiles {
+        if samples.is_empty() {
+            return Percentiles::default();
+        }
+        samples.sort_by(f64::total_cmp);
+        let rank = |p: f64| samples[((p * samples.len() as f64).ceil() as usize).clamp(1, samples.len()) - 1];
+        Percentiles { p50: rank(0.5), p90: rank(0.9), p99: rank(0.99), max: samples[samples.len() - 1] }
+    }
+}
+
+// Aggregates over the last LOAD_TIMING_WINDOW loads
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStats {
+    pub loads: usize,
+    pub failures: usize,
+    pub cache_hits: usize,     // Disk or revalidated
+    pub retries: usize,        // Attempts beyond the first
+    pub bytes: usize,
+    pub throughput: f64,       // Downloaded bytes per second of network load time
+    pub total_ms: Percentiles,
+    pub ttfb_ms: Percentiles,  // Only loads that got a response
+    pub queued_ms: Percentiles,
+    pub recent: Vec<LoadTiming>,  // Oldest first
+}
+
+const LOAD_TIMING_WINDOW: usize = 256;
+
+#[derive(Default)]
+struct LoadTimings {
+    recent: Mutex<std::collections::VecDeque<LoadTiming>>,
+}
+
+impl LoadTimings {
+    fn record(&self, timing: LoadTiming) {
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == LOAD_TIMING_WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back(timing);
+    }
+    
+    fn stats(&self) -> NetworkStats {
+        let recent: Vec<LoadTiming> = self.recent.lock().unwrap().iter().cloned().collect();
+        let downloads = recent.iter().filter(|timing| timing.source == Some(LoadSource::Network));
+        let (downloaded, download_ms) = downloads.fold((0, 0.0), |(bytes, ms), timing| (bytes + timing.bytes, ms + timing.total_ms));
+        NetworkStats {
+            loads: recent.len(),
+            failures: recent.iter().filter(|timing| timing.source.is_none()).count(),
+            cache_hits: recent.iter().filter(|timing| matches!(timing.source, Some(LoadSource::DiskCache | LoadSource::Revalidated))).count(),
+            retries: recent.iter().map(|timing| timing.attempts.saturating_sub(1) as usize).sum(),
+            bytes: recent.iter().map(|timing| timing.bytes).sum(),
+            throughput: if download_ms > 0.0 { downloaded as f64 * 1000.0 / download_ms } else { 0.0 },
+            total_ms: Percentiles::of(recent.iter().map(|timing| timing.total_ms).collect()),
+            ttfb_ms: Percentiles::of(recent.iter().filter_map(|timing| timing.ttfb_ms).collect()),
+            queued_ms: Percentiles::of(recent.iter().map(|timing| timing.queued_ms).collect()),
+            recent,
+        }
+    }
+}
+
+// ================================
+// === STATS SNAPSHOTS ===
+// ================================
+
+// Monotonic per-tier counters; unlike the usage figures they survive resets, so the
+// difference between two snapshots is always the activity in between. The shards also
+// carry the arena's live counts, which resets do clear.
+#[derive(Default)]
+struct TierCounters {
+    shards: [CachePadded<StatShard>; STAT_SHARDS],
+    evictions: AtomicUsize,
+}
+
+impl TierCounters {
+    // This thread's shard, for updates that touch several counters at once
+    #[inline(always)]
+    fn shard(&self) -> &StatShard {
+        &self.shards[STAT_SHARD.with(|shard| *shard)]
+    }
+    
+    fn allocations(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.shards, field: Some(|shard| &shard.allocations) }
+    }
+    
+    fn frees(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.shards, field: Some(|shard| &shard.frees) }
+    }
+    
+    fn bytes_allocated(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.shards, field: report_only(|shard| &shard.bytes_allocated) }
+    }
+    
+    fn bytes_freed(&self) -> ShardedCounter<'_> {
+        ShardedCounter { shards: &self.shards, field: report_only(|shard| &shard.bytes_freed) }
+    }
+    
+    #[inline(always)]
+    fn record_allocation(&self, size: usize) {
+        let shard = self.shard();
+        shard.allocations.fetch_add(1, Ordering::Relaxed);
+        if !cfg!(feature = "minimal-stats") {
+            shard.bytes_allocated.fetch_add(size, Ordering::Relaxed);
+        }
+    }
+    
+    #[inline(always)]
+    fn record_free(&self, size: usize) {
+        self.record_frees(1, size);
+    }
+    
+    #[inline(always)]
+    fn record_frees(&self, count: usize, bytes: usize) {
+        let shard = self.shard();
+        shard.frees.fetch_add(count, Ordering::Relaxed);
+        if !cfg!(feature = "minimal-stats") {
+            shard.bytes_freed.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierSnapshot {
+    pub allocations: usize,
+    pub frees: usize,
+    pub bytes_allocated: usize,  // Requested bytes, before tier alignment
+    pub bytes_freed: usize,
+    pub evictions: usize,
+    pub used: usize,             // Bump head at snapshot time
+    pub live_allocations: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsSnapshot {
+    pub tiers: [TierSnapshot; 3],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierDelta {
+    pub allocations: usize,
+    pub frees: usize,
+    pub bytes_allocated: usize,
+    pub bytes_freed: usize,
+    pub evictions: usize,
+    pub used_change: isize,
+    pub live_allocations_change: isize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsDelta {
+    pub tiers: [TierDelta; 3],
+}
+
+impl StatsSnapshot {
+    pub fn tier(&self, tier: Tier) -> &TierSnapshot {
+        &self.tiers[tier as usize]
+    }
+    
+    // Activity between `earlier` and this snapshot
+    pub fn diff(&self, earlier: &StatsSnapshot) -> StatsDelta {
+        StatsDelta {
+            tiers: std::array::from_fn(|i| {
+                let (now, then) = (&self.tiers[i], &earlier.tiers[i]);
+                TierDelta {
+                    allocations: now.allocations.wrapping_sub(then.allocations),
+                    frees: now.frees.wrapping_sub(then.frees),
+                    bytes_allocated: now.bytes_allocated.wrapping_sub(then.bytes_allocated),
+                    bytes_freed: now.bytes_freed.wrapping_sub(then.bytes_freed),
+                    evictions: now.evictions.wrapping_sub(then.evictions),
+                    used_change: now.used as isize - then.used as isize,
+                    live_allocations_change: now.live_allocations as isize - then.live_allocations as isize,
+                }
+            }),
+        }
+    }
+}
+
+impl StatsDelta {
+    pub fn tier(&self, tier: Tier) -> &TierDelta {
+        &self.tiers[tier as usize]
+    }
+}
+
+// ================================
+// === MEMORY ATTRIBUTION ===
+// ================================
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionGroup {
+    pub name: String,
+    pub count: usize,
+    pub bytes: usize,
+    pub tier_bytes: [usize; 3],  // Indexed by Tier
+}
+
+// Registered assets grouped three ways; `by_tag` also counts tagged allocate_with_opts blocks.
+// Bytes are aligned footprints, so they add up to the arena's live bytes; whatever neither
+// accounts for is `unattributed`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionReport {
+    pub by_asset_type: Vec<AttributionGroup>,
+    pub by_prefix: Vec<AttributionGroup>,
+    pub by_tag: Vec<AttributionGroup>,
+    pub registered_bytes: [usize; 3],
+    pub unattributed_bytes: [usize; 3],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UsageCount {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+// One AssetType's registered footprint, in total and per tier (indexed by Tier)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AssetTypeUsage {
+    pub asset_type: AssetType,
+    pub total: UsageCount,
+    pub tiers: [UsageCount; 3],
+}
+
+// Keys are grouped by their first path segment ("textures/hero.png" -> "textures/")
+fn key_prefix(key: &str) -> &str {
+    match key.find('/') {
+        Some(end) => &key[..=end],
+        None => "",
+    }
+}
+
+fn add_to_group(groups: &mut HashMap<String, AttributionGroup>, name: &str, tier: Tier, bytes: usize) {
+    let group = groups.entry(name.to_string()).or_insert_with(|| AttributionGroup {
+        name: name.to_string(),
+        count: 0,
+        bytes: 0,
+        tier_bytes: [0; 3],
+    });
+    group.count += 1;
+    group.bytes += bytes;
+    group.tier_bytes[tier as usize] += bytes;
+}
+
+fn sorted_groups(groups: HashMap<String, AttributionGroup>) -> Vec<AttributionGroup> {
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    groups
+}
+
+// ================================
+// === SCOPED ROLLBACK ===
+// ================================
+
+// Snapshot of a tier's bump state that can be rolled back to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArenaMark {
+    restores: usize,  // Rollbacks the arena had seen when the mark was taken
+    tier: Tier,
+    head: usize,
+    epoch: usize,
+    frees: usize,
+    slice_carves: usize,
+    allocated: usize,
+    allocation_count: usize,
+}
+
+impl ArenaMark {
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    pub fn head(&self) -> usize {
+        self.head
+    }
+    
+    // Flat form for handing marks to JS and back
+    pub fn to_words(&self) -> [f64; 8] {
+        [
+            self.tier as u8 as f64,
+            self.head as f64,
+            self.epoch as f64,
+            self.frees as f64,
+            self.slice_carves as f64,
+            self.allocated as f64,
+            self.allocation_count as f64,
+            self.restores as f64,
+        ]
+    }
+    
+    // Rollback still validates the result against the arena, so a forged mark can at
+    // worst be refused
+    pub fn from_words(words: &[f64]) -> Option<ArenaMark> {
+        let &[tier, head, epoch, frees, slice_carves, allocated, allocation_count, restores] = words else {
+            return None;
+        };
+        Some(ArenaMark {
+            tier: Tier::from_u8(tier as u8)?,
+            head: head as usize,
+            epoch: epoch as usize,
+            frees: frees as usize,
+            slice_carves: slice_carves as usize,
+            allocated: allocated as usize,
+            allocation_count: allocation_count as usize,
+            restores: restores as usize,
+        })
+    }
+}
+
+// Rollbacks are monotonic: once a tier is rolled back to some head, marks taken before that
+// rollback above the head are dead even if the tier later grows past them again. Floors
+// keep (rollback number, head) with both increasing, so the lowest head any rollback after
+// a mark went to is the first floor newer than the mark.
+#[derive(Default)]
+struct RestoreLog {
+    count: usize,
+    floors: Vec<(usize, usize)>,
+}
+
+impl RestoreLog {
+    fn unwound_below(&self, mark: &ArenaMark) -> bool {
+        self.floors.iter()
+            .find(|&&(restore, _)| restore > mark.restores)
+            .is_some_and(|&(_, head)| head < mark.head)
+    }
+    
+    fn record(&mut self, head: usize) {
+        self.count += 1;
+        while self.floors.last().is_some_and(|&(_, floor)| floor >= head) {
+            self.floors.pop();
+        }
+        self.floors.push((self.count, head));
+    }
+}
+
+// Everything allocated from the tier while the scope is alive is released when it drops.
+// The tier must not be shared with other threads for the scope's lifetime.
+pub struct FrameScope<'a> {
+    walloc: &'a Walloc,
+    mark: ArenaMark,
+    released: bool,
+}
+
+impl FrameScope<'_> {
+    pub fn tier(&self) -> Tier {
+        self.mark.tier
+    }
+    
+    pub fn mark(&self) -> ArenaMark {
+        self.mark
+    }
+    
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate(&self, size: usize) -> Option<MemoryHandle> {
+        self.walloc.allocate(size, self.mark.tier)
+    }
+    
+    // Roll back now and learn whether it worked; dropping does the same silently
+    pub fn release(mut self) -> Result<usize, &'static str> {
+        self.released = true;
+        self.walloc.rollback(&self.mark)
+    }
+}
+
+impl Drop for FrameScope<'_> {
+    fn drop(&mut self) {
+        if !self.released
+            && let Err(_reason) = self.walloc.rollback(&self.mark)
+        {
+            walloc_event!(warn, tier = ?self.mark.tier, reason = _reason, "frame scope rollback refused");
+        }
+    }
+}
+
+// Allocations and asset registrations made through a Walloc::transaction. Unless the
+// closure succeeds they are undone: registrations are reverted, the bump head goes back to
+// where the transaction started, and anything the head can't cover is freed one by one.
+pub struct Transaction<'a> {
+    walloc: &'a Walloc,
+    mark: ArenaMark,
+    allocations: Vec<(MemoryHandle, usize)>,
+    registered: Vec<(String, Option<AssetMetadata>)>,  // Key and the entry it replaced
+    committed: bool,
+}
+
+impl Transaction<'_> {
+    pub fn tier(&self) -> Tier {
+        self.mark.tier
+    }
+    
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate(&mut self, size: usize) -> Option<MemoryHandle> {
+        let handle = self.walloc.allocate(size, self.mark.tier)?;
+        self.allocations.push((handle, size));
+        Some(handle)
+    }
+    
+    pub fn register_asset(&mut self, key: String, metadata: AssetMetadata) -> bool {
+        let previous = self.walloc.assets.get(&key);
+        self.registered.push((key.clone(), previous));
+        self.walloc.register_asset(key, metadata)
+    }
+    
+    fn roll_back(&mut self) {
+        for (key, previous) in self.registered.drain(..).rev() {
+            match previous {
+                Some(metadata) => self.walloc.assets.insert(key, metadata),
+                None => self.walloc.assets.remove(&key),
+            };
+        }
+        
+        // Blocks recycled from below the mark aren't covered by moving the head back, so they
+        // go back to the freelists first and the mark is told about those frees
+        let arena = &self.walloc.arenas[self.mark.tier as usize];
+        let floor = arena.base_offset + self.mark.head;
+        let (recycled, bumped): (Vec<_>, Vec<_>) = self.allocations.drain(..)
+            .partition(|&(handle, _)| handle.offset() < floor);
+        for &(handle, size) in &recycled {
+            arena.deallocate(handle, size);
+        }
+        
+        let mark = ArenaMark { frees: self.mark.frees + recycled.len(), ..self.mark };
+        if let Err(_reason) = self.walloc.rollback(&mark) {
+            walloc_event!(debug, tier = ?self.mark.tier, reason = _reason, "transaction freeing blocks individually");
+            for (handle, size) in bumped {
+                arena.deallocate(handle, size);
+            }
+        }
+        self.walloc.check_compaction_policy(self.mark.tier);
+    }
+}
+
+// Also covers a panicking closure
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.roll_back();
+        }
+    }
+}
+
+// ================================
+// === TIER FORKS ===
+// ================================
+
+// A private copy of a tier's allocated bytes for speculative work, e.g. background level
+// baking. Handles keep their meaning; reads and writes go to the copy until commit writes
+// the modified ranges back, or the fork is dropped to discard them.
+pub struct TierFork<'a> {
+    walloc: &'a Walloc,
+    mark: ArenaMark,
+    base_offset: usize,
+    shadow: Vec<u8>,
+    dirty: Vec<(usize, usize)>,  // (start, end) within the shadow
+}
+
+impl TierFork<'_> {
+    pub fn tier(&self) -> Tier {
+        self.mark.tier
+    }
+    
+    // Bytes captured by the fork: everything below the tier's bump head at fork time
+    pub fn len(&self) -> usize {
+        self.shadow.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.shadow.is_empty()
+    }
+    
+    fn range(&self, handle: MemoryHandle, len: usize) -> Option<(usize, usize)> {
+        let start = handle.offset().checked_sub(self.base_offset)?;
+        let end = start.checked_add(len).filter(|&end| end <= self.shadow.len())?;
+        Some((start, end))
+    }
+    
+    pub fn read_data(&self, handle: MemoryHandle, len: usize) -> Option<&[u8]> {
+        let (start, end) = self.range(handle, len)?;
+        Some(&self.shadow[start..end])
+    }
+    
+    pub fn write_data(&mut self, handle: MemoryHandle, data: &[u8]) -> Result<(), &'static str> {
+        self.slice_mut(handle, data.len())
+            .ok_or("Range is outside the forked tier")?
+            .copy_from_slice(data);
+        Ok(())
+    }
+    
+    pub fn slice_mut(&mut self, handle: MemoryHandle, len: usize) -> Option<&mut [u8]> {
+        let (start, end) = self.range(handle, len)?;
+        if start < end {
+            self.dirty.push((start, end));
+        }
+        Some(&mut self.shadow[start..end])
+    }
+    
+    // Write the modified ranges back into the live tier. Refused if the tier was reset or
+    // compacted, or had blocks freed, since the fork: the ranges may belong to other data now.
+    pub fn commit(mut self) -> Result<usize, &'static str> {
+        let arena = &self.walloc.arenas[self.mark.tier as usize];
+        if arena.epoch.load(Ordering::SeqCst) != self.mark.epoch {
+            return Err("Tier was reset or compacted since the fork");
+        }
+        if arena.counters.frees().load(Ordering::SeqCst) != self.mark.frees {
+            return Err("Blocks were freed since the fork");
+        }
+        
+        self.dirty.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(self.dirty.len());
+        for &(start, end) in &self.dirty {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        
+        let tier_base = arena.base_ptr();
+        let mut written = 0;
+        for (start, end) in merged {
+            unsafe {
+                SIMDOps::fast_copy(self.shadow.as_ptr().add(start), tier_base.add(start), end - start);
+            }
+            written += end - start;
+        }
+        walloc_event!(debug, tier = ?self.mark.tier, written, "fork committed");
+        Ok(written)
+    }
+}
+
+// ================================
+// === HEAP DUMP ===
+// ================================
+
+// Binary heap dump, all integers little-endian:
+//
+//   header   "WALLOCHD" | version: u32 | memory_size: u64
+//   section  kind: u8 | payload_len: u64 | payload
+//   ...
+//   end      kind = 0, payload_len = 0
+//
+// Section payloads (version 1):
+//   1 ARENA      tier: u8, then u64 each: base_offset, capacity, head, allocated,
+//                allocation_count, peak, high_water_mark, total_allocated, compaction_limit
+//   2 FREELIST   tier: u8 | count: u64 | count x (class: u8, offset: u64, size: u64)
+//   3 ASSETS     count: u64 | count x (tier: u8, asset_type: u8, offset: u64, size: u64,
+//                key_len: u32, key: utf-8)
+//   4 CALL_SITES tier: u8 | count: u64 | count x (offset: u64, size: u64, line: u32,
+//                column: u32, file_len: u32, file: utf-8)
+//                Only written by builds with the `track-callers` feature.
+//
+// Offsets are global (MemoryHandle::offset). Readers must skip section kinds they do not
+// know, so new sections can be added without bumping the version; changing an existing
+// payload layout requires a version bump.
+const HEAP_DUMP_MAGIC: &[u8; 8] = b"WALLOCHD";
+pub const HEAP_DUMP_VERSION: u32 = 1;
+
+const DUMP_END: u8 = 0;
+const DUMP_ARENA: u8 = 1;
+const DUMP_FREELIST: u8 = 2;
+const DUMP_ASSETS: u8 = 3;
+const DUMP_CALL_SITES: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArenaDump {
+    pub tier: Tier,
+    pub base_offset: usize,
+    pub capacity: usize,
+    pub head: usize,
+    pub allocated: usize,
+    pub allocation_count: usize,
+    pub peak: usize,
+    pub high_water_mark: usize,
+    pub total_allocated: usize,
+    pub compaction_limit: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FreeBlockDump {
+    pub tier: Tier,
+    pub size_class: usize,
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssetDump {
+    pub key: String,
+    pub tier: Tier,
+    pub asset_type: AssetType,
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CallSiteDump {
+    pub tier: Tier,
+    pub offset: usize,
+    pub size: usize,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+// Parsed form of a dump, for offline inspection and tests
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeapDump {
+    pub version: u32,
+    pub memory_size: usize,
+    pub arenas: Vec<ArenaDump>,
+    pub free_blocks: Vec<FreeBlockDump>,
+    pub assets: Vec<AssetDump>,
+    pub call_sites: Vec<CallSiteDump>,
+}
+
+impl HeapDump {
+    pub fn read(mut input: impl Read) -> io::Result<HeapDump> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        let mut reader = DumpReader { bytes: &bytes, pos: 0 };
+        
+        if reader.take(8)? != HEAP_DUMP_MAGIC {
+            return Err(dump_error("not a walloc heap dump"));
+        }
+        let version = reader.u32()?;
+        if version > HEAP_DUMP_VERSION {
+            return Err(dump_error("unsupported heap dump version"));
+        }
+        
+        let mut dump = HeapDump {
+            version,
+            memory_size: reader.u64()?,
+            arenas: Vec::new(),
+            free_blocks: Vec::new(),
+            assets: Vec::new(),
+            call_sites: Vec::new(),
+        };
+        
+        loop {
+            let kind = reader.u8()?;
+            let len = reader.u64()?;
+            let mut section = DumpReader { bytes: reader.take(len)?, pos: 0 };
+            
+            match kind {
+                DUMP_END => break,
+                DUMP_ARENA => dump.arenas.push(ArenaDump {
+                    tier: section.tier()?,
+                    base_offset: section.u64()?,
+                    capacity: section.u64()?,
+                    head: section.u64()?,
+                    allocated: section.u64()?,
+                    allocation_count: section.u64()?,
+                    peak: section.u64()?,
+                    high_water_mark: section.u64()?,
+                    total_allocated: section.u64()?,
+                    compaction_limit: section.u64()?,
+                }),
+                DUMP_FREELIST => {
+                    let tier = section.tier()?;
+                    for _ in 0..section.u64()? {
+                        dump.free_blocks.push(FreeBlockDump {
+                            tier,
+                            size_class: section.u8()? as usize,
+                            offset: section.u64()?,
+                            size: section.u64()?,
+                        });
+                    }
+                }
+                DUMP_ASSETS => dump.assets.extend(section.assets()?),
+                DUMP_CALL_SITES => {
+                    let tier = section.tier()?;
+                    for _ in 0..section.u64()? {
+                        let offset = section.u64()?;
+                        let size = section.u64()?;
+                        let line = section.u32()?;
+                        let column = section.u32()?;
+                        let file_len = section.u32()? as usize;
+                        let file = String::from_utf8(section.take(file_len)?.to_vec())
+                            .map_err(|_| dump_error("call site file is not utf-8"))?;
+                        dump.call_sites.push(CallSiteDump { tier, offset, size, file, line, column });
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        Ok(dump)
+    }
+    
+    pub fn arena(&self, tier: Tier) -> Option<&ArenaDump> {
+        self.arenas.iter().find(|arena| arena.tier == tier)
+    }
+    
+    // Same definition as Walloc::fragmentation: freelist bytes over the bump head
+    pub fn fragmentation(&self, tier: Tier) -> f64 {
+        let head = self.arena(tier).map_or(0, |arena| arena.head);
+        if head == 0 {
+            return 0.0;
+        }
+        let free: usize = self.free_blocks.iter()
+            .filter(|block| block.tier == tier)
+            .map(|block| block.size)
+            .sum();
+        free.min(head) as f64 / head as f64
+    }
+}
+
+fn dump_error(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+struct DumpReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DumpReader<'a> {
+    fn take(&mut self, len: impl TryInto<usize>) -> io::Result<&'a [u8]> {
+        let len = len.try_into().map_err(|_| dump_error("section too large"))?;
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| dump_error("truncated heap dump"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+    
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1usize)?[0])
+    }
+    
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4usize)?.try_into().unwrap()))
+    }
+    
+    fn u64(&mut self) -> io::Result<usize> {
+        let value = u64::from_le_bytes(self.take(8usize)?.try_into().unwrap());
+        usize::try_from(value).map_err(|_| dump_error("value exceeds usize"))
+    }
+    
+    fn tier(&mut self) -> io::Result<Tier> {
+        Tier::from_u8(self.u8()?).ok_or_else(|| dump_error("invalid tier"))
+    }
+    
+    // An ASSETS payload; also the format of the shared-memory registry
+    fn assets(&mut self) -> io::Result<Vec<AssetDump>> {
+        let count = self.u64()?;
+        let mut assets = Vec::with_capacity(count.min(self.bytes.len()));
+        for _ in 0..count {
+            let tier = self.tier()?;
+            let asset_type = AssetType::from_u8(self.u8()?)
+                .ok_or_else(|| dump_error("invalid asset type"))?;
+            let offset = self.u64()?;
+            let size = self.u64()?;
+            let key_len = self.u32()? as usize;
+            let key = String::from_utf8(self.take(key_len)?.to_vec())
+                .map_err(|_| dump_error("asset key is not utf-8"))?;
+            assets.push(AssetDump { key, tier, asset_type, offset, size });
+        }
+        Ok(assets)
+    }
+}
+
+fn write_dump_section(out: &mut impl Write, kind: u8, payload: &[u8]) -> io::Result<()> {
+    out.write_all(&[kind])?;
+    out.write_all(&(payload.len() as u64).to_le_bytes())?;
+    out.write_all(payload)
+}
+
+fn put_u64(payload: &mut Vec<u8>, value: usize) {
+    payload.extend_from_slice(&(value as u64).to_le_bytes());
+}
+
+impl Walloc {
+    // Offsets are written less `origin`: 0 for heap dumps, which keep handle offsets, or the
+    // handle space for registries another instance reads back
+    fn encode_assets(&self, origin: usize) -> Vec<u8> {
+        let mut assets: Vec<_> = self.assets.assets.read().unwrap()
+            .iter()
+            .map(|(key, metadata)| (key.clone(), metadata.clone()))
+            .collect();
+        assets.sort_by_key(|(_, metadata)| metadata.handle.offset());
+        
+        let mut payload = Vec::new();
+        put_u64(&mut payload, assets.len());
+        for (key, metadata) in assets {
+            payload.push(metadata.tier as u8);
+            payload.push(metadata.asset_type as u8);
+            put_u64(&mut payload, metadata.handle.offset().wrapping_sub(origin));
+            put_u64(&mut payload, metadata.size);
+            payload.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            payload.extend_from_slice(key.as_bytes());
+        }
+        payload
+    }
+}
+
+// Saved asset registry, to rebuild lookups after the heap image it describes is restored:
+//
+//   "WALLOCRG" | version: u32 | memory_size: u64 | count: u64 | count x (tier: u8,
+//   asset_type: u8, offset: u64, size: u64, hash: u64, key_len: u32, key: utf-8)
+//
+// `hash` is FNV-1a over the asset's bytes, so a registry restored against the wrong image
+// is rejected rather than pointing keys at unrelated data.
+const REGISTRY_MAGIC: &[u8; 8] = b"WALLOCRG";
+pub const REGISTRY_VERSION: u32 = 1;
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+impl Walloc {
+    pub fn save_registry(&self, write: impl Write) -> io::Result<()> {
+        let mut assets: Vec<_> = self.assets.assets.read().unwrap()
+            .iter()
+            .map(|(key, metadata)| (key.clone(), metadata.clone()))
+            .collect();
+        assets.sort_by_key(|(_, metadata)| metadata.handle.offset());
+        
+        let mut out = io::BufWriter::new(write);
+        out.write_all(REGISTRY_MAGIC)?;
+        out.write_all(&REGISTRY_VERSION.to_le_bytes())?;
+        out.write_all(&(self.get_memory_limit() as u64).to_le_bytes())?;
+        out.write_all(&(assets.len() as u64).to_le_bytes())?;
+        for (key, metadata) in assets {
+            let bytes = self.read_data(metadata.handle, metadata.size)
+                .map_err(|_| dump_error("registered asset is not readable"))?;
+            let mut entry = vec![metadata.tier as u8, metadata.asset_type as u8];
+            put_u64(&mut entry, self.base_relative(metadata.handle));
+            put_u64(&mut entry, metadata.size);
+            entry.extend_from_slice(&content_hash(&bytes).to_le_bytes());
+            entry.extend_from_slice(&(key.len() as u32).to_le_bytes());
+            entry.extend_from_slice(key.as_bytes());
+            out.write_all(&entry)?;
+        }
+        out.flush()
+    }
+    
+    // Replace the registry with one written by save_registry. Every entry has to lie below
+    // its tier's bump head and hash to the saved contents; if any doesn't, the current
+    // registry is left untouched. Returns the number of restored assets.
+    pub fn restore_registry(&self, mut input: impl Read) -> io::Result<usize> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+        let mut reader = DumpReader { bytes: &bytes, pos: 0 };
+        
+        if reader.take(8)? != REGISTRY_MAGIC {
+            return Err(dump_error("not a walloc registry"));
+        }
+        if reader.u32()? > REGISTRY_VERSION {
+            return Err(dump_error("unsupported registry version"));
+        }
+        if reader.u64()? != self.get_memory_limit() {
+            return Err(dump_error("registry was saved for a different memory size"));
+        }
+        
+        let count = reader.u64()?;
+        let mut restored = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0..count {
+            let tier = reader.tier()?;
+            let asset_type = AssetType::from_u8(reader.u8()?)
+                .ok_or_else(|| dump_error("invalid asset type"))?;
+            let offset = self.handle_at(reader.u64()?).offset();
+            let size = reader.u64()?;
+            let hash = u64::from_le_bytes(reader.take(8usize)?.try_into().unwrap());
+            let key_len = reader.u32()? as usize;
+            let key = String::from_utf8(reader.take(key_len)?.to_vec())
+                .map_err(|_| dump_error("asset key is not utf-8"))?;
+            
+            let arena = &self.arenas[tier as usize];
+            let inside = offset >= arena.base_offset && offset.checked_add(size)
+                .is_some_and(|end| end <= arena.base_offset + arena.usage());
+            if !inside {
+                return Err(dump_error("asset lies outside the restored arenas"));
+            }
+            // Not read_data: the registry being replaced may not describe these ranges
+            let handle = MemoryHandle(offset);
+            let mut contents = vec![0u8; size];
+            unsafe {
+                copy_arena_bytes(handle.resolve(self.handle_base), contents.as_mut_ptr(), size);
+            }
+            if content_hash(&contents) != hash {
+                return Err(dump_error("asset contents do not match the registry"));
+            }
+            restored.push((key, AssetMetadata::new(asset_type, handle, size, tier)));
+        }
+        
+        let count = restored.len();
+        self.assets.clear();
+        for (key, metadata) in restored {
+            self.assets.insert(key, metadata);
+        }
+        Ok(count)
+    }
+}
+
+// ================================
+// === CALL-SITE TRACKING ===
+// ================================
+
+// With the `track-callers` feature every allocation remembers the source location that
+// requested it, keyed by handle offset, until it is freed, evicted or its tier is reset.
+#[cfg(feature = "track-callers")]
+#[derive(Clone, Copy, Debug)]
+struct CallSite {
+    size: usize,
+    location: &'static std::panic::Location<'static>,
+}
+
+#[cfg(feature = "track-callers")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeakSite {
+    pub location: &'static std::panic::Location<'static>,
+    pub count: usize,
+    pub bytes: usize,
+    pub handles: Vec<MemoryHandle>,
+}
+
+// Live allocations of one tier grouped by call site, largest first
+#[cfg(feature = "track-callers")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeakReport {
+    pub tier: Tier,
+    pub sites: Vec<LeakSite>,
+    pub total_count: usize,
+    pub total_bytes: usize,
+}
+
+#[cfg(feature = "track-callers")]
+impl std::fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} tier: {} live allocations, {} bytes", self.tier.name(), self.total_count, self.total_bytes)?;
+        for site in &self.sites {
+            writeln!(f, "  {:>12} bytes in {:>6} allocations at {}", site.bytes, site.count, site.location)?;
+        }
+        Ok(())
+    }
+}
+
+// ================================
+// === SIMPLE ASSET REGISTRY ===
+// ================================
+
+// How keys are canonicalized on insert and lookup, so that content authored on Windows
+// ("Textures\Foo.PNG") and URL-style paths ("textures/foo%20bar.png") name the same asset.
+// Normalizing an already normalized key changes nothing: a decoded "%25" stays "%25".
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyNormalizer {
+    lowercase: bool,
+    slashes: bool,
+    percent_decode: bool,
+}
+
+impl KeyNormalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    // Lowercase, slash and percent normalization together
+    pub fn portable() -> Self {
+        Self::new().with_lowercase().with_slashes().with_percent_decoding()
+    }
+    
+    pub fn with_lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+    
+    // Backslashes become slashes, runs of slashes collapse and leading/trailing ones go
+    pub fn with_slashes(mut self) -> Self {
+        self.slashes = true;
+        self
+    }
+    
+    // %XX escapes are decoded, unless the result would not be UTF-8
+    pub fn with_percent_decoding(mut self) -> Self {
+        self.percent_decode = true;
+        self
+    }
+    
+    pub fn normalize<'k>(&self, key: &'k str) -> std::borrow::Cow<'k, str> {
+        let mut key = std::borrow::Cow::Borrowed(key);
+        if self.percent_decode && key.contains('%') {
+            key = percent_decode(&key).into();
+        }
+        if self.slashes && (key.contains('\\') || key.contains("//") || key.starts_with('/') || key.ends_with('/')) {
+            key = key.replace('\\', "/").split('/').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("/").into();
+        }
+        if self.lowercase && key.chars().any(char::is_uppercase) {
+            key = key.to_lowercase().into();
+        }
+        key
+    }
+}
+
+fn percent_decode(key: &str) -> String {
+    let bytes = key.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            .filter(|&byte| bytes[i] == b'%' && byte != b'%');
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| key.to_string())
+}
+
+// An unpinned asset that may be evicted, as handed to EvictionPolicy::select_victims
+#[derive(Clone, Debug)]
+pub struct EvictionCandidate {
+    pub key: String,
+    pub metadata: AssetMetadata,
+    pub idle: u64,  // Registry clock ticks since the last insert or access
+}
+
+// Chooses which assets go when a tier needs room, either for allocate_or_evict or because a
+// registry cap was exceeded. Hooks run under registry locks, so they must not call back
+// into the registry.
+pub trait EvictionPolicy: Send + Sync {
+    fn on_access(&self, _key: &str) {}
+    
+    fn on_insert(&self, _key: &str, _metadata: &AssetMetadata) {}
+    
+    fn on_remove(&self, _key: &str) {}
+    
+    // Victims in eviction order, enough to cover `bytes` of registered size where possible.
+    // Callers may stop early once they have room.
+    fn select_victims(&self, candidates: Vec<EvictionCandidate>, bytes: usize) -> Vec<EvictionCandidate>;
+}
+
+// The built-in policies evict lower priorities first and only then apply their own order
+fn take_victims(mut candidates: Vec<EvictionCandidate>, bytes: usize, order: impl Fn(&EvictionCandidate) -> u128) -> Vec<EvictionCandidate> {
+    candidates.sort_by_key(|candidate| (candidate.metadata.priority, order(candidate)));
+    let mut covered = 0;
+    let count = candidates.iter()
+        .take_while(|candidate| {
+            let more = covered < bytes;
+            covered += candidate.metadata.size;
+            more
+        })
+        .count();
+    candidates.truncate(count);
+    candidates
+}
+
+// Least recently used first; the default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LruPolicy;
+
+impl EvictionPolicy for LruPolicy {
+    fn select_victims(&self, candidates: Vec<EvictionCandidate>, bytes: usize) -> Vec<EvictionCandidate> {
+        take_victims(candidates, bytes, |candidate| u128::from(u64::MAX - candidate.idle))
+    }
+}
+
+// Least frequently accessed first, oldest first among equals. Counts start over when a key
+// is registered again.
+#[derive(Debug, Default)]
+pub struct LfuPolicy {
+    hits: Mutex<HashMap<String, u64>>,
+}
+
+impl LfuPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn hits(&self, key: &str) -> u64 {
+        self.hits.lock().unwrap().get(key).copied().unwrap_or(0)
+    }
+}
+
+impl EvictionPolicy for LfuPolicy {
+    fn on_access(&self, key: &str) {
+        if let Some(hits) = self.hits.lock().unwrap().get_mut(key) {
+            *hits += 1;
+        }
+    }
+    
+    fn on_insert(&self, key: &str, _metadata: &AssetMetadata) {
+        self.hits.lock().unwrap().insert(key.to_string(), 0);
+    }
+    
+    fn on_remove(&self, key: &str) {
+        self.hits.lock().unwrap().remove(key);
+    }
+    
+    fn select_victims(&self, candidates: Vec<EvictionCandidate>, bytes: usize) -> Vec<EvictionCandidate> {
+        let hits = self.hits.lock().unwrap();
+        take_victims(candidates, bytes, |candidate| {
+            let count = hits.get(&candidate.key).copied().unwrap_or(0);
+            (u128::from(count) << 64) | u128::from(u64::MAX - candidate.idle)
+        })
+    }
+}
+
+// Largest size x idle time first, so one big stale asset goes before many small ones
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SizeWeightedPolicy;
+
+impl EvictionPolicy for SizeWeightedPolicy {
+    fn select_victims(&self, candidates: Vec<EvictionCandidate>, bytes: usize) -> Vec<EvictionCandidate> {
+        take_victims(candidates, bytes, |candidate| {
+            u128::MAX - candidate.metadata.size as u128 * (u128::from(candidate.idle) + 1)
+        })
+    }
+}
+
+// The registry's assets by key, with the byte range each covers indexed by start offset so
+// Walloc::check_range finds the asset containing a handle without scanning. Reads go through
+// the map; every change goes through insert, remove, update or clear to keep the index whole.
+#[derive(Default)]
+struct AssetMap {
+    by_key: HashMap<String, AssetMetadata>,
+    ranges: std::collections::BTreeMap<(usize, usize), usize>,  // (start, end) -> assets covering it
+    longest: usize,  // Widest range indexed since the last clear; bounds the covering search
+}
+
+impl std::ops::Deref for AssetMap {
+    type Target = HashMap<String, AssetMetadata>;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.by_key
+    }
+}
+
+impl AssetMap {
+    fn with_capacity(capacity: usize) -> Self {
+        Self { by_key: HashMap::with_capacity(capacity), ranges: Default::default(), longest: 0 }
+    }
+    
+    fn range_of(metadata: &AssetMetadata) -> Option<(usize, usize)> {
+        let start = metadata.handle.offset();
+        (!metadata.handle.is_null()).then(|| (start, start.saturating_add(metadata.size)))
+    }
+    
+    fn index(&mut self, metadata: &AssetMetadata) {
+        if let Some((start, end)) = Self::range_of(metadata) {
+            *self.ranges.entry((start, end)).or_insert(0) += 1;
+            self.longest = self.longest.max((end - start).max(1));
+        }
+    }
+    
+    fn unindex(&mut self, metadata: &AssetMetadata) {
+        let Some(range) = Self::range_of(metadata) else {
+            return;
+        };
+        if let std::collections::btree_map::Entry::Occupied(mut count) = self.ranges.entry(range) {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+    }
+    
+    fn insert(&mut self, key: String, metadata: AssetMetadata) -> Option<AssetMetadata> {
+        self.index(&metadata);
+        let previous = self.by_key.insert(key, metadata);
+        if let Some(previous) = &previous {
+            self.unindex(previous);
+        }
+        previous
+    }
+    
+    fn remove(&mut self, key: &str) -> Option<AssetMetadata> {
+        let removed = self.by_key.remove(key);
+        if let Some(removed) = &removed {
+            self.unindex(removed);
+        }
+        removed
+    }
+    
+    fn update<R>(&mut self, key: &str, f: impl FnOnce(&mut AssetMetadata) -> R) -> Option<R> {
+        let mut metadata = self.by_key.remove(key)?;
+        self.unindex(&metadata);
+        let result = f(&mut metadata);
+        self.index(&metadata);
+        self.by_key.insert(key.to_string(), metadata);
+        Some(result)
+    }
+    
+    fn clear(&mut self) {
+        self.by_key.clear();
+        self.ranges.clear();
+        self.longest = 0;
+    }
+    
+    // (start, end) of a registered asset whose bytes include `offset`, found even when it
+    // starts before a nearer, shorter asset that ends first. Of several covering it, the one
+    // starting closest wins, and of those the longest. An empty asset owns its first byte.
+    fn asset_covering(&self, offset: usize) -> Option<(usize, usize)> {
+        let lowest = (offset.saturating_sub(self.longest), 0);
+        self.ranges.range(lowest..=(offset, usize::MAX)).rev()
+            .map(|(&range, _)| range)
+            .find(|&(start, end)| offset < end.max(start + 1))
+    }
+    
+    // Whether a registered asset starts inside `start + 1..end`, i.e. a range beginning at
+    // `start` would run into it
+    fn asset_starts_within(&self, start: usize, end: usize) -> bool {
+        start.checked_add(1).is_some_and(|first| first < end && self.ranges.range((first, 0)..(end, 0)).next().is_some())
+    }
+}
+
+pub struct SimpleAssetRegistry {
+    assets: RwLock<AssetMap>,
+    // Logical access clock per key, kept apart so touches only need the read lock
+    recency: RwLock<HashMap<String, AtomicU64>>,
+    // Clock tick of each key's latest insert, so an AssetRef can tell a re-registered key apart
+    generations: RwLock<HashMap<String, u64>>,
+    // Live AssetGuards per key; eviction skips pinned keys
+    pins: Mutex<HashMap<String, usize>>,
+    // Alternate names (localized paths, legacy IDs), each mapped to the key it resolves to.
+    // Always locked before `assets`.
+    aliases: RwLock<HashMap<String, String>>,
+    // Applied to every key passed in; stored keys are already normalized
+    normalizer: KeyNormalizer,
+    policy: RwLock<Arc<dyn EvictionPolicy>>,
+    // Registered bytes allowed per tier before insert_capped evicts; 0 is unlimited
+    caps: [AtomicUsize; 3],
+    clock: AtomicU64,
+}
+
+impl Default for SimpleAssetRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn resolve_alias<'k>(aliases: &'k HashMap<String, String>, key: &'k str) -> &'k str {
+    aliases.get(key).map_or(key, String::as_str)
+}
+
+impl SimpleAssetRegistry {
+    pub fn new() -> Self {
+        Self {
+            assets: RwLock::new(AssetMap::with_capacity(256)),
+            recency: RwLock::new(HashMap::with_capacity(256)),
+            generations: RwLock::new(HashMap::with_capacity(256)),
+            pins: Mutex::new(HashMap::new()),
+            aliases: RwLock::new(HashMap::new()),
+            normalizer: KeyNormalizer::default(),
+            policy: RwLock::new(Arc::new(LruPolicy)),
+            caps: [AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)],
+            clock: AtomicU64::new(0),
+        }
+    }
+    
+    // Existing assets are not replayed through on_insert; set the policy before loading
+    pub fn set_policy(&self, policy: Arc<dyn EvictionPolicy>) {
+        *self.policy.write().unwrap() = policy;
+    }
+    
+    pub fn set_cap(&self, tier: Tier, bytes: usize) {
+        self.caps[tier as usize].store(bytes, Ordering::Relaxed);
+    }
+    
+    pub fn cap(&self, tier: Tier) -> usize {
+        self.caps[tier as usize].load(Ordering::Relaxed)
+    }
+    
+    pub fn with_normalizer(mut self, normalizer: KeyNormalizer) -> Self {
+        self.normalizer = normalizer;
+        self
+    }
+    
+    pub fn normalizer(&self) -> KeyNormalizer {
+        self.normalizer
+    }
+    
+    // Registering under an alias's name replaces the alias
+    pub fn insert(&self, key: String, metadata: AssetMetadata) -> bool {
+        let key = match self.normalizer.normalize(&key) {
+            std::borrow::Cow::Borrowed(_) => key,
+            std::borrow::Cow::Owned(normalized) => normalized,
+        };
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        aliases.remove(&key);
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        self.recency.write().unwrap().insert(key.clone(), AtomicU64::new(tick));
+        self.generations.write().unwrap().insert(key.clone(), tick);
+        self.policy.read().unwrap().on_insert(&key, &metadata);
+        assets.insert(key, metadata).is_none()
+    }
+    
+    // Insert, then bring the tier back under its cap by removing victims chosen by the policy.
+    // The new asset is never a victim. Returns whether the key was new and the removed
+    // assets, whose memory the caller frees; if too little is evictable the cap is exceeded.
+    pub fn insert_capped(&self, key: String, metadata: AssetMetadata) -> (bool, Vec<(String, AssetMetadata)>) {
+        let tier = metadata.tier;
+        let key = self.normalizer.normalize(&key).into_owned();
+        let inserted = self.insert(key.clone(), metadata);
+        let cap = self.cap(tier);
+        if cap == 0 {
+            return (inserted, Vec::new());
+        }
+        
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        let registered: usize = assets.values()
+            .filter(|metadata| metadata.tier == tier)
+            .map(|metadata| metadata.size)
+            .sum();
+        if registered <= cap {
+            return (inserted, Vec::new());
+        }
+        
+        let mut candidates = self.candidates(&assets, tier);
+        candidates.retain(|candidate| candidate.key != key);
+        let policy = self.policy.read().unwrap().clone();
+        let mut excess = registered - cap;
+        let mut removed = Vec::new();
+        for victim in policy.select_victims(candidates, excess) {
+            if excess == 0 {
+                break;
+            }
+            let Some(metadata) = assets.remove(&victim.key) else {
+                continue;
+            };
+            aliases.retain(|_, target| *target != victim.key);
+            self.recency.write().unwrap().remove(&victim.key);
+            self.generations.write().unwrap().remove(&victim.key);
+            policy.on_remove(&victim.key);
+            excess = excess.saturating_sub(metadata.size);
+            removed.push((victim.key, metadata));
+        }
+        (inserted, removed)
+    }
+    
+    pub fn get(&self, key: &str) -> Option<AssetMetadata> {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let assets = self.assets.read().unwrap();
+        assets.get(resolve_alias(&aliases, &key)).cloned()
+    }
+    
+    // One read lock for the whole lookup, in `keys` order
+    pub fn get_batch(&self, keys: &[&str]) -> Vec<Option<AssetMetadata>> {
+        let aliases = self.aliases.read().unwrap();
+        let assets = self.assets.read().unwrap();
+        keys.iter()
+            .map(|key| assets.get(resolve_alias(&aliases, &self.normalizer.normalize(key))).cloned())
+            .collect()
+    }
+    
+    // Removing through an alias removes the asset; either way its aliases go with it
+    pub fn remove(&self, key: &str) -> bool {
+        let key = self.normalizer.normalize(key);
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        let key = resolve_alias(&aliases, &key).to_string();
+        aliases.retain(|_, target| *target != key);
+        self.recency.write().unwrap().remove(&key);
+        self.generations.write().unwrap().remove(&key);
+        self.policy.read().unwrap().on_remove(&key);
+        assets.remove(&key).is_some()
+    }
+    
+    // Like remove, but refuses while an AssetGuard holds the key. The check and the removal
+    // happen under the write lock, so a concurrent pin either lands first or finds nothing.
+    pub fn remove_unpinned(&self, key: &str) -> bool {
+        let key = self.normalizer.normalize(key);
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        let key = resolve_alias(&aliases, &key).to_string();
+        if self.pins.lock().unwrap().contains_key(&key) {
+            return false;
+        }
+        aliases.retain(|_, target| *target != key);
+        self.recency.write().unwrap().remove(&key);
+        self.generations.write().unwrap().remove(&key);
+        self.policy.read().unwrap().on_remove(&key);
+        assets.remove(&key).is_some()
+    }
+    
+    // Give a registered asset another name. Fails if `existing` isn't registered or `alias`
+    // is already a key or alias; aliases of aliases resolve to the same asset.
+    pub fn alias(&self, existing: &str, alias: String) -> bool {
+        let existing = self.normalizer.normalize(existing);
+        let alias = self.normalizer.normalize(&alias).into_owned();
+        let mut aliases = self.aliases.write().unwrap();
+        let assets = self.assets.read().unwrap();
+        let target = resolve_alias(&aliases, &existing).to_string();
+        if !assets.contains_key(&target) || assets.contains_key(&alias) || aliases.contains_key(&alias) {
+            return false;
+        }
+        aliases.insert(alias, target);
+        true
+    }
+    
+    // Renaming a key keeps its metadata, recency, generation and aliases; renaming an alias
+    // only renames the alias. Refused while an AssetGuard holds the asset.
+    pub fn rename(&self, old: &str, new: String) -> bool {
+        let old = &*self.normalizer.normalize(old);
+        let new = self.normalizer.normalize(&new).into_owned();
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        if assets.contains_key(&new) || aliases.contains_key(&new) {
+            return false;
+        }
+        if let Some(target) = aliases.remove(old) {
+            aliases.insert(new, target);
+            return true;
+        }
+        if self.pins.lock().unwrap().contains_key(old) {
+            return false;
+        }
+        let Some(metadata) = assets.remove(old) else {
+            return false;
+        };
+        
+        let mut recency = self.recency.write().unwrap();
+        if let Some(last) = recency.remove(old) {
+            recency.insert(new.clone(), last);
+        }
+        let mut generations = self.generations.write().unwrap();
+        if let Some(generation) = generations.remove(old) {
+            generations.insert(new.clone(), generation);
+        }
+        for target in aliases.values_mut().filter(|target| *target == old) {
+            *target = new.clone();
+        }
+        let policy = self.policy.read().unwrap();
+        policy.on_remove(old);
+        policy.on_insert(&new, &metadata);
+        assets.insert(new, metadata);
+        true
+    }
+    
+    pub fn pin_count(&self, key: &str) -> usize {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        self.pins.lock().unwrap().get(resolve_alias(&aliases, &key)).copied().unwrap_or(0)
+    }
+    
+    // Pins are counted under the registered key, which is returned for the matching unpin
+    fn pin(&self, key: &str) -> Option<(String, AssetMetadata)> {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let assets = self.assets.read().unwrap();
+        let key = resolve_alias(&aliases, &key);
+        let metadata = assets.get(key)?.clone();
+        *self.pins.lock().unwrap().entry(key.to_string()).or_insert(0) += 1;
+        Some((key.to_string(), metadata))
+    }
+    
+    fn unpin(&self, key: &str) {
+        let mut pins = self.pins.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(count) = pins.get_mut(key) {
+            *count -= 1;
+            if *count == 0 {
+                pins.remove(key);
+            }
+        }
+    }
+    
+    // Changes every time the key is registered again, even at the same handle
+    pub fn generation(&self, key: &str) -> Option<u64> {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        self.generations.read().unwrap().get(resolve_alias(&aliases, &key)).copied()
+    }
+    
+    // Metadata and generation read under one lock, so they describe the same registration
+    fn get_with_generation(&self, key: &str) -> Option<(AssetMetadata, u64)> {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let assets = self.assets.read().unwrap();
+        let key = resolve_alias(&aliases, &key);
+        let metadata = assets.get(key)?.clone();
+        let generation = self.generations.read().unwrap().get(key).copied()?;
+        Some((metadata, generation))
+    }
+    
+    // Mark an asset as most recently used
+    pub fn touch(&self, key: &str) {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let key = resolve_alias(&aliases, &key);
+        if let Some(last) = self.recency.read().unwrap().get(key) {
+            let tick = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+            last.store(tick, Ordering::Relaxed);
+            self.policy.read().unwrap().on_access(key);
+        }
+    }
+    
+    pub fn last_access(&self, key: &str) -> Option<u64> {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        self.recency.read().unwrap().get(resolve_alias(&aliases, &key)).map(|last| last.load(Ordering::Relaxed))
+    }
+    
+    pub fn set_priority(&self, key: &str, priority: u8) -> bool {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        assets.update(resolve_alias(&aliases, &key), |metadata| metadata.priority = priority).is_some()
+    }
+    
+    pub fn set_pinned(&self, key: &str, pinned: bool) -> bool {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        assets.update(resolve_alias(&aliases, &key), |metadata| metadata.pinned = pinned).is_some()
+    }
+    
+    // Unpinned assets of a tier in the policy's eviction order; with the default LruPolicy,
+    // lowest priority first, then least recently used
+    pub fn eviction_candidates(&self, tier: Tier) -> Vec<(String, AssetMetadata)> {
+        let candidates = self.candidates(&self.assets.read().unwrap(), tier);
+        self.policy.read().unwrap().select_victims(candidates, usize::MAX)
+            .into_iter()
+            .map(|candidate| (candidate.key, candidate.metadata))
+            .collect()
+    }
+    
+    fn candidates(&self, assets: &HashMap<String, AssetMetadata>, tier: Tier) -> Vec<EvictionCandidate> {
+        let recency = self.recency.read().unwrap();
+        let pins = self.pins.lock().unwrap();
+        let now = self.clock.load(Ordering::Relaxed);
+        assets.iter()
+            .filter(|(key, metadata)| metadata.tier == tier && !metadata.pinned && !metadata.handle.is_null() && !pins.contains_key(*key))
+            .map(|(key, metadata)| {
+                let last = recency.get(key).map_or(0, |last| last.load(Ordering::Relaxed));
+                EvictionCandidate { key: key.clone(), metadata: metadata.clone(), idle: now.saturating_sub(last) }
+            })
+            .collect()
+    }
+    
+    // Repoint an asset after relocation, unless it was evicted or replaced in the meantime
+    pub fn update_handle(&self, key: &str, expected: MemoryHandle, new_handle: MemoryHandle) -> bool {
+        let mut assets = self.assets.write().unwrap();
+        if assets.get(key).is_none_or(|metadata| metadata.handle != expected) {
+            return false;
+        }
+        assets.update(key, |metadata| {
+            metadata.handle = new_handle;
+            metadata.offset = new_handle.offset();
+        });
+        true
+    }
+    
+    // Move whichever asset starts at `from` in `from_tier` to a new tier and handle
+    pub fn retier(&self, from: MemoryHandle, from_tier: Tier, to: MemoryHandle, to_tier: Tier) -> Option<String> {
+        let mut assets = self.assets.write().unwrap();
+        let key = assets.iter()
+            .find(|(_, metadata)| metadata.handle == from && metadata.tier == from_tier)
+            .map(|(key, _)| key.clone())?;
+        assets.update(&key, |metadata| {
+            metadata.handle = to;
+            metadata.offset = to.offset();
+            metadata.tier = to_tier;
+        });
+        Some(key)
+    }
+    
+    pub fn set_freshness(&self, key: &str, freshness: Option<Freshness>) -> bool {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        assets.update(resolve_alias(&aliases, &key), |metadata| metadata.freshness = freshness).is_some()
+    }
+    
+    pub fn set_tag(&self, key: &str, tag: Option<String>) -> bool {
+        let key = self.normalizer.normalize(key);
+        let aliases = self.aliases.read().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        assets.update(resolve_alias(&aliases, &key), |metadata| metadata.tag = tag).is_some()
+    }
+    
+    pub fn remove_batch(&self, keys: &[String]) -> usize {
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        let mut recency = self.recency.write().unwrap();
+        let mut generations = self.generations.write().unwrap();
+        let policy = self.policy.read().unwrap();
+        let mut count = 0;
+        
+        for key in keys {
+            let key = resolve_alias(&aliases, &self.normalizer.normalize(key)).to_string();
+            aliases.retain(|_, target| *target != key);
+            recency.remove(&key);
+            generations.remove(&key);
+            policy.on_remove(&key);
+            if assets.remove(&key).is_some() {
+                count += 1;
+            }
+        }
+        
+        count
+    }
+    
+    pub fn clear(&self) {
+        let mut aliases = self.aliases.write().unwrap();
+        let mut assets = self.assets.write().unwrap();
+        aliases.clear();
+        self.recency.write().unwrap().clear();
+        self.generations.write().unwrap().clear();
+        let policy = self.policy.read().unwrap();
+        for key in assets.keys() {
+            policy.on_remove(key);
+        }
+        assets.clear();
+    }
+    
+    pub fn len(&self) -> usize {
+        let assets = self.assets.read().unwrap();
+        assets.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        let assets = self.assets.read().unwrap();
+        assets.is_empty()
+    }
+    
+    // Enhanced: Get all assets for a tier
+    pub fn get_assets_by_tier(&self, tier: Tier) -> Vec<(String, AssetMetadata)> {
+        let assets = self.assets.read().unwrap();
+        assets.iter()
+            .filter(|(_, metadata)| metadata.tier == tier)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+}
+
+unsafe impl Send for SimpleAssetRegistry {}
+unsafe impl Sync for SimpleAssetRegistry {}
+
+// A weak reference to a registered asset. It remembers the registration, handle and tier
+// epoch it was taken at, and stops resolving once the asset is evicted, re-registered,
+// relocated by compaction or migration, or its tier is reset.
+#[derive(Clone)]
+pub struct AssetRef<'a> {
+    walloc: &'a Walloc,
+    key: String,
+    handle: MemoryHandle,
+    size: usize,
+    tier: Tier,
+    generation: u64,
+    epoch: usize,
+}
+
+impl AssetRef<'_> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    // The handle, if it still holds the asset this reference was taken for
+    pub fn resolve(&self) -> Option<MemoryHandle> {
+        let (metadata, generation) = self.walloc.assets.get_with_generation(&self.key)?;
+        let current = generation == self.generation
+            && metadata.handle == self.handle
+            && metadata.tier == self.tier
+            && self.walloc.arenas[self.tier as usize].epoch.load(Ordering::SeqCst) == self.epoch;
+        current.then_some(self.handle)
+    }
+    
+    pub fn is_valid(&self) -> bool {
+        self.resolve().is_some()
+    }
+}
+
+// Pins an asset for as long as it lives: eviction skips the key and compaction leaves the
+// bytes where they are, so a view handed to an upload stays valid until it finishes
+pub struct AssetGuard<'a> {
+    walloc: &'a Walloc,
+    key: String,
+    metadata: AssetMetadata,
+    bytes: ReadGuard<'a>,
+}
+
+impl AssetGuard<'_> {
+    // The registered key, also when the guard was taken through an alias
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    
+    pub fn handle(&self) -> MemoryHandle {
+        self.bytes.handle()
+    }
+    
+    pub fn metadata(&self) -> &AssetMetadata {
+        &self.metadata
+    }
+}
+
+impl std::ops::Deref for AssetGuard<'_> {
+    type Target = [u8];
+    
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl AsRef<[u8]> for AssetGuard<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Drop for AssetGuard<'_> {
+    fn drop(&mut self) {
+        self.walloc.assets.unpin(&self.key);
+    }
+}
+
+impl std::fmt::Debug for AssetRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetRef")
+            .field("key", &self.key)
+            .field("handle", &self.handle)
+            .field("size", &self.size)
+            .field("tier", &self.tier)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+// ================================
+// === MEMORY BACKENDS ===
+// ================================
+
+// Where walloc's single contiguous block comes from. Implement this to place the tiers in
+// GPU host-visible heaps, shared memory segments and the like; see Walloc::with_backend.
+pub trait MemoryBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    
+    // Address space for `size` bytes. The block must stay at this address until release.
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str>;
+    
+    // Make reserved bytes readable and writable. Backends that hand out usable memory from
+    // reserve keep the default.
+    /// # Safety
+    /// `start..start + len` must lie inside a block returned by this backend's `reserve`.
+    unsafe fn commit(&self, start: *mut u8, len: usize) -> Result<(), &'static str> {
+        let _ = (start, len);
+        Ok(())
+    }
+    
+    // Extend the block in place by at least `additional` bytes, returning the new total size
+    fn grow(&self, additional: usize) -> Option<usize> {
+        let _ = additional;
+        None
+    }
+    
+    // Let the OS reclaim a committed range. It must stay usable; contents become unspecified.
+    /// # Safety
+    /// The range must be committed memory from this backend holding no live data.
+    unsafe fn decommit(&self, start: *mut u8, len: usize) -> bool {
+        let _ = (start, len);
+        false
+    }
+    
+    /// # Safety
+    /// `base` and `size` must be exactly what `reserve` was given and returned, and nothing
+    /// may use the block afterwards.
+    unsafe fn release(&self, base: *mut u8, size: usize);
+}
+
+// Trimmed ranges are aligned to this, which covers 4K and 16K pages and Windows' granularity
+const TRIM_GRANULE: usize = 64 * 1024;
+
+#[cfg(unix)]
+mod os {
+    pub const PROT_NONE: i32 = 0;
+    pub const PROT_READ: i32 = 1;
+    pub const PROT_READ_WRITE: i32 = 1 | 2;
+    pub const MAP_PRIVATE: i32 = 0x02;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const MAP_ANONYMOUS: i32 = 0x20;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const MAP_ANONYMOUS: i32 = 0x1000;
+    #[cfg(not(miri))]
+    pub const MADV_DONTNEED: i32 = 4;
+    pub const MAP_SHARED: i32 = 0x01;
+    pub const O_RDWR: i32 = 0x02;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub const O_CREAT_EXCL: i32 = 0o100 | 0o200;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub const O_CREAT_EXCL: i32 = 0x0200 | 0x0800;
+    pub const SEEK_END: i32 = 2;
+    
+    unsafe extern "C" {
+        pub fn shm_open(name: *const std::ffi::c_char, oflag: i32, ...) -> i32;
+        pub fn shm_unlink(name: *const std::ffi::c_char) -> i32;
+        pub fn ftruncate(fd: i32, length: i64) -> i32;
+        pub fn lseek(fd: i32, offset: i64, whence: i32) -> i64;
+        pub fn close(fd: i32) -> i32;
+        pub fn mmap(addr: *mut std::ffi::c_void, len: usize, prot: i32, flags: i32, fd: i32, offset: i64) -> *mut std::ffi::c_void;
+        pub fn mprotect(addr: *mut std::ffi::c_void, len: usize, prot: i32) -> i32;
+        pub fn munmap(addr: *mut std::ffi::c_void, len: usize) -> i32;
+        #[cfg(not(miri))]
+        pub fn madvise(addr: *mut std::ffi::c_void, len: usize, advice: i32) -> i32;
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    pub const MEM_COMMIT: u32 = 0x0000_1000;
+    pub const MEM_RESERVE: u32 = 0x0000_2000;
+    pub const MEM_RESET: u32 = 0x0008_0000;
+    pub const MEM_RELEASE: u32 = 0x0000_8000;
+    pub const PAGE_NOACCESS: u32 = 0x01;
+    pub const PAGE_READWRITE: u32 = 0x04;
+    
+    unsafe extern "system" {
+        pub fn VirtualAlloc(address: *mut std::ffi::c_void, size: usize, allocation_type: u32, protect: u32) -> *mut std::ffi::c_void;
+        pub fn VirtualFree(address: *mut std::ffi::c_void, size: usize, free_type: u32) -> i32;
+    }
+}
+
+// Drop the physical pages behind a committed range while keeping it mapped
+#[cfg(not(target_arch = "wasm32"))]
+fn discard_pages(start: *mut u8, len: usize) -> bool {
+    #[cfg(all(unix, not(miri)))]
+    { unsafe { os::madvise(start.cast(), len, os::MADV_DONTNEED) == 0 } }
+    
+    // MEM_DECOMMIT would make the pages fault until recommitted; MEM_RESET keeps them usable
+    #[cfg(all(windows, not(miri)))]
+    { unsafe { !os::VirtualAlloc(start.cast(), len, os::MEM_RESET, os::PAGE_READWRITE).is_null() } }
+    
+    // Miri can't call into the OS, so decommits simply report that nothing was released
+    #[cfg(any(miri, not(any(unix, windows))))]
+    {
+        let _ = (start, len);
+        false
+    }
+}
+
+// The global allocator; the default native backing
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SystemBackend;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SystemBackend {
+    fn layout(size: usize) -> Result<std::alloc::Layout, &'static str> {
+        std::alloc::Layout::from_size_align(size, 4096).map_err(|_| "Invalid memory layout")
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl MemoryBackend for SystemBackend {
+    fn name(&self) -> &'static str {
+        "system"
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        let memory_base = unsafe { std::alloc::alloc(Self::layout(size)?) };
+        if memory_base.is_null() {
+            return Err("Failed to allocate memory for Walloc");
+        }
+        Ok(memory_base)
+    }
+    
+    unsafe fn decommit(&self, start: *mut u8, len: usize) -> bool {
+        discard_pages(start, len)
+    }
+    
+    unsafe fn release(&self, base: *mut u8, size: usize) {
+        if let Ok(layout) = Self::layout(size) {
+            unsafe { std::alloc::dealloc(base, layout) };
+        }
+    }
+}
+
+// Address space reserved straight from the OS (mmap / VirtualAlloc) and committed separately,
+// so untouched and trimmed pages never count against the process
+#[cfg(any(unix, windows))]
+pub struct VirtualMemoryBackend;
+
+#[cfg(unix)]
+impl MemoryBackend for VirtualMemoryBackend {
+    fn name(&self) -> &'static str {
+        "mmap"
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        let base = unsafe {
+            os::mmap(std::ptr::null_mut(), size, os::PROT_NONE, os::MAP_PRIVATE | os::MAP_ANONYMOUS, -1, 0)
+        };
+        if base as isize == -1 {
+            return Err("mmap failed to reserve address space");
+        }
+        Ok(base.cast())
+    }
+    
+    unsafe fn commit(&self, start: *mut u8, len: usize) -> Result<(), &'static str> {
+        match unsafe { os::mprotect(start.cast(), len, os::PROT_READ_WRITE) } {
+            0 => Ok(()),
+            _ => Err("mprotect failed to commit memory"),
+        }
+    }
+    
+    unsafe fn decommit(&self, start: *mut u8, len: usize) -> bool {
+        discard_pages(start, len)
+    }
+    
+    unsafe fn release(&self, base: *mut u8, size: usize) {
+        unsafe { os::munmap(base.cast(), size) };
+    }
+}
+
+#[cfg(windows)]
+impl MemoryBackend for VirtualMemoryBackend {
+    fn name(&self) -> &'static str {
+        "virtual-alloc"
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        let base = unsafe { os::VirtualAlloc(std::ptr::null_mut(), size, os::MEM_RESERVE, os::PAGE_NOACCESS) };
+        if base.is_null() {
+            return Err("VirtualAlloc failed to reserve address space");
+        }
+        Ok(base.cast())
+    }
+    
+    unsafe fn commit(&self, start: *mut u8, len: usize) -> Result<(), &'static str> {
+        match unsafe { os::VirtualAlloc(start.cast(), len, os::MEM_COMMIT, os::PAGE_READWRITE) }.is_null() {
+            false => Ok(()),
+            true => Err("VirtualAlloc failed to commit memory"),
+        }
+    }
+    
+    unsafe fn decommit(&self, start: *mut u8, len: usize) -> bool {
+        discard_pages(start, len)
+    }
+    
+    unsafe fn release(&self, base: *mut u8, _size: usize) {
+        unsafe { os::VirtualFree(base.cast(), 0, os::MEM_RELEASE) };
+    }
+}
+
+// Memory walloc manages but does not own, such as external buffers. Nothing is
+// decommitted or released.
+struct BorrowedBackend {
+    name: &'static str,
+    base: *mut u8,
+    len: usize,
+}
+
+unsafe impl Send for BorrowedBackend {}
+unsafe impl Sync for BorrowedBackend {}
+
+impl MemoryBackend for BorrowedBackend {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        if self.base.is_null() && !cfg!(target_arch = "wasm32") {
+            return Err("External buffer is null");
+        }
+        if size > self.len {
+            return Err("External buffer is smaller than requested");
+        }
+        Ok(self.base)
+    }
+    
+    unsafe fn release(&self, _base: *mut u8, _size: usize) {}
+}
+
+// The module's linear memory, which always starts at 0 and can only grow
+#[cfg(target_arch = "wasm32")]
+pub struct WasmMemoryBackend;
+
+#[cfg(target_arch = "wasm32")]
+impl MemoryBackend for WasmMemoryBackend {
+    fn name(&self) -> &'static str {
+        "wasm-linear-memory"
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        if size > core::arch::wasm32::memory_size(0) * 65536 {
+            return Err("Linear memory is smaller than requested");
+        }
+        Ok(std::ptr::null_mut())
+    }
+    
+    fn grow(&self, additional: usize) -> Option<usize> {
+        let pages = additional.div_ceil(65536);
+        let old_pages = core::arch::wasm32::memory_grow(0, pages);
+        if old_pages == usize::MAX {
+            return None;
+        }
+        Some((old_pages + pages) * 65536)
+    }
+    
+    unsafe fn release(&self, _base: *mut u8, _size: usize) {}
+}
+
+// ================================
+// === SHARED MEMORY ===
+// ================================
+
+// A named POSIX shared-memory segment (shm_open), so several processes can map the same
+// tiers. Handles are offsets, so they mean the same thing in every process. The creating
+// process unlinks the name when its Walloc is dropped; existing mappings stay valid.
+#[cfg(unix)]
+pub struct SharedMemoryBackend {
+    name: std::ffi::CString,
+    create: bool,
+    mapped: AtomicUsize,  // The whole mapping, including the registry area after the tiers
+}
+
+#[cfg(unix)]
+impl SharedMemoryBackend {
+    pub fn create(name: &str) -> Result<Self, &'static str> {
+        Self::new(name, true)
+    }
+    
+    pub fn open(name: &str) -> Result<Self, &'static str> {
+        Self::new(name, false)
+    }
+    
+    fn new(name: &str, create: bool) -> Result<Self, &'static str> {
+        let name = std::ffi::CString::new(format!("/{}", name.trim_start_matches('/')))
+            .map_err(|_| "Segment name contains a NUL byte")?;
+        Ok(Self { name, create, mapped: AtomicUsize::new(0) })
+    }
+}
+
+#[cfg(unix)]
+impl MemoryBackend for SharedMemoryBackend {
+    fn name(&self) -> &'static str {
+        "shm"
+    }
+    
+    fn reserve(&self, size: usize) -> Result<*mut u8, &'static str> {
+        let flags = if self.create { os::O_RDWR | os::O_CREAT_EXCL } else { os::O_RDWR };
+        let fd = unsafe { os::shm_open(self.name.as_ptr(), flags, 0o600 as std::ffi::c_uint) };
+        if fd < 0 {
+            return Err(if self.create { "Shared segment already exists or cannot be created" } else { "Shared segment not found" });
+        }
+        
+        let sized = if self.create {
+            unsafe { os::ftruncate(fd, size as i64) == 0 }
+        } else {
+            unsafe { os::lseek(fd, 0, os::SEEK_END) >= size as i64 }
+        };
+        let base = match sized {
+            true => unsafe { os::mmap(std::ptr::null_mut(), size, os::PROT_READ_WRITE, os::MAP_SHARED, fd, 0) },
+            false => -1isize as *mut std::ffi::c_void,
+        };
+        unsafe { os::close(fd) };
+        
+        if base as isize == -1 {
+            if self.create {
+                unsafe { os::shm_unlink(self.name.as_ptr()) };
+            }
+            return Err("Shared segment could not be sized or mapped");
+        }
+        self.mapped.store(size, Ordering::Release);
+        Ok(base.cast())
+    }
+    
+    // Other processes may still be using pages past this process's bump heads
+    unsafe fn decommit(&self, _start: *mut u8, _len: usize) -> bool {
+        false
+    }
+    
+    unsafe fn release(&self, base: *mut u8, _size: usize) {
+        unsafe {
+            os::munmap(base.cast(), self.mapped.load(Ordering::Acquire));
+            if self.create {
+                os::shm_unlink(self.name.as_ptr());
+            }
+        }
+    }
+}
+
+// The asset registry, published by one process into an area after the tiers and mirrored
+// by the others. Layout: magic "WALLOCSR" | sequence: u64 | len: u64 | heap-dump ASSETS
+// payload. The sequence is odd while the publisher is writing.
+#[cfg(unix)]
+const SHARED_REGISTRY_MAGIC: &[u8; 8] = b"WALLOCSR";
+#[cfg(unix)]
+const SHARED_REGISTRY_BYTES: usize = 1024 * 1024;
+#[cfg(unix)]
+const SHARED_REGISTRY_HEADER: usize = 24;
+
+#[cfg(unix)]
+struct SharedRegistry {
+    area: *mut u8,
+    synced: AtomicU64,  // Sequence last mirrored by this process
+}
+
+#[cfg(unix)]
+impl SharedRegistry {
+    fn sequence(&self) -> &AtomicU64 {
+        unsafe { &*(self.area.add(8) as *const AtomicU64) }
+    }
+}
+
+#[cfg(unix)]
+impl Walloc {
+    // Create the named segment with `memory_size` bytes of tiers. This process is the one
+    // that allocates and publishes; see open_shared.
+    pub fn create_shared(name: &str, memory_size: usize) -> Result<Self, &'static str> {
+        let walloc = Self::with_shared_backend(SharedMemoryBackend::create(name)?, memory_size)?;
+        let registry = walloc.shared_registry.as_ref().unwrap();
+        unsafe {
+            std::ptr::copy_nonoverlapping(SHARED_REGISTRY_MAGIC.as_ptr(), registry.area, 8);
+        }
+        walloc.publish_shared_registry()?;
+        Ok(walloc)
+    }
+    
+    // Map a segment made by create_shared in another process. Allocation state is per
+    // process, so attached processes should only read, and mirror the creator's assets
+    // with sync_shared_registry.
+    pub fn open_shared(name: &str, memory_size: usize) -> Result<Self, &'static str> {
+        let walloc = Self::with_shared_backend(SharedMemoryBackend::open(name)?, memory_size)?;
+        // The creator's blocks are live even though this process never allocated them
+        asan_unpoison(walloc.memory_base, walloc.memory_size);
+        let area = walloc.shared_registry.as_ref().unwrap().area;
+        if unsafe { std::slice::from_raw_parts(area, 8) } != SHARED_REGISTRY_MAGIC {
+            return Err("Segment was not created by walloc");
+        }
+        Ok(walloc)
+    }
+    
+    fn with_shared_backend(backend: SharedMemoryBackend, memory_size: usize) -> Result<Self, &'static str> {
+        if memory_size > MAX_MEMORY_LIMIT {
+            return Err("Memory size exceeds platform limit");
+        }
+        let memory_base = backend.reserve(memory_size + SHARED_REGISTRY_BYTES)?;
+        let mut walloc = Self::with_memory(Box::new(backend), memory_base, memory_size, DEFAULT_TIER_SPLIT)?;
+        walloc.shared_registry = Some(SharedRegistry {
+            area: unsafe { memory_base.add(memory_size) },
+            synced: AtomicU64::new(0),
+        });
+        Ok(walloc)
+    }
+    
+    // Write this process's asset registry to the segment. Returns the new sequence number.
+    pub fn publish_shared_registry(&self) -> Result<u64, &'static str> {
+        let registry = self.shared_registry.as_ref().ok_or("Walloc is not backed by shared memory")?;
+        let payload = self.encode_assets(self.handle_space);
+        if payload.len() > SHARED_REGISTRY_BYTES - SHARED_REGISTRY_HEADER {
+            return Err("Asset registry does not fit the shared segment");
+        }
+        
+        let sequence = registry.sequence();
+        let start = sequence.load(Ordering::Acquire) & !1;
+        sequence.store(start + 1, Ordering::Release);
+        std::sync::atomic::fence(Ordering::SeqCst);
+        unsafe {
+            std::ptr::write_unaligned(registry.area.add(16) as *mut u64, payload.len() as u64);
+            std::ptr::copy_nonoverlapping(payload.as_ptr(), registry.area.add(SHARED_REGISTRY_HEADER), payload.len());
+        }
+        sequence.store(start + 2, Ordering::Release);
+        registry.synced.store(start + 2, Ordering::Release);
+        Ok(start + 2)
+    }
+    
+    // Mirror the published registry: new and moved assets are registered, assets the
+    // publisher dropped are unregistered (their memory is the publisher's to free).
+    // Returns the number of published assets, or 0 if nothing changed since the last sync.
+    pub fn sync_shared_registry(&self) -> Result<usize, &'static str> {
+        let registry = self.shared_registry.as_ref().ok_or("Walloc is not backed by shared memory")?;
+        let sequence = registry.sequence();
+        
+        let (published, payload) = loop {
+            let before = sequence.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            if before == registry.synced.load(Ordering::Acquire) {
+                return Ok(0);
+            }
+            
+            let len = unsafe { std::ptr::read_unaligned(registry.area.add(16) as *const u64) } as usize;
+            let len = len.min(SHARED_REGISTRY_BYTES - SHARED_REGISTRY_HEADER);
+            let payload = unsafe { std::slice::from_raw_parts(registry.area.add(SHARED_REGISTRY_HEADER), len) }.to_vec();
+            std::sync::atomic::fence(Ordering::Acquire);
+            if sequence.load(Ordering::Acquire) == before {
+                break (before, payload);
+            }
+        };
+        
+        let assets = DumpReader { bytes: &payload, pos: 0 }.assets()
+            .map_err(|_| "Shared registry is corrupt")?;
+        let keys: std::collections::HashSet<_> = assets.iter().map(|asset| asset.key.as_str()).collect();
+        let stale: Vec<_> = self.assets.assets.read().unwrap().keys()
+            .filter(|key| !keys.contains(key.as_str()))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.assets.remove(&key);
+        }
+        for asset in &assets {
+            let handle = self.handle_at(asset.offset);
+            if self.assets.get(&asset.key).is_none_or(|known| known.handle != handle || known.size != asset.size) {
+                self.assets.insert(asset.key.clone(), AssetMetadata::new(asset.asset_type, handle, asset.size, asset.tier));
+            }
+        }
+        
+        registry.synced.store(published, Ordering::Release);
+        walloc_event!(debug, sequence = published, assets = assets.len(), "synced shared registry");
+        Ok(assets.len())
+    }
+}
+
+// ================================
+// === MAPPED FILES ===
+// ================================
+
+// Read-only private mappings of local pack files. Their views are registered as pinned
+// Middle assets whose handles resolve like any other (the offset from the memory base wraps
+// when the mapping lies below it), but the bytes stay in the page cache instead of a tier.
+// Compaction and eviction leave them alone; unmap_asset or dropping the Walloc unmaps them.
+#[cfg(unix)]
+const MAPPED_VIEW_ALIGN: usize = 4096;
+
+#[cfg(unix)]
+#[derive(Clone, Debug)]
+pub struct MappedView {
+    pub name: String,      // Registry key
+    pub offset: usize,     // Into the file, a multiple of 4 KiB
+    pub len: usize,
+    pub asset_type: AssetType,
+}
+
+#[cfg(unix)]
+struct MappedFile {
+    path: String,
+    ptr: *mut u8,
+    len: usize,
+    views: Vec<String>,
+}
+
+#[cfg(unix)]
+impl MappedFile {
+    fn open(path: &std::path::Path) -> io::Result<Self> {
+        use std::os::fd::AsRawFd;
+        
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot map an empty file"));
+        }
+        // The mapping outlives the descriptor, which closes when `file` drops
+        let ptr = unsafe { os::mmap(std::ptr::null_mut(), len, os::PROT_READ, os::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(MappedFile { path: path.to_string_lossy().into_owned(), ptr: ptr.cast(), len, views: Vec::new() })
+    }
+    
+    fn contains(&self, start: *const u8, len: usize) -> bool {
+        let start = start as usize;
+        let base = self.ptr as usize;
+        start >= base && start.checked_add(len).is_some_and(|end| end <= base + self.len)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        unsafe {
+            os::munmap(self.ptr.cast(), self.len);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Walloc {
+    // The whole file as one Binary asset, registered under its path
+    pub fn load_asset_mmap(&self, path: impl AsRef<std::path::Path>) -> io::Result<MemoryHandle> {
+        let mapping = MappedFile::open(path.as_ref())?;
+        let view = MappedView { name: mapping.path.clone(), offset: 0, len: mapping.len, asset_type: AssetType::Binary };
+        Ok(self.register_mapping(mapping, &[view])?[0])
+    }
+    
+    // Maps the file once and registers each view, returning their handles in order. Mapping
+    // a path again replaces the previous mapping and its views.
+    pub fn load_pack_mmap(&self, path: impl AsRef<std::path::Path>, views: &[MappedView]) -> io::Result<Vec<MemoryHandle>> {
+        let mapping = MappedFile::open(path.as_ref())?;
+        self.register_mapping(mapping, views)
+    }
+    
+    fn register_mapping(&self, mut mapping: MappedFile, views: &[MappedView]) -> io::Result<Vec<MemoryHandle>> {
+        for view in views {
+            if view.offset % MAPPED_VIEW_ALIGN != 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("View '{}' is not page-aligned", view.name)));
+            }
+            if view.offset.checked_add(view.len).is_none_or(|end| end > mapping.len) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("View '{}' runs past the end of the file", view.name)));
+            }
+        }
+        self.unmap_asset(&mapping.path);
+        
+        let handles = views.iter()
+            .map(|view| {
+                let handle = MemoryHandle::from_ptr(mapping.ptr.wrapping_add(view.offset), self.handle_base);
+                self.assets.insert(view.name.clone(), AssetMetadata::new(view.asset_type, handle, view.len, Tier::Middle).pinned());
+                handle
+            })
+            .collect();
+        mapping.views = views.iter().map(|view| view.name.clone()).collect();
+        walloc_event!(debug, bytes = mapping.len, views = views.len(), "pack mapped");
+        self.mappings.lock().unwrap().push(mapping);
+        Ok(handles)
+    }
+    
+    // Unregisters the file's views and unmaps it. Their handles dangle afterwards.
+    pub fn unmap_asset(&self, path: impl AsRef<std::path::Path>) -> bool {
+        let path = path.as_ref().to_string_lossy();
+        let mapping = {
+            let mut mappings = self.mappings.lock().unwrap();
+            match mappings.iter().position(|mapping| mapping.path == path) {
+                Some(index) => mappings.swap_remove(index),
+                None => return false,
+            }
+        };
+        // A view name may have been re-registered for something else since
+        for name in &mapping.views {
+            if self.assets.get(name).is_some_and(|metadata| mapping.contains(self.handle_ptr(metadata.handle), 0)) {
+                self.assets.remove(name);
+            }
+        }
+        true
+    }
+    
+    pub fn is_mapped(&self, handle: MemoryHandle) -> bool {
+        self.is_mapped_range(handle, 0)
+    }
+    
+    fn is_mapped_range(&self, handle: MemoryHandle, len: usize) -> bool {
+        !handle.is_null() && self.mappings.lock().unwrap().iter().any(|mapping| mapping.contains(self.handle_ptr(handle), len))
+    }
+}
+
+// ================================
+// === PLATFORM STRATEGIES ===
+// ================================
+
+#[cfg(target_arch = "wasm32")]
+pub struct WasmStrategy {
+    initial_pages: AtomicUsize,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl WasmStrategy {
+    pub fn new() -> Self {
+        Self {
+            initial_pages: AtomicUsize::new(core::arch::wasm32::memory_size(0)),
+        }
+    }
+    
+    pub fn try_grow(&self, backend: &dyn MemoryBackend, arena: &LockFreeArena, size: usize) -> Option<usize> {
+        let current_usage = arena.usage();
+        let available = arena.capacity().saturating_sub(current_usage);
+        
+        if available >= size {
+            return None;
+        }
+        
+        let needed = size - available;
+        let pages_needed = (needed + 65535) / 65536;
+        let actual_pages = pages_needed.max(16);
+        
+        let new_total_size = backend.grow(actual_pages * 65536)?;
+        let tier_percentage = arena.tier.memory_percentage();
+        let new_tier_size = (new_total_size * tier_percentage) / 100;
+        
+        unsafe {
+            arena.extend_capacity(new_tier_size);
+        }
+        
+        arena.allocate(size)
+    }
+}
+
+impl LockFreeArena {
+    // Decommit whole granules between the bump head and the end of the tier. The range is
+    // claimed by holding the head at capacity for the syscall; usage() keeps reporting the
+    // saved head and bump allocations wait for it (see wait_for_trim). Returns 0 if another
+    // trim of this tier is running.
+    fn trim(&self, backend: &dyn MemoryBackend) -> usize {
+        let capacity = self.capacity();
+        let mut head = self.allocation_head.load(Ordering::Acquire);
+        if self.trim_head.compare_exchange(usize::MAX, head, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            return 0;
+        }
+        while let Err(current) = self.allocation_head.compare_exchange_weak(head, capacity, Ordering::AcqRel, Ordering::Acquire) {
+            head = current;
+            self.trim_head.store(head, Ordering::Release);
+        }
+        
+        let tier_start = self.base_ptr() as usize;
+        let start = (tier_start + head).next_multiple_of(TRIM_GRANULE);
+        let end = (tier_start + capacity) & !(TRIM_GRANULE - 1);
+        let resident = (tier_start + self.resident_end.load(Ordering::Acquire)).min(end);
+        
+        let mut returned = 0;
+        if resident > start && unsafe { backend.decommit(start as *mut u8, end - start) } {
+            returned = resident - start;
+            self.resident_end.store(start - tier_start, Ordering::Release);
+        }
+        
+        // A reset racing with the trim owns the head now
+        let _ = self.allocation_head.compare_exchange(capacity, head, Ordering::AcqRel, Ordering::Acquire);
+        self.trim_head.store(usize::MAX, Ordering::Release);
+        returned
+    }
+}
+
+// ================================
+// === INCREMENTAL COMPACTION ===
+// ================================
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactionProgress {
+    pub tier: Tier,
+    pub assets_moved: usize,
+    pub assets_remaining: usize,
+    pub bytes_moved: usize,
+    pub bytes_reclaimed: usize,
+    pub complete: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompactionMode {
+    // Run the whole slide in the first idle tick after the policy trips
+    Synchronous,
+    // Plan the slide and advance it by `budget_bytes` per idle tick
+    Incremental { budget_bytes: usize },
+}
+
+// "Compact Middle when >30% fragmented and idle" is
+// CompactionPolicy { threshold: 0.3, min_free_bytes: 0, only_when_idle: true, mode }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CompactionPolicy {
+    pub threshold: f64,        // Freelisted bytes / bump head, 0.0..=1.0
+    pub min_free_bytes: usize, // Ignore fragmentation below this many freelisted bytes
+    pub only_when_idle: bool,  // Otherwise frees may plan the job early; data only moves when idle
+    pub mode: CompactionMode,
+}
+
+// A planned slide of one tier's registered assets toward the arena base. Work is applied in
+// bounded steps, so everything needed to resume between calls lives here.
+struct CompactionJob {
+    tier: Tier,
+    assets: Vec<(String, MemoryHandle, usize)>, // (key, handle, size) in address order
+    cursor: usize,
+    start_head: usize,   // Bump head when the job was planned
+    epoch: usize,
+    assets_moved: usize,
+    bytes_moved: usize,
+}
+
+impl CompactionJob {
+    fn progress(&self, bytes_reclaimed: usize) -> CompactionProgress {
+        CompactionProgress {
+            tier: self.tier,
+            assets_moved: self.assets_moved,
+            assets_remaining: self.assets.len() - self.cursor,
+            bytes_moved: self.bytes_moved,
+            bytes_reclaimed,
+            complete: self.cursor == self.assets.len(),
+        }
+    }
+}
+
+// Only the registry knows where assets live; plain allocations, WBox and WVec blocks below
+// the head are invisible to it. So a job never assumes a gap is garbage: it starts from the
+// parked blocks, adds whatever is freed under it while it runs, and slides a survivor only
+// over a free run that ends right where the survivor starts.
+impl LockFreeArena {
+    // Fence off everything below the current head and take its freelists as the job's free
+    // ranges. Returns the head the job starts from.
+    fn plan_compaction(&self) -> usize {
+        // Open thread slices and slabs must not keep handing out memory inside the region
+        self.retire_slices();
+        self.retire_slabs();
+        self.drain_free_queues();
+        
+        let mut ranges = self.compaction_free.lock().unwrap();
+        let start_head = self.usage();
+        self.compaction_limit.store(start_head, Ordering::SeqCst);
+        let base = self.base_ptr() as usize;
+        ranges.clear();
+        ranges.extend(self.detach_freelists().into_iter().map(|(node, size)| (node as usize - base, node as usize - base + size)));
+        merge_ranges(&mut ranges);
+        start_head
+    }
+    
+    // Called by frees under the compaction limit; false if the job ended in the meantime
+    fn hold_for_compaction(&self, local_offset: usize, size: usize) -> bool {
+        let mut ranges = self.compaction_free.lock().unwrap();
+        if local_offset >= self.compaction_limit.load(Ordering::Acquire) {
+            return false;
+        }
+        ranges.push((local_offset, local_offset + size));
+        true
+    }
+    
+    // Where the block at `local_offset` may slide to: the start of the free run ending at it
+    fn slide_target(&self, local_offset: usize) -> Option<usize> {
+        let mut ranges = self.compaction_free.lock().unwrap();
+        merge_ranges(&mut ranges);
+        ranges.iter().find(|&&(_, end)| end == local_offset).map(|&(start, _)| start)
+    }
+    
+    fn commit_slide(&self, from: usize, to: usize, block_size: usize) {
+        let mut ranges = self.compaction_free.lock().unwrap();
+        ranges.retain(|&(start, end)| end <= to || start >= to + block_size);
+        ranges.push((to + block_size, from + block_size));
+        merge_ranges(&mut ranges);
+    }
+    
+    // Lift the fence. A free run ending at the planned head goes back to bump space if nothing
+    // was bumped meanwhile; every other range returns to the freelists. Returns the bytes
+    // handed back to bump space.
+    fn end_compaction(&self, start_head: usize) -> usize {
+        let mut ranges = {
+            let mut ranges = self.compaction_free.lock().unwrap();
+            self.compaction_limit.store(0, Ordering::SeqCst);
+            std::mem::take(&mut *ranges)
+        };
+        merge_ranges(&mut ranges);
+        
+        let mut reclaimed = 0;
+        if let Some(&(start, end)) = ranges.last()
+            && end == start_head
+            && self.allocation_head.compare_exchange(start_head, start, Ordering::SeqCst, Ordering::SeqCst).is_ok()
+        {
+            ranges.pop();
+            reclaimed = end - start;
+        }
+        for (start, end) in ranges {
+            self.park_block(unsafe { self.base_ptr().add(start) } as *mut FreeNode, end - start);
+        }
+        reclaimed
+    }
+    
+    // The arena was reset or fast compacted under the job, so its ranges mean nothing now
+    fn abandon_compaction(&self) {
+        let mut ranges = self.compaction_free.lock().unwrap();
+        self.compaction_limit.store(0, Ordering::SeqCst);
+        ranges.clear();
+    }
+}
+
+// Sort and coalesce (start, end) ranges, dropping empty ones
+fn merge_ranges(ranges: &mut Vec<(usize, usize)>) {
+    ranges.retain(|&(start, end)| start < end);
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *ranges = merged;
+}
+
+// ================================
+// === MEMORY WATERMARKS ===
+// ================================
+
+// Delivered once when a tier's live bytes cross a threshold upward, and once more when
+// they fall back below it, so streaming systems can start and stop unloading early.
+#[derive(Clone, Copy, Debug)]
+pub struct WatermarkEvent {
+    pub tier: Tier,
+    pub threshold: f64,
+    pub used: usize,
+    pub capacity: usize,
+    pub rising: bool,
+}
+
+type WatermarkCallback = Arc<dyn Fn(&WatermarkEvent) + Send + Sync>;
+
+struct Watermark {
+    threshold: f64,
+    above: AtomicBool,
+    callback: WatermarkCallback,
+}
+
+#[derive(Default)]
+struct TierWatermarks {
+    active: AtomicBool,  // Keeps the allocation fast path to one load when nothing is registered
+    entries: RwLock<Vec<Watermark>>,
+}
+
+impl TierWatermarks {
+    fn add(&self, threshold: f64, callback: WatermarkCallback) {
+        self.entries.write().unwrap().push(Watermark {
+            threshold: threshold.clamp(0.0, 1.0),
+            above: AtomicBool::new(false),
+            callback,
+        });
+        self.active.store(true, Ordering::Release);
+    }
+    
+    fn clear(&self) {
+        self.active.store(false, Ordering::Release);
+        self.entries.write().unwrap().clear();
+    }
+    
+    // Callbacks run after the lock is released so they may register or clear watermarks
+    fn check(&self, tier: Tier, used: usize, capacity: usize) {
+        let fill = used as f64 / capacity.max(1) as f64;
+        let fired: Vec<_> = self.entries.read().unwrap().iter()
+            .filter_map(|mark| {
+                let rising = fill >= mark.threshold;
+                mark.above.compare_exchange(!rising, rising, Ordering::AcqRel, Ordering::Relaxed).ok()?;
+                Some((mark.callback.clone(), WatermarkEvent { tier, threshold: mark.threshold, used, capacity, rising }))
+            })
+            .collect();
+        
+        for (callback, event) in fired {
+            walloc_event!(info, ?tier, threshold = event.threshold, used, rising = event.rising, "watermark");
+            callback(&event);
+        }
+    }
+}
+
+// ================================
+// === OOM HANDLING ===
+// ================================
+
+// What the OOM handler sees each time an allocation has failed, including after growth
+#[derive(Clone, Copy, Debug)]
+pub struct OomInfo {
+    pub tier: Tier,
+    pub requested: usize,
+    pub used: usize,
+    pub capacity: usize,
+    pub attempt: u32,  // 0 on the first call for this allocation
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OomAction {
+    Fail,         // Give up; allocate returns None
+    Retry,        // The handler freed memory itself
+    Evict,        // Evict unpinned assets in allocate_or_evict order until it fits
+    Compact,      // Run a full compaction of the tier's assets, then retry
+    Spill(Tier),  // allocate_spillable serves the request from this tier instead; elsewhere a Fail
+    Abort,        // Panic with the OomInfo
+}
+
+// The handler is consulted at most this many times per allocation
+const OOM_MAX_ATTEMPTS: u32 = 8;
+
+type OomHandler = Arc<dyn Fn(OomInfo) -> OomAction + Send + Sync>;
+
+// ================================
+// === DEFERRED FREES ===
+// ================================
+
+// A block the GPU may still read, freed by flush_deferred once `fence` has completed. The
+// epoch catches resets in between, after which the block no longer exists to be freed.
+struct DeferredFree {
+    fence: u64,
+    handle: MemoryHandle,
+    size: usize,
+    tier: Tier,
+    epoch: usize,
+}
+
+// ================================
+// === CHECKED SLICE ACCESS ===
+// ================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessError {
+    NullHandle,
+    OutOfBounds,  // Range is not inside a single tier's allocated region or asset
+    Aliased,      // Range overlaps a slice currently lent out by with_slice/with_slice_ref
+    UnknownAsset,
+    Misaligned,   // Handle or tier alignment is too small for the requested element type
+    WrongInstance,  // InstanceHandle from another Walloc, or this one's memory is no longer current
+}
+
+impl std::fmt::Display for AccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AccessError::NullHandle => "Memory handle is null",
+            AccessError::OutOfBounds => "Memory access out of bounds",
+            AccessError::Aliased => "Memory range is already borrowed",
+            AccessError::UnknownAsset => "Asset is not registered",
+            AccessError::Misaligned => "Memory handle is misaligned for type",
+            AccessError::WrongInstance => "Memory handle belongs to another walloc instance",
+        })
+    }
+}
+
+impl std::error::Error for AccessError {}
+
+#[derive(Clone, Copy)]
+struct ActiveBorrow {
+    start: usize,
+    end: usize,
+    exclusive: bool,
+}
+
+// Ranges currently lent out. Overlap checks are linear, which is fine for the handful of
+// borrows a frame typically holds at once.
+#[derive(Default)]
+struct BorrowTracker {
+    active: Mutex<Vec<ActiveBorrow>>,
+}
+
+impl BorrowTracker {
+    fn acquire(&self, start: usize, end: usize, exclusive: bool) -> Result<BorrowGuard<'_>, AccessError> {
+        let mut active = self.active.lock().unwrap();
+        let conflict = active.iter().any(|borrow| {
+            borrow.start < end && start < borrow.end && (exclusive || borrow.exclusive)
+        });
+        if conflict {
+            return Err(AccessError::Aliased);
+        }
+        
+        active.push(ActiveBorrow { start, end, exclusive });
+        Ok(BorrowGuard { tracker: self, start, end, exclusive })
+    }
+}
+
+// Releases the range even if the closure panics
+struct BorrowGuard<'a> {
+    tracker: &'a BorrowTracker,
+    start: usize,
+    end: usize,
+    exclusive: bool,
+}
+
+impl Drop for BorrowGuard<'_> {
+    fn drop(&mut self) {
+        let mut active = self.tracker.active.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(index) = active.iter().position(|borrow| {
+            borrow.start == self.start && borrow.end == self.end && borrow.exclusive == self.exclusive
+        }) {
+            active.swap_remove(index);
+        }
+    }
+}
+
+// Shared view of `len` bytes at a handle, held until dropped. Writers to the range fail with
+// Aliased meanwhile, and compaction leaves the bytes where they are.
+pub struct ReadGuard<'a> {
+    handle: MemoryHandle,
+    ptr: *const u8,
+    len: usize,
+    _borrow: Option<BorrowGuard<'a>>,  // None for mapped files, which nothing writes or moves
+}
+
+// The pointer is into the borrowed range, so the guards move between threads like the borrow
+unsafe impl Send for ReadGuard<'_> {}
+unsafe impl Sync for ReadGuard<'_> {}
+unsafe impl<T: Pod> Send for SliceMut<'_, T> {}
+unsafe impl<T: Pod> Sync for SliceMut<'_, T> {}
+unsafe impl Send for HandleWriter<'_> {}
+unsafe impl Sync for HandleWriter<'_> {}
+
+impl ReadGuard<'_> {
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+}
+
+impl std::ops::Deref for ReadGuard<'_> {
+    type Target = [u8];
+    
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl AsRef<[u8]> for ReadGuard<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+// Typed views from cast_slice / cast_slice_mut, with the same borrow rules as ReadGuard
+// and with_slice
+pub struct SliceRef<'a, T: Pod> {
+    bytes: ReadGuard<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> std::ops::Deref for SliceRef<'_, T> {
+    type Target = [T];
+    
+    fn deref(&self) -> &[T] {
+        bytemuck::cast_slice(&self.bytes)
+    }
+}
+
+pub struct SliceMut<'a, T: Pod> {
+    ptr: *mut u8,
+    len: usize,
+    _borrow: BorrowGuard<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> std::ops::Deref for SliceMut<'_, T> {
+    type Target = [T];
+    
+    fn deref(&self) -> &[T] {
+        bytemuck::cast_slice(unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) })
+    }
+}
+
+impl<T: Pod> std::ops::DerefMut for SliceMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        bytemuck::cast_slice_mut(unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) })
+    }
+}
+
+// Frees a with_scratch buffer even if the closure panics
+struct ScratchBlock<'a> {
+    walloc: &'a Walloc,
+    handle: MemoryHandle,
+    size: usize,
+}
+
+impl Drop for ScratchBlock<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[Tier::Bottom as usize].deallocate(self.handle, self.size);
+        self.walloc.check_compaction_policy(Tier::Bottom);
+    }
+}
+
+// Streams into `capacity` bytes at a handle, e.g. a decoder's output or a download of known
+// length. The range is borrowed exclusively until the writer is dropped, and a write that
+// would run past the end fails whole, leaving the bytes before it in place.
+pub struct HandleWriter<'a> {
+    handle: MemoryHandle,
+    ptr: *mut u8,
+    capacity: usize,
+    written: usize,
+    _borrow: BorrowGuard<'a>,
+}
+
+impl HandleWriter<'_> {
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    pub fn written(&self) -> usize {
+        self.written
+    }
+    
+    pub fn remaining(&self) -> usize {
+        self.capacity - self.written
+    }
+    
+    // Bytes written, releasing the borrow
+    pub fn finish(self) -> usize {
+        self.written
+    }
+}
+
+impl Write for HandleWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining() {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "Write exceeds the handle's capacity"));
+        }
+        unsafe {
+            SIMDOps::fast_copy(buf.as_ptr(), self.ptr.add(self.written), buf.len());
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Arena writes never block, so every poll completes immediately
+impl futures::io::AsyncWrite for HandleWriter<'_> {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Poll::Ready(self.get_mut().write(buf))
+    }
+    
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+    
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+// ================================
+// === HANDLE INTROSPECTION ===
+// ================================
+
+// What walloc knows about a raw offset, e.g. one passed in from JS bindings
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandleInfo {
+    #[serde(skip)]
+    pub tier: Tier,
+    pub tier_name: &'static str,
+    pub offset: usize,
+    pub tier_offset: usize,        // Distance from the start of the tier's arena
+    pub in_live_range: bool,       // Below the bump head, so handed out at some point
+    pub block_aligned: bool,       // Could be the start of a block in this tier
+    pub alignment: usize,          // Largest power of two dividing the address, capped at 4096
+    pub on_freelist: bool,         // Inside a freed block waiting for reuse
+    pub size: Option<usize>,       // Size requested for the asset or live allocation starting here
+    pub asset: Option<String>,     // Registered asset whose bytes contain the offset
+}
+
+const MAX_REPORTED_ALIGNMENT: usize = 4096;
+
+// ================================
+// === ASYNC ALLOCATION ===
+// ================================
+
+// Retries the allocation each time the tier reports freed space
+struct AllocationWait<'a> {
+    walloc: &'a Walloc,
+    size: usize,
+    tier: Tier,
+}
+
+impl Future for AllocationWait<'_> {
+    type Output = MemoryHandle;
+    
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<MemoryHandle> {
+        if let Some(handle) = self.walloc.try_allocate(self.size, self.tier) {
+            return Poll::Ready(handle);
+        }
+        
+        // Register before the second attempt so a free racing with us is not missed
+        self.walloc.arenas[self.tier as usize].register_waiter(cx.waker());
+        match self.walloc.try_allocate(self.size, self.tier) {
+            Some(handle) => Poll::Ready(handle),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    use wasm_bindgen::JsCast;
+    
+    let millis = duration.as_millis().min(i32::MAX as u128) as i32;
+    let promise = Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        if let Ok(set_timeout) = js_sys::Reflect::get(&global, &JsValue::from_str("setTimeout"))
+            && let Ok(set_timeout) = set_timeout.dyn_into::<js_sys::Function>()
+        {
+            let _ = set_timeout.call2(&global, &resolve, &JsValue::from(millis));
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+// ================================
+// === ALLOCATION HEADERS ===
+// ================================
+
+// Written immediately before the handle returned by allocate_headed. The block starts one
+// tier-aligned prefix earlier, so user data keeps the tier's alignment. The magic sits at
+// the end so the freelist node written over the start of a freed block leaves it intact,
+// which is what lets a second free be recognised.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct AllocationHeader {
+    size: u64,
+    tier: u8,
+    _reserved: [u8; 3],
+    generation: u32,  // Arena epoch at allocation; reset and compaction invalidate the handle
+    magic: u32,
+    _reserved2: u32,
+}
+
+const HEADER_SIZE: usize = std::mem::size_of::<AllocationHeader>();
+const HEADER_LIVE: u32 = 0x5741_4C43;   // "WALC"
+const HEADER_FREED: u32 = 0x4652_4545;  // "FREE"
+
+impl LockFreeArena {
+    fn header_prefix(&self) -> usize {
+        self.align_size(HEADER_SIZE)
+    }
+    
+    // The header in front of `handle`, if the bytes there could hold one
+    fn read_header(&self, handle: MemoryHandle) -> Option<AllocationHeader> {
+        let offset = handle.offset();
+        if !self.contains(handle) || offset < self.base_offset + self.header_prefix() {
+            return None;
+        }
+        let header_ptr = unsafe { handle.resolve(self.handle_base).sub(HEADER_SIZE) };
+        if asan_first_poisoned(header_ptr, HEADER_SIZE).is_some() {
+            return None;
+        }
+        let header = unsafe { std::ptr::read_unaligned(header_ptr as *const AllocationHeader) };
+        matches!(header.magic, HEADER_LIVE | HEADER_FREED).then_some(header)
+    }
+}
+
+// ================================
+// === HTTP CLIENT ===
+// ================================
+
+// Settings for the client that load_asset_unified and the batch loaders fetch through.
+// Unset fields keep reqwest's defaults. Timeouts, proxies, TLS and pooling belong to the
+// browser on WASM, so only the user agent and default headers take effect there.
+#[derive(Clone, Debug, Default)]
+pub struct HttpConfig {
+    pub connect_timeout: Option<Duration>,
+    pub timeout: Option<Duration>,  // Whole request, including reading the body
+    pub proxy: Option<String>,      // Applied to every scheme
+    pub user_agent: Option<String>,
+    pub default_headers: Vec<(String, String)>,
+    pub accept_invalid_certs: bool,
+    pub root_certificates: Vec<Vec<u8>>,  // Extra PEM trust anchors, e.g. a corporate CA
+    pub pool_max_idle_per_host: Option<usize>,
+    pub pool_idle_timeout: Option<Duration>,
+}
+
+impl HttpConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+    
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    
+    pub fn with_proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+    
+    pub fn with_user_agent(mut self, agent: impl Into<String>) -> Self {
+        self.user_agent = Some(agent.into());
+        self
+    }
+    
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+    
+    pub fn with_root_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+    
+    // Skips certificate validation entirely; for local test servers only
+    pub fn with_invalid_certs_accepted(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+    
+    pub fn with_pool(mut self, max_idle_per_host: usize, idle_timeout: Duration) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle_per_host);
+        self.pool_idle_timeout = Some(idle_timeout);
+        self
+    }
+    
+    fn build_client(&self) -> Result<Client, String> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.default_headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name '{}'", name))?;
+            let value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|_| format!("Invalid value for header '{}'", name))?;
+            headers.append(name, value);
+        }
+        
+        let mut builder = Client::builder().default_headers(headers);
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.connect_timeout(timeout);
+            }
+            if let Some(timeout) = self.timeout {
+                builder = builder.timeout(timeout);
+            }
+            if let Some(url) = &self.proxy {
+                let proxy = reqwest::Proxy::all(url.as_str())
+                    .map_err(|e| format!("Invalid proxy '{}': {}", url, e))?;
+                builder = builder.proxy(proxy);
+            }
+            for pem in &self.root_certificates {
+                let certificate = reqwest::Certificate::from_pem(pem)
+                    .map_err(|e| format!("Invalid root certificate: {}", e))?;
+                builder = builder.add_root_certificate(certificate);
+            }
+            if let Some(max) = self.pool_max_idle_per_host {
+                builder = builder.pool_max_idle_per_host(max);
+            }
+            if let Some(timeout) = self.pool_idle_timeout {
+                builder = builder.pool_idle_timeout(timeout);
+            }
+            builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        }
+        
+        builder.build().map_err(|e| format!("Failed to build HTTP client: {}", e))
+    }
+}
+
+// Loader settings swapped as one unit by Walloc::set_loader_config, from any thread. Loads
+// take a snapshot when they start, so ones in flight finish with the settings they began with.
+#[derive(Clone, Debug)]
+pub struct LoaderConfig {
+    pub base_urls: Vec<String>,           // Mirrors in failover order
+    pub http: HttpConfig,
+    pub max_connections_per_host: usize,  // 0 removes the limit
+    pub max_attempts: u32,                // Per mirror, however often middleware asks for a retry
+}
+
+impl Default for LoaderConfig {
+    fn default() -> Self {
+        Self {
+            base_urls: Vec::new(),
+            http: HttpConfig::default(),
+            max_connections_per_host: 0,
+            max_attempts: FETCH_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl LoaderConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_urls.push(url.into());
+        self
+    }
+    
+    pub fn with_http(mut self, http: HttpConfig) -> Self {
+        self.http = http;
+        self
+    }
+    
+    pub fn with_max_connections_per_host(mut self, max: usize) -> Self {
+        self.max_connections_per_host = max;
+        self
+    }
+    
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+}
+
+// The current LoaderConfig with what was built from it
+#[derive(Clone)]
+struct Loader {
+    config: LoaderConfig,
+    client: Client,
+    mirrors: Arc<Vec<String>>,
+}
+
+impl Loader {
+    fn new(config: LoaderConfig) -> Result<Self, String> {
+        Ok(Self {
+            client: config.http.build_client()?,
+            mirrors: mirror_list(config.base_urls.iter().cloned()),
+            config,
+        })
+    }
+}
+
+// Credentials attached to asset fetches, either globally via set_auth or per call via
+// load_asset_with_auth. Browsers refuse a script-set Cookie header, so on WASM signed
+// cookies have to come from the page itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetAuth {
+    Bearer(String),
+    ApiKey { header: String, key: String },
+    Cookie(String),  // Sent verbatim, e.g. "CloudFront-Signature=...; CloudFront-Key-Pair-Id=..."
+}
+
+impl AssetAuth {
+    fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            AssetAuth::Bearer(token) => request.bearer_auth(token),
+            AssetAuth::ApiKey { header, key } => request.header(header.as_str(), key.as_str()),
+            AssetAuth::Cookie(cookie) => request.header(reqwest::header::COOKIE, cookie.as_str()),
+        }
+    }
+}
+
+// Per-call overrides for load_asset_with
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions {
+    pub auth: Option<AssetAuth>,         // Replaces the global credentials; no refresh on 401/403
+    pub base_urls: Option<Vec<String>>,  // Replaces the mirror list, in failover order
+    pub unthrottled: bool,               // Skips the bandwidth limit, e.g. for gameplay-critical loads
+}
+
+impl LoadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_auth(mut self, auth: AssetAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+    
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_urls.get_or_insert_with(Vec::new).push(url.into());
+        self
+    }
+    
+    pub fn unthrottled(mut self) -> Self {
+        self.unthrottled = true;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UploadMethod {
+    Put,
+    Post,
+}
+
+// Transport errors, 5xx and 429 move on to the next mirror; anything else is the answer
+fn should_fail_over(response: &Result<reqwest::Response, LoadError>) -> bool {
+    match response {
+        Ok(response) => response.status().is_server_error() || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS,
+        Err(error) => matches!(error, LoadError::Request(_)),
+    }
+}
+
+// Mirrors with an empty entry dropped; no mirrors means paths are full URLs
+fn mirror_list(urls: impl IntoIterator<Item = String>) -> Arc<Vec<String>> {
+    Arc::new(urls.into_iter().filter(|url| !url.is_empty()).collect())
+}
+
+type AuthFuture = Pin<Box<dyn Future<Output = Result<AssetAuth, String>> + Send>>;
+type AuthRefresh = Arc<dyn Fn() -> AuthFuture + Send + Sync>;
+
+// What middleware sees, and may rewrite, before each attempt at fetching an asset. `url`
+// and `headers` start from base_url + path on every attempt.
+#[derive(Clone, Debug)]
+pub struct FetchRequest {
+    pub path: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub attempt: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct FetchResponse {
+    pub url: String,  // After redirects
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub content_length: Option<u64>,
+}
+
+impl FetchResponse {
+    fn new(response: &reqwest::Response) -> Self {
+        FetchResponse {
+            url: response.url().to_string(),
+            status: response.status().as_u16(),
+            headers: response.headers().iter()
+                .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into_owned()))
+                .collect(),
+            content_length: response.content_length(),
+        }
+    }
+}
+
+// HTTP caching state of a fetched asset, parsed from Cache-Control, Expires, Age, Date,
+// ETag and Last-Modified the way a browser cache would. Times are on this process's
+// clock, so freshness does not survive a heap dump or a shared registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Freshness {
+    pub received_ms: f64,    // When the response arrived
+    pub initial_age: Duration,  // Age the response already had, from the Age header
+    pub lifetime: Duration,  // max-age, else Expires - Date, else 10% of Date - Last-Modified
+    pub no_cache: bool,      // Revalidate before every use
+    pub no_store: bool,      // Keep out of the disk cache
+    pub must_revalidate: bool,  // Stale copies must not be served even if revalidation fails
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+// Cap on the Last-Modified heuristic, as browsers apply one
+const HEURISTIC_LIFETIME_LIMIT: Duration = Duration::from_secs(24 * 60 * 60);
+
+impl Freshness {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        let mut freshness = Freshness {
+            received_ms: clock_ms(),
+            initial_age: Duration::from_secs(header(reqwest::header::AGE).and_then(|age| age.trim().parse().ok()).unwrap_or(0)),
+            lifetime: Duration::ZERO,
+            no_cache: false,
+            no_store: false,
+            must_revalidate: false,
+            etag: header(reqwest::header::ETAG).map(str::to_string),
+            last_modified: header(reqwest::header::LAST_MODIFIED).map(str::to_string),
+        };
+        
+        let mut max_age = None;
+        for directive in headers.get_all(reqwest::header::CACHE_CONTROL).iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+        {
+            let (name, argument) = directive.split_once('=').unwrap_or((directive, ""));
+            match name.trim().to_ascii_lowercase().as_str() {
+                "max-age" => max_age = argument.trim().trim_matches('"').parse().ok(),
+                "no-cache" => freshness.no_cache = true,
+                "no-store" => freshness.no_store = true,
+                "must-revalidate" | "proxy-revalidate" => freshness.must_revalidate = true,
+                _ => {}
+            }
+        }
+        
+        let date = header(reqwest::header::DATE).and_then(parse_http_date);
+        freshness.lifetime = if let Some(seconds) = max_age {
+            Duration::from_secs(seconds)
+        } else if let Some(expires) = header(reqwest::header::EXPIRES) {
+            // An unparseable Expires, such as "0", means already expired
+            let now = date.unwrap_or_else(unix_time_secs);
+            parse_http_date(expires).map_or(Duration::ZERO, |expires| Duration::from_secs(expires.saturating_sub(now)))
+        } else if let (Some(date), Some(modified)) = (date, freshness.last_modified.as_deref().and_then(parse_http_date)) {
+            Duration::from_secs(date.saturating_sub(modified) / 10).min(HEURISTIC_LIFETIME_LIMIT)
+        } else {
+            Duration::ZERO
+        };
+        freshness
+    }
+    
+    pub fn current_age(&self) -> Duration {
+        self.initial_age + Duration::from_secs_f64((clock_ms() - self.received_ms).max(0.0) / 1000.0)
+    }
+    
+    pub fn is_fresh(&self) -> bool {
+        !self.no_cache && self.current_age() < self.lifetime
+    }
+    
+    // Time left before the asset goes stale; zero once it has
+    pub fn expires_in(&self) -> Duration {
+        if self.no_cache {
+            return Duration::ZERO;
+        }
+        self.lifetime.saturating_sub(self.current_age())
+    }
+}
+
+// Conditional request headers that let the server answer 304 if the copy is current
+fn validator_headers(etag: Option<&str>, last_modified: Option<&str>) -> Vec<(String, String)> {
+    let etag = etag.map(|etag| ("If-None-Match".to_string(), etag.to_string()));
+    let modified = last_modified.map(|date| ("If-Modified-Since".to_string(), date.to_string()));
+    etag.into_iter().chain(modified).collect()
+}
+
+// Seconds since the Unix epoch of an IMF-fixdate such as "Sun, 06 Nov 1994 08:49:37 GMT",
+// the only form servers may send; the obsolete RFC 850 and asctime forms are not accepted.
+fn parse_http_date(date: &str) -> Option<u64> {
+    let mut fields = date.split_whitespace().skip(1);
+    let day: u64 = fields.next()?.parse().ok()?;
+    let month_name = fields.next()?;
+    let month = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"]
+        .iter()
+        .position(|name| *name == month_name)? as u64 + 1;
+    let year: u64 = fields.next()?.parse().ok()?;
+    let mut clock = fields.next()?.split(':').map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    if fields.next()? != "GMT" || year < 1970 || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    
+    // Days from the civil date, counting years from March so leap days fall at the end
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era_days = year * 365 + year / 4 - year / 100 + year / 400;
+    let days = era_days + (153 * month + 2) / 5 + day - 1 - 719_468;
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+fn unix_time_secs() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MiddlewareAction {
+    Continue,
+    Retry,  // Run the whole request again with attempt + 1, e.g. against another mirror
+}
+
+// By default a fetch is attempted at most this many times however often middleware asks for
+// a retry; see LoaderConfig::max_attempts
+const FETCH_MAX_ATTEMPTS: u32 = 4;
+
+pub type MiddlewareFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// Hooks run around every asset fetch in registration order. Every layer sees every
+// response, including transport errors, even after an earlier one has asked for a retry.
+pub trait FetchMiddleware: Send + Sync {
+    // Returning an error aborts the load with that message
+    fn on_request<'a>(&'a self, _request: &'a mut FetchRequest) -> MiddlewareFuture<'a, Result<(), String>> {
+        Box::pin(async { Ok(()) })
+    }
+    
+    fn on_response<'a>(&'a self, _request: &'a FetchRequest, _response: &'a Result<FetchResponse, String>) -> MiddlewareFuture<'a, MiddlewareAction> {
+        Box::pin(async { MiddlewareAction::Continue })
+    }
+}
+
+// Why an asset load failed. Display gives the message the String-returning loaders report.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadError {
+    Request(String),  // DNS, connect, TLS or timeout; nothing was received
+    Status { status: u16, url: String },
+    Body(String),     // The connection failed while the response was being read
+    Auth(String),     // The refresh hook failed after the server rejected the credentials
+    Rejected(String), // Middleware refused to send the request
+    CoolingDown { error: String, failures: u32, retry_in_ms: u64 },
+    OutOfMemory { size: usize },
+    Decrypt { path: String, reason: &'static str },  // Wrong key, or the body was tampered with
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Request(message) | LoadError::Rejected(message) => f.write_str(message),
+            LoadError::Status { status, url } => match reqwest::StatusCode::from_u16(*status) {
+                Ok(code) => write!(f, "HTTP error {}: {}", code, url),
+                Err(_) => write!(f, "HTTP error {}: {}", status, url),
+            },
+            LoadError::Body(message) => write!(f, "Failed to get bytes: {}", message),
+            LoadError::Auth(message) => write!(f, "Auth refresh failed: {}", message),
+            LoadError::CoolingDown { error, failures, retry_in_ms } => {
+                write!(f, "{} (failed {} times, retrying in {} ms)", error, failures, retry_in_ms)
+            }
+            LoadError::OutOfMemory { size } => write!(f, "Failed to allocate {} bytes", size),
+            LoadError::Decrypt { path, reason } => write!(f, "Failed to decrypt '{}': {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+// One entry of load_assets_batch, which returns them in request order
+#[derive(Clone, Debug)]
+pub struct BatchLoadResult {
+    pub path: String,
+    pub asset_type: AssetType,
+    pub result: Result<MemoryHandle, LoadError>,
+    pub bytes: usize,  // 0 when the load failed
+    pub duration: Duration,
+}
+
+impl BatchLoadResult {
+    pub fn handle(&self) -> Option<MemoryHandle> {
+        self.result.as_ref().ok().copied()
+    }
+}
+
+// ================================
+// === LOAD THROTTLING ===
+// ================================
+
+// A token bucket for download bandwidth and a cap on requests in flight per host, shared by
+// every load. Zero turns either off; both can change while loads are running.
+#[derive(Default)]
+struct Throttle {
+    bytes_per_sec: AtomicU64,
+    bucket: Mutex<(f64, f64)>,  // Bytes that may be read now, and when that was worked out
+    max_per_host: AtomicUsize,
+    hosts: Mutex<HashMap<String, HostSlots>>,
+    throttled_ms: AtomicU64,
+    queued_ms: AtomicU64,
+}
+
+#[derive(Default)]
+struct HostSlots {
+    in_flight: usize,
+    waiters: Vec<Waker>,
+}
+
+impl Throttle {
+    // Waits until `bytes` fit the bandwidth limit. The bucket holds at most one second of
+    // traffic and may go into debt, so a large chunk delays whatever is read after it.
+    async fn consume(&self, bytes: usize) {
+        let rate = self.bytes_per_sec.load(Ordering::Relaxed) as f64;
+        if rate == 0.0 {
+            return;
+        }
+        let wait_ms = {
+            let mut bucket = self.bucket.lock().unwrap();
+            let now = clock_ms();
+            let (available, counted_at) = *bucket;
+            let available = (available + (now - counted_at) * rate / 1000.0).min(rate) - bytes as f64;
+            *bucket = (available, now);
+            (-available * 1000.0 / rate).max(0.0)
+        };
+        if wait_ms > 0.0 {
+            self.throttled_ms.fetch_add(wait_ms as u64, Ordering::Relaxed);
+            sleep(Duration::from_secs_f64(wait_ms / 1000.0)).await;
+        }
+    }
+    
+    async fn acquire_host(&self, url: &str) -> HostPermit<'_> {
+        if self.max_per_host.load(Ordering::Relaxed) == 0 {
+            return HostPermit { throttle: self, host: None };
+        }
+        let host = match reqwest::Url::parse(url) {
+            Ok(url) => format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or(0)),
+            Err(_) => String::new(),
+        };
+        
+        let started = clock_ms();
+        HostWait { throttle: self, host: &host }.await;
+        self.queued_ms.fetch_add((clock_ms() - started).max(0.0) as u64, Ordering::Relaxed);
+        HostPermit { throttle: self, host: Some(host) }
+    }
+    
+    fn wake_waiters(&self) {
+        for slots in self.hosts.lock().unwrap().values_mut() {
+            slots.waiters.drain(..).for_each(Waker::wake);
+        }
+    }
+}
+
+struct HostWait<'a> {
+    throttle: &'a Throttle,
+    host: &'a str,
+}
+
+impl Future for HostWait<'_> {
+    type Output = ();
+    
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let limit = self.throttle.max_per_host.load(Ordering::Relaxed);
+        let mut hosts = self.throttle.hosts.lock().unwrap();
+        let slots = hosts.entry(self.host.to_string()).or_default();
+        if limit == 0 || slots.in_flight < limit {
+            slots.in_flight += 1;
+            Poll::Ready(())
+        } else {
+            slots.waiters.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+// Holds a host's connection slot until the response body has been read
+struct HostPermit<'a> {
+    throttle: &'a Throttle,
+    host: Option<String>,
+}
+
+impl Drop for HostPermit<'_> {
+    fn drop(&mut self) {
+        let Some(host) = self.host.take() else {
+            return;
+        };
+        let mut hosts = self.throttle.hosts.lock().unwrap();
+        if let Some(slots) = hosts.get_mut(&host) {
+            slots.in_flight = slots.in_flight.saturating_sub(1);
+            slots.waiters.drain(..).for_each(Waker::wake);
+            if slots.in_flight == 0 {
+                hosts.remove(&host);
+            }
+        }
+    }
+}
+
+// ================================
+// === DISK CACHE ===
+// ================================
+
+// Fetched bodies kept on disk between runs, one file per URL: the URL, ETag and
+// Last-Modified on their own lines, then the body. An entry with validators is revalidated
+// with a conditional request and served from disk on 304; without revalidation any entry
+// is served as is. Once the directory exceeds its cap the least recently used entries go.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DiskCache {
+    dir: std::path::PathBuf,
+    max_bytes: u64,
+    revalidate: bool,
+    writes: AtomicU64,  // Keeps temporary file names unique across concurrent stores
+}
+
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: Vec<u8>,
+    fresh: bool,  // Serve without a conditional request
+}
+
+impl CachedResponse {
+    fn validators(&self) -> Vec<(String, String)> {
+        validator_headers(self.etag.as_deref(), self.last_modified.as_deref())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const DISK_CACHE_EXTENSION: &str = "cache";
+
+#[cfg(not(target_arch = "wasm32"))]
+impl DiskCache {
+    pub fn new(dir: impl Into<std::path::PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(DiskCache { dir, max_bytes, revalidate: true, writes: AtomicU64::new(0) })
+    }
+    
+    // Serve cached entries without asking the server, e.g. for CI fixtures that never change
+    pub fn with_revalidation(mut self, revalidate: bool) -> Self {
+        self.revalidate = revalidate;
+        self
+    }
+    
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+    
+    // Bytes currently on disk, headers included
+    pub fn size(&self) -> u64 {
+        self.entries().iter().map(|(_, len, _)| len).sum()
+    }
+    
+    pub fn clear(&self) -> io::Result<()> {
+        for (path, _, _) in self.entries() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+    
+    fn entry_path(&self, url: &str) -> std::path::PathBuf {
+        // FNV-1a; the URL stored in the entry catches the rare collision
+        let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+        self.dir.join(format!("{:016x}.{}", hash, DISK_CACHE_EXTENSION))
+    }
+    
+    fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let path = self.entry_path(url);
+        let contents = std::fs::read(&path).ok()?;
+        let mut fields = contents.splitn(4, |&byte| byte == b'\n');
+        let (stored_url, etag, last_modified) = (fields.next()?, fields.next()?, fields.next()?);
+        let body = fields.next()?;
+        if stored_url != url.as_bytes() {
+            return None;
+        }
+        
+        // Recency for the LRU cleanup is the file's modification time
+        if let Ok(file) = std::fs::File::options().append(true).open(&path) {
+            let _ = file.set_modified(std::time::SystemTime::now());
+        }
+        let field = |bytes: &[u8]| (!bytes.is_empty()).then(|| String::from_utf8_lossy(bytes).into_owned());
+        Some(CachedResponse {
+            etag: field(etag),
+            last_modified: field(last_modified),
+            body: body.to_vec(),
+            fresh: !self.revalidate,
+        })
+    }
+    
+    fn store(&self, url: &str, etag: Option<&str>, last_modified: Option<&str>, body: &[u8]) -> io::Result<()> {
+        // Revalidating an entry without validators would always download it again
+        if self.revalidate && etag.is_none() && last_modified.is_none() {
+            return Ok(());
+        }
+        let header = format!("{}\n{}\n{}\n", url, etag.unwrap_or_default(), last_modified.unwrap_or_default());
+        if (header.len() + body.len()) as u64 > self.max_bytes || header.matches('\n').count() != 3 {
+            return Ok(());
+        }
+        
+        // Written aside and renamed into place so readers never see a partial entry
+        let path = self.entry_path(url);
+        let write = self.writes.fetch_add(1, Ordering::Relaxed);
+        let temporary = path.with_extension(format!("{}.{}", std::process::id(), write));
+        let mut file = std::fs::File::create(&temporary)?;
+        file.write_all(header.as_bytes())?;
+        file.write_all(body)?;
+        drop(file);
+        std::fs::rename(&temporary, &path)?;
+        self.trim();
+        Ok(())
+    }
+    
+    // (path, length, last use) of every entry
+    fn entries(&self) -> Vec<(std::path::PathBuf, u64, std::time::SystemTime)> {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        dir.flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|extension| extension == DISK_CACHE_EXTENSION))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((entry.path(), metadata.len(), metadata.modified().ok()?))
+            })
+            .collect()
+    }
+    
+    fn trim(&self) {
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        entries.sort_by_key(|(_, _, used)| *used);
+        for (path, len, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= len;
+            }
+        }
+        walloc_event!(debug, total, "disk cache trimmed");
+    }
+}
+
+// ================================
+// === ASSET STREAMS ===
+// ================================
+
+// Live asset delivery over a long-lived channel. Begin reserves the asset's full size up
+// front, Data frames are copied straight into that handle, and End registers it in place of
+// any previous copy. Frames travel as `[kind][path len u16 LE][path][payload]`: one binary
+// message per frame over WebSocket, or one base64 `data:` line per frame over SSE. Begin
+// carries the size as a u32, so one streamed asset is at most 4 GiB.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamFrame {
+    Begin { path: String, asset_type: AssetType, size: usize },
+    Data { path: String, bytes: Vec<u8> },
+    End { path: String },
+    Abort { path: String },
+}
+
+impl StreamFrame {
+    pub fn path(&self) -> &str {
+        match self {
+            StreamFrame::Begin { path, .. }
+            | StreamFrame::Data { path, .. }
+            | StreamFrame::End { path }
+            | StreamFrame::Abort { path } => path,
+        }
+    }
+    
+    pub fn encode(&self) -> Result<Vec<u8>, &'static str> {
+        let (kind, payload) = match self {
+            StreamFrame::Begin { asset_type, size, .. } => {
+                let size = u32::try_from(*size).map_err(|_| "Streamed asset is larger than 4 GiB")?;
+                let mut payload = vec![*asset_type as u8];
+                payload.extend_from_slice(&size.to_le_bytes());
+                (0u8, payload)
+            }
+            StreamFrame::Data { bytes, .. } => (1, bytes.clone()),
+            StreamFrame::End { .. } => (2, Vec::new()),
+            StreamFrame::Abort { .. } => (3, Vec::new()),
+        };
+        let path = self.path().as_bytes();
+        let path_len = u16::try_from(path.len()).map_err(|_| "Stream frame path is too long")?;
+        let mut out = Vec::with_capacity(3 + path.len() + payload.len());
+        out.push(kind);
+        out.extend_from_slice(&path_len.to_le_bytes());
+        out.extend_from_slice(path);
+        out.extend_from_slice(&payload);
+        Ok(out)
+    }
+    
+    pub fn decode(bytes: &[u8]) -> Result<StreamFrame, &'static str> {
+        let (&kind, rest) = bytes.split_first().ok_or("Empty stream frame")?;
+        if rest.len() < 2 {
+            return Err("Truncated stream frame");
+        }
+        let path_len = u16::from_le_bytes([rest[0], rest[1]]) as usize;
+        let rest = &rest[2..];
+        if rest.len() < path_len {
+            return Err("Truncated stream frame");
+        }
+        let path = std::str::from_utf8(&rest[..path_len])
+            .map_err(|_| "Stream frame path is not UTF-8")?
+            .to_string();
+        let payload = &rest[path_len..];
+        
+        match kind {
+            0 => {
+                let [asset_type, a, b, c, d] = payload.try_into().map_err(|_| "Malformed Begin frame")?;
+                let asset_type = AssetType::from_u8(asset_type).ok_or("Invalid asset type")?;
+                let size = u32::from_le_bytes([a, b, c, d]) as usize;
+                Ok(StreamFrame::Begin { path, asset_type, size })
+            }
+            1 => Ok(StreamFrame::Data { path, bytes: payload.to_vec() }),
+            2 => Ok(StreamFrame::End { path }),
+            3 => Ok(StreamFrame::Abort { path }),
+            _ => Err("Unknown stream frame kind"),
+        }
+    }
+    
+    // The frame as one SSE event, for servers feeding stream_assets
+    pub fn to_sse(&self) -> Result<String, &'static str> {
+        Ok(format!("data: {}\n\n", encode_base64(&self.encode()?)))
+    }
+}
+
+// An asset that arrived whole and is now registered under `path`
+#[derive(Clone, Debug)]
+pub struct StreamedAsset {
+    pub path: String,
+    pub asset_type: AssetType,
+    pub handle: MemoryHandle,
+    pub size: usize,
+}
+
+struct PendingStream {
+    asset_type: AssetType,
+    handle: MemoryHandle,
+    size: usize,
+    written: usize,
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.push(BASE64_ALPHABET[(word >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => out.push('='),
+            }
+        }
+    }
+    out
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut word = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes() {
+        let value = BASE64_ALPHABET.iter().position(|&c| c == byte)? as u32;
+        word = word << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((word >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+// ================================
+// === ASSET SOURCES ===
+// ================================
+
+// Somewhere other than base_url + path to load assets from, e.g. an object store. The source
+// builds (and signs) each request; load_from_source sends them, fetching objects larger than
+// part_size as ranged parts in parallel, each copied straight into the asset's handle.
+pub trait AssetSource: Send + Sync {
+    // A GET for `path`, limited to the inclusive byte `range` when given
+    fn request(&self, client: &Client, path: &str, range: Option<(usize, usize)>) -> Result<reqwest::RequestBuilder, LoadError>;
+    
+    fn part_size(&self) -> usize {
+        8 * 1024 * 1024
+    }
+}
+
+// S3-compatible storage with SigV4-signed requests. Also covers GCS through its XML API:
+// endpoint https://storage.googleapis.com, region "auto" and an HMAC key.
+#[cfg(feature = "s3")]
+#[derive(Clone)]
+pub struct S3Source {
+    endpoint: String,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    prefix: String,
+    virtual_hosted: bool,
+    part_size: usize,
+}
+
+#[cfg(feature = "s3")]
+impl S3Source {
+    pub fn new(endpoint: impl Into<String>, bucket: impl Into<String>, region: impl Into<String>, access_key: impl Into<String>, secret_key: impl Into<String>) -> Self {
+        S3Source {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            bucket: bucket.into(),
+            region: region.into(),
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            session_token: None,
+            prefix: String::new(),
+            virtual_hosted: false,
+            part_size: 8 * 1024 * 1024,
+        }
+    }
+    
+    // Temporary credentials, sent as x-amz-security-token
+    pub fn with_session_token(mut self, token: impl Into<String>) -> Self {
+        self.session_token = Some(token.into());
+        self
+    }
+    
+    // Prepended to every asset path to form the object key
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+    
+    // bucket.endpoint/key rather than endpoint/bucket/key
+    pub fn with_virtual_hosted_style(mut self) -> Self {
+        self.virtual_hosted = true;
+        self
+    }
+    
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+    
+    // The URL and headers of a signed GET for `key` at `unix_secs`
+    pub fn sign(&self, key: &str, range: Option<(usize, usize)>, unix_secs: u64) -> (String, Vec<(String, String)>) {
+        let (scheme, host) = self.endpoint.split_once("://").unwrap_or(("https", &self.endpoint));
+        let encoded_key: Vec<String> = key.split('/').map(uri_encode).collect();
+        let (host, uri) = match self.virtual_hosted {
+            true => (format!("{}.{}", self.bucket, host), format!("/{}", encoded_key.join("/"))),
+            false => (host.to_string(), format!("/{}/{}", uri_encode(&self.bucket), encoded_key.join("/"))),
+        };
+        
+        let amz_date = format_amz_date(unix_secs);
+        let scope = format!("{}/{}/s3/aws4_request", &amz_date[..8], self.region);
+        let empty_payload = hex_digest(b"");
+        
+        // Sorted by name, as the canonical request requires
+        let mut headers = vec![("host".to_string(), host.clone())];
+        if let Some((start, end)) = range {
+            headers.push(("range".to_string(), format!("bytes={}-{}", start, end)));
+        }
+        headers.push(("x-amz-content-sha256".to_string(), empty_payload.clone()));
+        headers.push(("x-amz-date".to_string(), amz_date.clone()));
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        
+        let signed_headers = headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value.trim())).collect();
+        let canonical_request = format!("GET\n{}\n\n{}\n{}\n{}", uri, canonical_headers, signed_headers, empty_payload);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex_digest(canonical_request.as_bytes()));
+        
+        let mut signing_key = format!("AWS4{}", self.secret_key).into_bytes();
+        for part in [&amz_date[..8], &self.region, "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+        let signature: String = hmac_sha256(&signing_key, string_to_sign.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+        
+        headers.retain(|(name, _)| name != "host");
+        headers.push(("authorization".to_string(), format!(
+            "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+            self.access_key, scope, signed_headers, signature
+        )));
+        (format!("{}://{}{}", scheme, host, uri), headers)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl AssetSource for S3Source {
+    fn request(&self, client: &Client, path: &str, range: Option<(usize, usize)>) -> Result<reqwest::RequestBuilder, LoadError> {
+        let key = format!("{}{}", self.prefix, path.trim_start_matches('/'));
+        let (url, headers) = self.sign(&key, range, unix_time_secs());
+        let mut builder = client.get(url);
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder)
+    }
+    
+    fn part_size(&self) -> usize {
+        self.part_size
+    }
+}
+
+#[cfg(feature = "s3")]
+fn uri_encode(segment: &str) -> String {
+    segment.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(feature = "s3")]
+fn hex_digest(data: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, data).as_ref().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(feature = "s3")]
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    ring::hmac::sign(&ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key), data).as_ref().to_vec()
+}
+
+// YYYYMMDD'T'HHMMSS'Z', the inverse of the day count in parse_http_date
+#[cfg(feature = "s3")]
+fn format_amz_date(unix_secs: u64) -> String {
+    let days = unix_secs / 86_400 + 719_468;
+    let (era, day_of_era) = (days / 146_097, days % 146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let year = era * 400 + year_of_era;
+    let (year, month) = if month >= 10 { (year + 1, month - 9) } else { (year, month + 3) };
+    let seconds = unix_secs % 86_400;
+    format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, seconds / 3_600, seconds / 60 % 60, seconds % 60)
+}
+
+// The object's total size from a `Content-Range: bytes 0-N/TOTAL` header
+fn content_range_total(response: &reqwest::Response) -> Option<usize> {
+    let range = response.headers().get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?;
+    range.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+// ================================
+// === ASSET ENCRYPTION ===
+// ================================
+
+// Encrypted assets travel as nonce || ciphertext || tag, sealed without associated data.
+// Only the ciphertext is copied into the asset's handle and it is decrypted there in place,
+// so the plaintext never exists outside walloc memory. ring picks AES-NI/NEON/SIMD code
+// paths where the CPU has them.
+const SEALED_NONCE_LEN: usize = 12;
+const SEALED_TAG_LEN: usize = 16;
+
+#[cfg(feature = "encryption")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cipher {
+    Aes128Gcm,
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+// No Debug, to keep key material out of logs
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub struct AssetKey {
+    cipher: Cipher,
+    key: Arc<ring::aead::LessSafeKey>,
+}
+
+// Picks the key for an asset path, or None for assets stored in the clear
+#[cfg(feature = "encryption")]
+pub type KeyProvider = Arc<dyn Fn(&str) -> Option<AssetKey> + Send + Sync>;
+
+#[cfg(feature = "encryption")]
+impl AssetKey {
+    pub fn new(cipher: Cipher, key: &[u8]) -> Result<Self, &'static str> {
+        let algorithm = match cipher {
+            Cipher::Aes128Gcm => &ring::aead::AES_128_GCM,
+            Cipher::Aes256Gcm => &ring::aead::AES_256_GCM,
+            Cipher::ChaCha20Poly1305 => &ring::aead::CHACHA20_POLY1305,
+        };
+        let key = ring::aead::UnboundKey::new(algorithm, key).map_err(|_| "Wrong key length for cipher")?;
+        Ok(AssetKey { cipher, key: Arc::new(ring::aead::LessSafeKey::new(key)) })
+    }
+    
+    pub fn cipher(&self) -> Cipher {
+        self.cipher
+    }
+    
+    // The sealed form load paths expect, for packing tools. A nonce must never be reused
+    // with the same key.
+    pub fn seal(&self, nonce: [u8; SEALED_NONCE_LEN], plaintext: &[u8]) -> Vec<u8> {
+        let mut sealed = Vec::with_capacity(plaintext.len() + SEALED_NONCE_LEN + SEALED_TAG_LEN);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(plaintext);
+        let tag = self.key
+            .seal_in_place_separate_tag(ring::aead::Nonce::assume_unique_for_key(nonce), ring::aead::Aad::empty(), &mut sealed[SEALED_NONCE_LEN..])
+            .expect("Asset too large to seal");
+        sealed.extend_from_slice(tag.as_ref());
+        sealed
+    }
+    
+    // `trailer` is the nonce followed by the tag
+    fn open_in_place(&self, trailer: &[u8], ciphertext: &mut [u8]) -> Result<(), &'static str> {
+        let (nonce, tag) = trailer.split_at(SEALED_NONCE_LEN);
+        let nonce = ring::aead::Nonce::try_assume_unique_for_key(nonce).map_err(|_| "Malformed nonce")?;
+        let tag = ring::aead::Tag::try_from(tag).map_err(|_| "Malformed tag")?;
+        self.key.open_in_place_separate_tag(nonce, ring::aead::Aad::empty(), tag, ciphertext, 0..)
+            .map(|_| ())
+            .map_err(|_| "Decryption failed")
+    }
+}
+
+// ================================
+// === ARCHIVED ASSETS ===
+// ================================
+
+// With the `rkyv` feature a downloaded asset can be read as an rkyv archive straight from
+// its arena bytes. The archive is validated once when the view is taken; after that
+// field access is a pointer walk with no copy onto the Rust heap.
+#[cfg(feature = "rkyv")]
+#[derive(Debug)]
+pub enum ArchiveError {
+    Access(AccessError),
+    Invalid(rkyv::rancor::Error),  // Corrupt, truncated or misaligned for the archived type
+}
+
+#[cfg(feature = "rkyv")]
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Access(error) => write!(f, "{}", error),
+            ArchiveError::Invalid(error) => write!(f, "Invalid archive: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl std::error::Error for ArchiveError {}
+
+#[cfg(feature = "rkyv")]
+impl From<AccessError> for ArchiveError {
+    fn from(error: AccessError) -> Self {
+        ArchiveError::Access(error)
+    }
+}
+
+// Holds a shared borrow of the asset's bytes, so they can't be rewritten or compacted away
+// while the archive is in use
+#[cfg(feature = "rkyv")]
+pub struct ArchivedAsset<'a, T: rkyv::Archive> {
+    bytes: ReadGuard<'a>,
+    _marker: PhantomData<T>,
+}
+
+#[cfg(feature = "rkyv")]
+impl<T: rkyv::Archive> std::ops::Deref for ArchivedAsset<'_, T>
+where
+    T::Archived: rkyv::Portable,
+{
+    type Target = T::Archived;
+    
+    fn deref(&self) -> &T::Archived {
+        // Validated by get_asset_archived, and the borrow keeps the bytes unchanged since
+        unsafe { rkyv::access_unchecked::<T::Archived>(&self.bytes) }
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl Walloc {
+    // The registered asset `key` as an archived T. Assets should live in the Top or Middle
+    // tier: Bottom's 8-byte alignment is too small for most archives.
+    pub fn get_asset_archived<T>(&self, key: &str) -> Result<ArchivedAsset<'_, T>, ArchiveError>
+    where
+        T: rkyv::Archive,
+        T::Archived: rkyv::Portable + for<'b> rkyv::bytecheck::CheckBytes<rkyv::api::high::HighValidator<'b, rkyv::rancor::Error>>,
+    {
+        let metadata = self.assets.get(key).ok_or(AccessError::UnknownAsset)?;
+        self.assets.touch(key);
+        let bytes = self.read_view(metadata.handle, metadata.size)?;
+        rkyv::access::<T::Archived, rkyv::rancor::Error>(&bytes).map_err(ArchiveError::Invalid)?;
+        Ok(ArchivedAsset { bytes, _marker: PhantomData })
+    }
+}
+
+// ================================
+// === MAIN WALLOC IMPLEMENTATION ===
+// ================================
+
+pub struct Walloc {
+    arenas: [LockFreeArena; 3],
+    instance_id: u32,
+    pub assets: Arc<SimpleAssetRegistry>,
+    loader: RwLock<Arc<Loader>>,  // Swapped whole at runtime; loads work from a snapshot
+    auth: RwLock<Option<AssetAuth>>,
+    auth_refresh: RwLock<Option<AuthRefresh>>,
+    middleware: RwLock<Vec<Arc<dyn FetchMiddleware>>>,
+    memory_base: *mut u8,
+    memory_size: usize,
+    // Handles are offsets from handle_base, which sits handle_space bytes below memory_base
+    handle_base: *mut u8,
+    handle_space: usize,
+    backend: Box<dyn MemoryBackend>,
+    compaction: Mutex<[Option<CompactionJob>; 3]>,
+    compaction_policies: RwLock<[Option<CompactionPolicy>; 3]>,
+    fetch_stats: FetchCounters,
+    borrows: BorrowTracker,
+    watermarks: [TierWatermarks; 3],
+    oom_handler: RwLock<Option<OomHandler>>,
+    #[cfg(unix)]
+    shared_registry: Option<SharedRegistry>,
+    #[cfg(unix)]
+    mappings: Mutex<Vec<MappedFile>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    disk_cache: Option<DiskCache>,
+    streams: Mutex<HashMap<String, PendingStream>>,
+    deferred_frees: Mutex<Vec<DeferredFree>>,
+    #[cfg(feature = "encryption")]
+    keys: RwLock<Option<KeyProvider>>,
+    #[cfg(target_arch = "wasm32")]
+    deferred_compaction: AtomicBool,
+    
+    #[cfg(target_arch = "wasm32")]
+    wasm_strategy: WasmStrategy,
+}
+
+impl Walloc {
+    pub fn new() -> Result<Self, &'static str> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let memory_size = core::arch::wasm32::memory_size(0) * 65536;
+            Self::with_backend(WasmMemoryBackend, memory_size)
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Self::with_backend(SystemBackend, 64 * 1024 * 1024)
+        }
+    }
+    
+    // Lay the tiers out in `memory_size` bytes reserved and committed from `backend`, which
+    // releases them again when the Walloc is dropped
+    pub fn with_backend(backend: impl MemoryBackend + 'static, memory_size: usize) -> Result<Self, &'static str> {
+        Self::with_backend_split(backend, memory_size, DEFAULT_TIER_SPLIT)
+    }
+    
+    fn with_backend_split(backend: impl MemoryBackend + 'static, memory_size: usize, split: [usize; 3]) -> Result<Self, &'static str> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if memory_size > MAX_MEMORY_LIMIT {
+            return Err("Memory size exceeds platform limit");
+        }
+        
+        let memory_base = backend.reserve(memory_size)?;
+        if let Err(error) = unsafe { backend.commit(memory_base, memory_size) } {
+            unsafe { backend.release(memory_base, memory_size) };
+            return Err(error);
+        }
+        Self::with_memory(Box::new(backend), memory_base, memory_size, split)
+    }
+    
+    // Manage `len` bytes at `ptr` that the caller already owns, such as a console's fixed
+    // region or a mapped file. The memory is never freed by walloc.
+    /// # Safety
+    /// `ptr` must be valid for reads and writes of `len` bytes for the Walloc's whole
+    /// lifetime, and nothing else may access that memory while the Walloc is in use.
+    pub unsafe fn from_external(ptr: *mut u8, len: usize) -> Result<Self, &'static str> {
+        Self::with_backend(BorrowedBackend { name: "external", base: ptr, len }, len)
+    }
+    
+    pub fn from_slice(buffer: &'static mut [u8]) -> Result<Self, &'static str> {
+        unsafe { Self::from_external(buffer.as_mut_ptr(), buffer.len()) }
+    }
+    
+    // `split` is the percent of memory for Top and Middle; Bottom gets the rest
+    fn with_memory(backend: Box<dyn MemoryBackend>, memory_base: *mut u8, memory_size: usize, split: [usize; 3]) -> Result<Self, &'static str> {
+        let aligned_base = (memory_base as usize + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
+        let adjusted_size = memory_size.saturating_sub(aligned_base - memory_base as usize);
+        
+        let render_size = ((adjusted_size * split[0] / 100) + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
+        let scene_size = ((adjusted_size * split[1] / 100) + CACHE_LINE_SIZE - 1) & !(CACHE_LINE_SIZE - 1);
+        let entity_size = adjusted_size - render_size - scene_size;
+        
+        let render_base = aligned_base as *mut u8;
+        let scene_base = unsafe { render_base.add(render_size) };
+        let entity_base = unsafe { scene_base.add(scene_size) };
+        
+        let instance_id = NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed);
+        let handle_space = handle_space(instance_id);
+        let handle_base = memory_base.wrapping_sub(handle_space);
+        #[cfg(not(target_arch = "wasm32"))]
+        LATEST_HANDLE_BASE.store(handle_base, Ordering::Release);
+        
+        Ok(Self {
+            arenas: [
+                LockFreeArena::new(render_base, render_size, Tier::Top, handle_base),
+                LockFreeArena::new(scene_base, scene_size, Tier::Middle, handle_base),
+                LockFreeArena::new(entity_base, entity_size, Tier::Bottom, handle_base),
+            ],
+            instance_id,
+            assets: Arc::new(SimpleAssetRegistry::new()),
+            loader: RwLock::new(Arc::new(Loader { config: LoaderConfig::default(), client: Client::new(), mirrors: Arc::new(Vec::new()) })),
+            auth: RwLock::new(None),
+            auth_refresh: RwLock::new(None),
+            middleware: RwLock::new(Vec::new()),
+            memory_base,
+            memory_size,
+            handle_base,
+            handle_space,
+            backend,
+            compaction: Mutex::new([None, None, None]),
+            compaction_policies: RwLock::new([None, None, None]),
+            fetch_stats: FetchCounters::default(),
+            borrows: BorrowTracker::default(),
+            watermarks: Default::default(),
+            oom_handler: RwLock::new(None),
+            #[cfg(unix)]
+            shared_registry: None,
+            #[cfg(unix)]
+            mappings: Mutex::new(Vec::new()),
+            #[cfg(not(target_arch = "wasm32"))]
+            disk_cache: None,
+            streams: Mutex::new(HashMap::new()),
+            deferred_frees: Mutex::new(Vec::new()),
+            #[cfg(feature = "encryption")]
+            keys: RwLock::new(None),
+            #[cfg(target_arch = "wasm32")]
+            deferred_compaction: AtomicBool::new(false),
+            
+            #[cfg(target_arch = "wasm32")]
+            wasm_strategy: WasmStrategy::new(),
+        })
+    }
+    
+    // Shorthand for sharing the allocator across threads and tasks
+    pub fn into_arc(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+    
+    pub fn instance_id(&self) -> u32 {
+        self.instance_id
+    }
+    
+    pub fn scope_handle(&self, handle: MemoryHandle) -> InstanceHandle {
+        InstanceHandle { handle, instance: self.instance_id }
+    }
+    
+    // The bare handle, if `handle` came from this instance
+    pub fn resolve_handle(&self, handle: InstanceHandle) -> Result<MemoryHandle, AccessError> {
+        if handle.instance != self.instance_id {
+            return Err(AccessError::WrongInstance);
+        }
+        Ok(handle.handle)
+    }
+    
+    // Where `handle` points in this instance's memory. Handles are offsets from the memory
+    // base on native, so the same handle names different bytes in another instance.
+    #[inline(always)]
+    pub fn handle_ptr(&self, handle: MemoryHandle) -> *mut u8 {
+        handle.resolve(self.handle_base)
+    }
+    
+    // Whether `handle` lies in this instance's handle space; see handle_space
+    fn owns(&self, handle: MemoryHandle) -> bool {
+        !handle.is_null() && in_handle_space(handle.offset(), self.handle_space)
+    }
+    
+    // Offsets that leave the process (saved and shared registries) are from the memory base,
+    // so another instance of the same layout can take them back into its own handle space
+    fn base_relative(&self, handle: MemoryHandle) -> usize {
+        handle.offset().wrapping_sub(self.handle_space)
+    }
+    
+    fn handle_at(&self, base_relative: usize) -> MemoryHandle {
+        MemoryHandle(base_relative.wrapping_add(self.handle_space))
+    }
+    
+    // Move a fresh instance's handles into `space`, before anything is allocated in it
+    fn adopt_handle_space(&mut self, space: usize) {
+        let handle_base = self.memory_base.wrapping_sub(space);
+        for arena in &mut self.arenas {
+            arena.base_offset = arena.base_offset.wrapping_sub(self.handle_space).wrapping_add(space);
+            arena.handle_base = handle_base;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = LATEST_HANDLE_BASE.compare_exchange(self.handle_base, handle_base, Ordering::AcqRel, Ordering::Acquire);
+        self.handle_space = space;
+        self.handle_base = handle_base;
+    }
+    
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_scoped(&self, size: usize, tier: Tier) -> Option<InstanceHandle> {
+        self.allocate(size, tier).map(|handle| self.scope_handle(handle))
+    }
+    
+    // An independent copy in freshly allocated memory: arena contents, freelists and the
+    // asset registry, so handles and asset offsets mean the same data in both. The copy
+    // shares the original's handle space to make that work, so only InstanceHandle tells
+    // their handles apart. Open thread slices are copied as allocated space, and the copy
+    // starts without watermarks, an OOM handler or policies.
+    pub fn duplicate(&self) -> Result<Walloc, &'static str> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err("Linear memory cannot be duplicated")
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut copy = Self::with_backend(SystemBackend, self.memory_size)?;
+            copy.adopt_handle_space(self.handle_space);
+            let layout_matches = copy.arenas.iter().zip(&self.arenas)
+                .all(|(ours, theirs)| ours.base_offset == theirs.base_offset && ours.capacity() == theirs.capacity());
+            if !layout_matches {
+                return Err("Duplicate memory does not match the original's layout");
+            }
+            
+            for (ours, theirs) in copy.arenas.iter_mut().zip(&self.arenas) {
+                unsafe {
+                    copy_arena_bytes(theirs.base_ptr(), ours.base_ptr(), theirs.usage());
+                }
+                ours.copy_state_from(theirs);
+            }
+            
+            copy = copy.with_key_normalizer(self.assets.normalizer())?;
+            for (key, metadata) in self.assets.assets.read().unwrap().iter() {
+                copy.assets.insert(key.clone(), metadata.clone());
+            }
+            *copy.loader.write().unwrap() = self.loader();
+            Ok(copy)
+        }
+    }
+    
+    // Canonicalize asset keys on insert and lookup, e.g. KeyNormalizer::portable(). Only
+    // allowed before any asset is registered.
+    pub fn with_key_normalizer(mut self, normalizer: KeyNormalizer) -> Result<Self, &'static str> {
+        let registry = Arc::get_mut(&mut self.assets)
+            .filter(|registry| registry.is_empty())
+            .ok_or("Key normalization must be set before assets are registered")?;
+        registry.normalizer = normalizer;
+        Ok(self)
+    }
+    
+    // Replace a tier's size-class table, e.g. &[64, 128, 320] for a workload of 48/112/320
+    // byte objects. Bounds are compared against tier-aligned sizes, and anything above the
+    // last bound shares one catch-all class. Only allowed before the tier is used.
+    pub fn with_size_classes(mut self, tier: Tier, bounds: &[usize]) -> Result<Self, &'static str> {
+        let table = size_class_table(bounds)?;
+        let arena = &mut self.arenas[tier as usize];
+        if arena.usage() != 0 {
+            return Err("Size classes must be set before the tier is used");
+        }
+        arena.size_classes = table;
+        Ok(self)
+    }
+    
+    // Builder method to set base URL before converting to Arc
+    pub fn with_base_url(self, url: String) -> Self {
+        self.set_base_url(url);
+        self
+    }
+    
+    pub fn with_base_urls(self, urls: Vec<String>) -> Self {
+        self.set_base_urls(urls);
+        self
+    }
+    
+    // Replaces the default HTTP client; fails if a header, proxy or certificate is malformed
+    pub fn with_http_config(self, config: &HttpConfig) -> Result<Self, String> {
+        self.set_http_config(config)?;
+        Ok(self)
+    }
+    
+    pub fn with_loader_config(self, config: LoaderConfig) -> Result<Self, String> {
+        self.set_loader_config(config)?;
+        Ok(self)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_disk_cache(mut self, cache: DiskCache) -> Self {
+        self.disk_cache = Some(cache);
+        self
+    }
+    
+    // ================================
+    // === ENHANCED ALLOCATION API ===
+    // ================================
+    
+    // An empty ownership group for a subsystem; fill it with owner.allocate(size)
+    pub fn create_owner(&self, tier: Tier) -> MemoryOwner<'_> {
+        MemoryOwner::new(tier as usize, self)
+    }
+    
+    // Allocate with memory owner tracking
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_with_owner(&self, size: usize, tier: Tier) -> Option<(MemoryOwner<'_>, MemoryHandle)> {
+        let mut owner = self.create_owner(tier);
+        let handle = owner.allocate(size)?;
+        Some((owner, handle))
+    }
+    
+    #[inline(always)]
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        self.try_allocate(size, tier).or_else(|| self.recover_from_oom(size, tier, false).map(|(handle, _)| handle))
+    }
+    
+    // Like allocate, but an OOM handler answering Spill is honoured: the block then comes from
+    // another tier, and the tier returned with the handle is the one to deallocate it from.
+    // Other allocation calls treat Spill as Fail, since their callers free with the tier they asked for.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_spillable(&self, size: usize, tier: Tier) -> Option<(MemoryHandle, Tier)> {
+        self.try_allocate(size, tier)
+            .map(|handle| (handle, tier))
+            .or_else(|| self.recover_from_oom(size, tier, true))
+    }
+    
+    // Allocation without the OOM handler, for recovery paths that must not re-enter it
+    #[inline(always)]
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    fn try_allocate(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        let arena = &self.arenas[tier as usize];
+        
+        if let Some(global_offset) = arena.allocate(size) {
+            walloc_event!(trace, ?tier, size, offset = global_offset, "allocate");
+            #[cfg(feature = "track-callers")]
+            arena.record_call_site(global_offset, size);
+            self.check_watermarks(tier);
+            return Some(MemoryHandle(global_offset));
+        }
+        
+        #[cfg(target_arch = "wasm32")]
+        {
+            let start = clock_ns();
+            if let Some(global_offset) = self.wasm_strategy.try_grow(self.backend.as_ref(), arena, size) {
+                if arena.latency.enabled() {
+                    arena.latency.record(LatencyPath::Growth, clock_ns().saturating_sub(start));
+                }
+                walloc_event!(trace, ?tier, size, offset = global_offset, grown = true, "allocate");
+                #[cfg(feature = "track-callers")]
+                arena.record_call_site(global_offset, size);
+                self.check_watermarks(tier);
+                return Some(MemoryHandle(global_offset));
+            }
+        }
+        
+        walloc_event!(debug, ?tier, size, "allocation failed");
+        None
+    }
+    
+    // Allocate with a stricter alignment, zero fill or an attribution tag for this call only.
+    // Over-aligned blocks are carved from a padded allocation whose slack goes straight back
+    // to the freelists without counting as frees, so the stats see one allocation and the
+    // result frees with a plain deallocate(handle, size, tier).
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_with_opts(&self, size: usize, tier: Tier, opts: AllocOpts) -> Option<MemoryHandle> {
+        let tier_align = tier.alignment().max(SIMD_ALIGNMENT);
+        let align = opts.align.max(tier_align);
+        if !align.is_power_of_two() {
+            return None;
+        }
+        
+        let padded = align > tier_align;
+        let request = if padded {
+            self.arenas[tier as usize].align_size(size).checked_add(align - tier_align)?
+        } else {
+            size
+        };
+        let block = self.allocate(request, tier)?;
+        let arena = &self.arenas[tier as usize];
+        
+        let handle = if padded {
+            let block_size = arena.align_size(request);
+            let aligned_size = arena.align_size(size);
+            let lead = block.resolve(self.handle_base).align_offset(align);
+            // A tier base that is not itself tier-aligned can leave no usable start in the block
+            if !lead.is_multiple_of(arena.tier.alignment().max(SIMD_ALIGNMENT)) || lead + aligned_size > block_size {
+                arena.deallocate(block, block_size);
+                return None;
+            }
+            
+            let trail = block_size - lead - aligned_size;
+            if trail > 0 {
+                arena.release_slack(block.advance(lead + aligned_size), trail);
+            }
+            if lead > 0 {
+                arena.forget_block(block.offset(), request);
+                #[cfg(feature = "track-callers")]
+                arena.call_sites.lock().unwrap().remove(&block.offset());
+                arena.release_slack(block, lead);
+            }
+            let handle = block.advance(lead);
+            arena.record_block(handle.offset(), size);
+            #[cfg(feature = "track-callers")]
+            arena.record_call_site(handle.offset(), size);
+            handle
+        } else {
+            block
+        };
+        
+        if opts.zeroed {
+            unsafe {
+                std::ptr::write_bytes(handle.resolve(self.handle_base), 0, size);
+            }
+        }
+        if let Some(tag) = opts.tag {
+            arena.set_tag(handle.offset(), size, tag);
+        }
+        walloc_event!(trace, tier = ?arena.tier, size, align, offset = handle.offset(), "allocate_with_opts");
+        Some(handle)
+    }
+    
+    // Tag given to a live allocate_with_opts block
+    pub fn allocation_tag(&self, handle: MemoryHandle) -> Option<String> {
+        let arena = self.arenas.iter().find(|arena| arena.contains(handle))?;
+        arena.tags.lock().unwrap().get(&handle.offset()).map(|(_, tag)| tag.clone())
+    }
+    
+    // Waits for frees, evictions, resets or compaction to make room instead of failing.
+    // Requests that can never fit fail immediately; on wasm the tier may still grow.
+    pub async fn allocate_async(&self, size: usize, tier: Tier, timeout: Duration) -> Result<MemoryHandle, &'static str> {
+        if let Some(handle) = self.allocate(size, tier) {
+            return Ok(handle);
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let arena = &self.arenas[tier as usize];
+            if arena.align_size(size) > arena.capacity() {
+                return Err("Allocation exceeds tier capacity");
+            }
+        }
+        
+        let wait = AllocationWait { walloc: self, size, tier };
+        let timer = std::pin::pin!(sleep(timeout));
+        match futures::future::select(wait, timer).await {
+            futures::future::Either::Left((handle, _)) => Ok(handle),
+            futures::future::Either::Right(_) => Err("Timed out waiting for memory"),
+        }
+    }
+    
+    // Evicts unpinned assets of the tier (lowest priority, then least recently used) until
+    // the allocation fits. Nothing is evicted when even evicting every candidate could not help.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_or_evict(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        self.try_allocate(size, tier)
+            .or_else(|| self.evict_for(size, tier))
+            .or_else(|| self.recover_from_oom(size, tier, false).map(|(handle, _)| handle))
+    }
+    
+    fn evict_for(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        let arena = &self.arenas[tier as usize];
+        let candidates = self.assets.eviction_candidates(tier);
+        let evictable: usize = candidates.iter().map(|(_, metadata)| arena.align_size(metadata.size)).sum();
+        let live = arena.live_bytes();
+        if arena.regular_limit().saturating_sub(live).saturating_add(evictable) < arena.align_size(size) {
+            walloc_event!(debug, ?tier, size, evictable, "allocate_or_evict cannot make room");
+            return None;
+        }
+        
+        for (key, _) in candidates {
+            if !self.evict_asset(&key) {
+                continue;
+            }
+            
+            if let Some(handle) = self.try_allocate(size, tier) {
+                walloc_event!(debug, ?tier, size, victim = %key, "allocate_or_evict succeeded");
+                return Some(handle);
+            }
+        }
+        
+        walloc_event!(debug, ?tier, size, "allocate_or_evict exhausted candidates");
+        None
+    }
+    
+    // Keep the last `bytes` of the tier for allocate_reserved, so crash handling and
+    // save-before-exit paths still get memory once regular allocations are exhausted.
+    // Fails if that much headroom is no longer free; 0 releases the reserve.
+    pub fn set_reserve(&self, tier: Tier, bytes: usize) -> Result<(), &'static str> {
+        let arena = &self.arenas[tier as usize];
+        let bytes = arena.align_size(bytes);
+        if arena.usage().saturating_add(bytes) > arena.capacity() {
+            return Err("Reserve exceeds the tier's free headroom");
+        }
+        arena.reserved.store(bytes, Ordering::Relaxed);
+        Ok(())
+    }
+    
+    // Return the physical pages past the tier's bump head to the OS, e.g. after a large
+    // eviction and reset. Returns the bytes released; 0 if the backend can't decommit.
+    pub fn trim(&self, tier: Tier) -> usize {
+        let returned = self.arenas[tier as usize].trim(self.backend.as_ref());
+        walloc_event!(debug, ?tier, returned, backend = self.backend.name(), "trim");
+        returned
+    }
+    
+    // Lower the tier's bump head over freed blocks that end at it; deallocate does this
+    // opportunistically. Returns the bytes reclaimed.
+    pub fn trim_tail(&self, tier: Tier) -> usize {
+        self.arenas[tier as usize].trim_tail()
+    }
+    
+    pub fn reserve_remaining(&self, tier: Tier) -> usize {
+        self.arenas[tier as usize].reserve_remaining()
+    }
+    
+    // Falls back to the tier's reserve when regular allocation fails. Never consults the OOM
+    // handler, so it is safe to call from inside one.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_reserved(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        let arena = &self.arenas[tier as usize];
+        let global_offset = arena.allocate_reserved(size)?;
+        walloc_event!(debug, ?tier, size, offset = global_offset, reserve_left = arena.reserve_remaining(), "allocate_reserved");
+        #[cfg(feature = "track-callers")]
+        arena.record_call_site(global_offset, size);
+        self.check_watermarks(tier);
+        Some(MemoryHandle(global_offset))
+    }
+    
+    // Install the policy consulted whenever allocate or allocate_or_evict is about to return
+    // None. The handler runs on the allocating thread and may call back into walloc.
+    pub fn set_oom_handler(&self, handler: impl Fn(OomInfo) -> OomAction + Send + Sync + 'static) {
+        *self.oom_handler.write().unwrap() = Some(Arc::new(handler));
+    }
+    
+    pub fn clear_oom_handler(&self) {
+        *self.oom_handler.write().unwrap() = None;
+    }
+    
+    #[cold]
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    fn recover_from_oom(&self, size: usize, tier: Tier, spill: bool) -> Option<(MemoryHandle, Tier)> {
+        let handler = self.oom_handler.read().unwrap().clone()?;
+        let arena = &self.arenas[tier as usize];
+        
+        for attempt in 0..OOM_MAX_ATTEMPTS {
+            let (_, capacity, _, used) = arena.stats();
+            let info = OomInfo { tier, requested: size, used, capacity, attempt };
+            let action = handler(info);
+            walloc_event!(warn, ?tier, size, used, attempt, ?action, "out of memory");
+            
+            let allocated = match action {
+                OomAction::Fail => return None,
+                OomAction::Retry => self.try_allocate(size, tier).map(|handle| (handle, tier)),
+                OomAction::Evict => self.evict_for(size, tier).map(|handle| (handle, tier)),
+                OomAction::Compact => {
+                    if self.compaction_progress(tier).is_some() || self.begin_compaction(tier) {
+                        self.finish_compaction(tier);
+                    }
+                    self.try_allocate(size, tier).map(|handle| (handle, tier))
+                }
+                OomAction::Spill(other) if spill => self.try_allocate(size, other).map(|handle| (handle, other)),
+                OomAction::Spill(_) => return None,
+                OomAction::Abort => panic!("walloc: out of memory: {:?}", info),
+            };
+            if allocated.is_some() {
+                return allocated;
+            }
+        }
+        None
+    }
+    
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_batch(&self, requests: &[(usize, Tier)]) -> Vec<Option<MemoryHandle>> {
+        let mut results = Vec::with_capacity(requests.len());
+        
+        let mut tier_groups: [Vec<(usize, usize)>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+        
+        for (idx, &(size, tier)) in requests.iter().enumerate() {
+            tier_groups[tier as usize].push((idx, size));
+        }
+        
+        results.resize(requests.len(), None);
+        
+        for (tier_idx, group) in tier_groups.iter().enumerate() {
+            let arena = &self.arenas[tier_idx];
+            
+            for &(original_idx, size) in group {
+                if let Some(global_offset) = arena.allocate(size) {
+                    #[cfg(feature = "track-callers")]
+                    arena.record_call_site(global_offset, size);
+                    results[original_idx] = Some(MemoryHandle(global_offset));
+                }
+            }
+        }
+        
+        results
+    }
+    
+    // Offsets of each sub-allocation from the block start, plus the block size: every part
+    // starts on the tier's alignment, packed in order
+    fn many_layout(tier: Tier, sizes: &[usize]) -> Option<(Vec<usize>, usize)> {
+        let align = tier.alignment();
+        let mut offsets = Vec::with_capacity(sizes.len());
+        let mut cursor = 0usize;
+        for &size in sizes {
+            let offset = cursor.checked_add(align - 1)? & !(align - 1);
+            offsets.push(offset);
+            cursor = offset.checked_add(size)?;
+        }
+        Some((offsets, cursor))
+    }
+    
+    // One contiguous block carved into `sizes`, so interleaved vertex/index/uniform data
+    // stays adjacent and costs a single arena allocation. The parts are freed together
+    // with deallocate_many.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_many(&self, tier: Tier, sizes: &[usize]) -> Option<Vec<MemoryHandle>> {
+        if sizes.is_empty() {
+            return Some(Vec::new());
+        }
+        let (offsets, total) = Self::many_layout(tier, sizes)?;
+        let block = self.allocate(total, tier)?;
+        Some(offsets.into_iter().map(|offset| block.advance(offset)).collect())
+    }
+    
+    // Release an allocate_many block; `sizes` must be the slice it was allocated with
+    pub fn deallocate_many(&self, tier: Tier, handles: &[MemoryHandle], sizes: &[usize]) -> Result<(), &'static str> {
+        if handles.len() != sizes.len() {
+            return Err("Handle and size counts differ");
+        }
+        let Some(&block) = handles.first() else {
+            return Ok(());
+        };
+        let (offsets, total) = Self::many_layout(tier, sizes).ok_or("Sizes overflow the address space")?;
+        if handles.iter().zip(&offsets).any(|(&handle, &offset)| handle != block.advance(offset)) {
+            return Err("Handles don't match the allocate_many layout");
+        }
+        self.deallocate(block, total, tier)
+    }
+    
+    // Free a plain allocate() result. `size` must be the size it was allocated with. Handles
+    // still registered as assets must go through evict_asset instead, or the registry will
+    // point at recycled memory.
+    pub fn deallocate(&self, handle: MemoryHandle, size: usize, tier: Tier) -> Result<(), &'static str> {
+        self.check_free(handle, size, tier)?;
+        self.arenas[tier as usize].deallocate(handle, size);
+        self.check_compaction_policy(tier);
+        Ok(())
+    }
+    
+    // Like allocate, but the size, tier and arena epoch are stored in a header in front of
+    // the returned handle so deallocate_headed needs nothing else. Costs one tier-aligned
+    // prefix (32 to 128 bytes) per allocation.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_headed(&self, size: usize, tier: Tier) -> Option<MemoryHandle> {
+        let arena = &self.arenas[tier as usize];
+        let prefix = arena.header_prefix();
+        let block = self.allocate(prefix.checked_add(size)?, tier)?;
+        let handle = block.advance(prefix);
+        
+        let header = AllocationHeader {
+            size: size as u64,
+            tier: tier as u8,
+            _reserved: [0; 3],
+            generation: arena.epoch.load(Ordering::SeqCst) as u32,
+            magic: HEADER_LIVE,
+            _reserved2: 0,
+        };
+        unsafe {
+            std::ptr::write_unaligned(handle.resolve(self.handle_base).sub(HEADER_SIZE) as *mut AllocationHeader, header);
+        }
+        Some(handle)
+    }
+    
+    // Free an allocate_headed result. Double frees, handles without a header and handles
+    // from before a reset or compaction of their tier are reported instead of corrupting
+    // the freelists. Returns the size that was allocated.
+    pub fn deallocate_headed(&self, handle: MemoryHandle) -> Result<usize, &'static str> {
+        if handle.is_null() {
+            return Err("Null handle");
+        }
+        let arena = self.arenas.iter().find(|arena| arena.contains(handle))
+            .ok_or("Handle is outside walloc memory")?;
+        let tier = arena.tier;
+        
+        let header = arena.read_header(handle).ok_or("Handle has no allocation header")?;
+        if header.magic == HEADER_FREED {
+            return Err("Double free");
+        }
+        if header.tier != tier as u8 {
+            return Err("Header tier does not match the handle's tier");
+        }
+        if header.generation != arena.epoch.load(Ordering::SeqCst) as u32 {
+            return Err("Stale handle: tier was reset or compacted since allocation");
+        }
+        
+        let size = header.size as usize;
+        let prefix = arena.header_prefix();
+        let block = MemoryHandle(handle.offset() - prefix);
+        self.check_free(block, prefix + size, tier)?;
+        
+        unsafe {
+            let magic = handle.resolve(self.handle_base).sub(HEADER_SIZE).add(std::mem::offset_of!(AllocationHeader, magic)) as *mut u32;
+            std::ptr::write_unaligned(magic, HEADER_FREED);
+        }
+        arena.deallocate(block, prefix + size);
+        // Left readable so a second free is reported as one
+        asan_unpoison(unsafe { handle.resolve(self.handle_base).sub(HEADER_SIZE) }, HEADER_SIZE);
+        self.check_compaction_policy(tier);
+        Ok(size)
+    }
+    
+    // Frees are grouped per tier and pushed to the freelists in one splice per size class.
+    // Entries that fail deallocate's validation are skipped; returns how many were freed.
+    pub fn deallocate_batch(&self, blocks: &[(MemoryHandle, usize, Tier)]) -> usize {
+        let mut tier_groups: [Vec<(MemoryHandle, usize)>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+        
+        for &(handle, size, tier) in blocks {
+            if self.check_free(handle, size, tier).is_ok() {
+                tier_groups[tier as usize].push((handle, size));
+            }
+        }
+        
+        let mut freed = 0;
+        for tier in [Tier::Top, Tier::Middle, Tier::Bottom] {
+            let group = &tier_groups[tier as usize];
+            if !group.is_empty() {
+                freed += self.arenas[tier as usize].deallocate_batch(group);
+                self.check_compaction_policy(tier);
+            }
+        }
+        freed
+    }
+    
+    // Free once the caller's fence reaches `fence_value`, e.g. a GPU submission counter. The
+    // handle is validated now, so mistakes surface at the call rather than at the flush.
+    pub fn deallocate_deferred(&self, handle: MemoryHandle, size: usize, tier: Tier, fence_value: u64) -> Result<(), &'static str> {
+        self.check_free(handle, size, tier)?;
+        let epoch = self.arenas[tier as usize].epoch.load(Ordering::SeqCst);
+        self.deferred_frees.lock().unwrap().push(DeferredFree { fence: fence_value, handle, size, tier, epoch });
+        Ok(())
+    }
+    
+    // Release every deferred block whose fence is at or below `completed_fence` through the
+    // batch freelist path. Blocks whose tier was reset since are dropped. Returns how many
+    // were freed.
+    pub fn flush_deferred(&self, completed_fence: u64) -> usize {
+        let mut ready = Vec::new();
+        self.deferred_frees.lock().unwrap().retain(|deferred| {
+            if deferred.fence > completed_fence {
+                return true;
+            }
+            if deferred.epoch == self.arenas[deferred.tier as usize].epoch.load(Ordering::SeqCst) {
+                ready.push((deferred.handle, deferred.size, deferred.tier));
+            }
+            false
+        });
+        if ready.is_empty() {
+            return 0;
+        }
+        let freed = self.deallocate_batch(&ready);
+        walloc_event!(trace, completed_fence, freed, "flushed deferred frees");
+        freed
+    }
+    
+    // Blocks and bytes still waiting on a fence
+    pub fn pending_deferred(&self) -> (usize, usize) {
+        let deferred = self.deferred_frees.lock().unwrap();
+        (deferred.len(), deferred.iter().map(|deferred| deferred.size).sum())
+    }
+    
+    // Copy an allocation into another tier and free the original. A registered asset starting
+    // at `handle` is repointed, so asset lookups keep working; other copies of the old handle
+    // are left dangling.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn migrate(&self, handle: MemoryHandle, size: usize, from_tier: Tier, to_tier: Tier) -> Result<MemoryHandle, &'static str> {
+        if from_tier == to_tier {
+            return Ok(handle);
+        }
+        self.check_free(handle, size, from_tier)?;
+        
+        // Hold the source exclusively so no with_slice view observes it mid-move
+        let _guard = self.borrows.acquire(handle.offset(), handle.offset() + size, true)
+            .map_err(|_| "Allocation is currently borrowed")?;
+        
+        let new_handle = self.allocate(size, to_tier).ok_or("Destination tier is full")?;
+        unsafe {
+            SIMDOps::fast_copy(handle.resolve(self.handle_base), new_handle.resolve(self.handle_base), size);
+        }
+        
+        let _asset = self.assets.retier(handle, from_tier, new_handle, to_tier);
+        self.arenas[from_tier as usize].deallocate(handle, size);
+        self.check_compaction_policy(from_tier);
+        
+        walloc_event!(debug, from = ?from_tier, to = ?to_tier, size, asset = ?_asset, "migrate");
+        Ok(new_handle)
+    }
+    
+    fn check_free(&self, handle: MemoryHandle, size: usize, tier: Tier) -> Result<(), &'static str> {
+        if handle.is_null() {
+            return Err("Null handle");
+        }
+        
+        let arena = &self.arenas[tier as usize];
+        if !arena.contains(handle) {
+            return Err(if self.arenas.iter().any(|other| other.contains(handle)) {
+                "Handle belongs to a different tier"
+            } else if !self.owns(handle) {
+                "Handle belongs to another walloc instance"
+            } else {
+                "Handle is outside walloc memory"
+            });
+        }
+        
+        let local_offset = handle.offset() - arena.base_offset;
+        if local_offset + arena.align_size(size.max(1)) > arena.usage() {
+            return Err("Handle was never allocated");
+        }
+        if !local_offset.is_multiple_of(tier.alignment().max(SIMD_ALIGNMENT)) {
+            return Err("Handle is not the start of an allocation");
+        }
+        Ok(())
+    }
+
+    // Enhanced: Fast compact tier with proper data preservation
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "walloc.fast_compact", level = "debug", skip(self)))]
+    pub fn fast_compact_tier(&self, tier: Tier, preserve_bytes: usize) -> bool {
+        let arena = &self.arenas[tier as usize];
+        // The bump head must stay aligned or every later allocation in the tier is misaligned
+        let preserve_bytes = arena.align_size(preserve_bytes);
+        
+        let current_usage = arena.usage();
+        let capacity = arena.capacity();
+        
+        // If we need more space than currently allocated
+        if preserve_bytes > current_usage {
+            // Check if the requested size exceeds our capacity
+            if preserve_bytes > capacity {
+                #[cfg(target_arch = "wasm32")]
+                {
+                    // Get current memory stats
+                    let current_memory = self.get_memory_limit();
+                    let additional_needed = preserve_bytes - capacity;
+                    
+                    // Check if growth would exceed limit
+                    if current_memory + additional_needed > MAX_MEMORY_LIMIT {
+                        return false;
+                    }
+                    
+                    // Preserve existing data if needed
+                    let preserve_data = if current_usage > 0 {
+                        #[cfg(miri)]
+                        let mut data = vec![0u8; current_usage];
+                        #[cfg(not(miri))]
+                        let mut data = Vec::with_capacity(current_usage);
+                        unsafe {
+                            SIMDOps::fast_copy(arena.base_ptr(), data.as_mut_ptr(), current_usage);
+                            #[cfg(not(miri))]
+                            data.set_len(current_usage);
+                        }
+                        Some(data)
+                    } else {
+                        None
+                    };
+                    
+                    // Try to grow memory
+                    let pages_needed = (additional_needed + 65535) / 65536;
+                    let grow_result = core::arch::wasm32::memory_grow(0, pages_needed);
+                    
+                    if grow_result == usize::MAX {
+                        return false;
+                    }
+                    
+                    // Calculate new tier size
+                    let new_total_pages = grow_result + pages_needed;
+                    let new_total_size = new_total_pages * 65536;
+                    let tier_percentage = arena.tier.memory_percentage();
+                    let new_tier_size = (new_total_size * tier_percentage) / 100;
+                    
+                    // Extend arena capacity
+                    unsafe {
+                        arena.extend_capacity(new_tier_size);
+                    }
+                    
+                    // Restore preserved data
+                    if let Some(data) = preserve_data {
                         unsafe {
-                            let src_ptr = asset_meta.handle.to_ptr();
-                            if !src_ptr.is_null() {
-                                let mut temp = vec![0u8; asset_meta.size];
-                                SIMDOps::fast_copy(src_ptr, temp.as_mut_ptr(), asset_meta.size);
-                                preserve_buffer.extend_from_slice(&temp);
-                                new_offsets.push((asset_path.clone(), new_offset, asset_meta.clone()));
-                            }
+                            SIMDOps::fast_copy(data.as_ptr(), arena.base_ptr(), data.len());
                         }
                     }
                     
-                    // Reset the tier
-                    self.reset_tier(tier);
+                    // Set allocation head to preserve_bytes
+                    arena.retire_slices();
+                    arena.epoch.fetch_add(1, Ordering::SeqCst);
+                    arena.allocation_head.store(preserve_bytes, Ordering::SeqCst);
+                    arena.allocated().store(preserve_bytes, Ordering::SeqCst);
+                    
+                    // Clear freelists
+                    arena.clear_freelists();
+                    
+                    return true;
+                }
+                
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    // For non-WASM, we can't grow arena in place
+                    // But we can still preserve data by creating a temporary buffer
+                    return false;
+                }
+            }
+            
+            // We have enough capacity, just update allocation head
+            arena.retire_slices();
+            arena.epoch.fetch_add(1, Ordering::SeqCst);
+            arena.allocation_head.store(preserve_bytes, Ordering::SeqCst);
+            arena.allocated().store(preserve_bytes, Ordering::SeqCst);
+            
+            // Clear freelists
+            arena.clear_freelists();
+            
+            return true;
+        }
+        
+        // Standard case: preserve_bytes <= current_usage
+        // Use arena's fast compact
+        arena.fast_compact(preserve_bytes)
+    }
+    
+    // ================================
+    // === COMPACTION SCHEDULER ===
+    // ================================
+    
+    // When deferred, evictions only plan a compaction and the slide is left to compact_step
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_deferred_compaction(&self, deferred: bool) {
+        self.deferred_compaction.store(deferred, Ordering::Relaxed);
+    }
+    
+    // Plan a compaction of the tier's registered assets. Returns false if one is already in
+    // flight for the tier or there is nothing to slide.
+    pub fn begin_compaction(&self, tier: Tier) -> bool {
+        let mut jobs = self.compaction.lock().unwrap();
+        if jobs[tier as usize].is_some() {
+            return false;
+        }
+        
+        let arena = &self.arenas[tier as usize];
+        let mut assets: Vec<_> = self.assets.get_assets_by_tier(tier)
+            .into_iter()
+            .filter(|(_, metadata)| arena.contains(metadata.handle))
+            .map(|(key, metadata)| (key, metadata.handle, metadata.size))
+            .collect();
+        
+        if assets.is_empty() {
+            return false;
+        }
+        assets.sort_by_key(|&(_, handle, _)| handle.offset());
+        
+        // Nothing below the current head may be recycled while survivors slide over it
+        let start_head = arena.plan_compaction();
+        
+        walloc_event!(debug, ?tier, assets = assets.len(), head = start_head, "compaction planned");
+        jobs[tier as usize] = Some(CompactionJob {
+            tier,
+            assets,
+            cursor: 0,
+            start_head,
+            epoch: arena.epoch.load(Ordering::SeqCst),
+            assets_moved: 0,
+            bytes_moved: 0,
+        });
+        true
+    }
+    
+    // Advance the first pending compaction by roughly `budget_bytes` of relocation work.
+    // Every call moves at least one asset, so a small budget still makes progress.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "walloc.compact_step", level = "trace", skip(self)))]
+    pub fn compact_step(&self, budget_bytes: usize) -> Option<CompactionProgress> {
+        let mut jobs = self.compaction.lock().unwrap();
+        let slot = jobs.iter_mut().find(|job| job.is_some())?;
+        Some(self.step_compaction(slot, budget_bytes))
+    }
+    
+    pub fn compaction_progress(&self, tier: Tier) -> Option<CompactionProgress> {
+        let jobs = self.compaction.lock().unwrap();
+        jobs[tier as usize].as_ref().map(|job| job.progress(0))
+    }
+    
+    // Abandon an in-flight compaction. Assets already moved stay valid at their new offsets
+    // and the free space found so far goes back to the freelists.
+    pub fn cancel_compaction(&self, tier: Tier) -> bool {
+        let mut jobs = self.compaction.lock().unwrap();
+        if let Some(job) = jobs[tier as usize].take() {
+            self.arenas[tier as usize].end_compaction(job.start_head);
+            true
+        } else {
+            false
+        }
+    }
+    
+    fn compact_tier_step(&self, tier: Tier, budget_bytes: usize) -> Option<CompactionProgress> {
+        let mut jobs = self.compaction.lock().unwrap();
+        jobs[tier as usize].as_ref()?;
+        Some(self.step_compaction(&mut jobs[tier as usize], budget_bytes))
+    }
+    
+    // Drive the tier's compaction to completion on the calling thread
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "walloc.compaction", level = "debug", skip(self)))]
+    pub fn finish_compaction(&self, tier: Tier) -> Option<CompactionProgress> {
+        let mut jobs = self.compaction.lock().unwrap();
+        let mut progress = None;
+        while jobs[tier as usize].is_some() {
+            progress = Some(self.step_compaction(&mut jobs[tier as usize], usize::MAX));
+        }
+        progress
+    }
+    
+    fn step_compaction(&self, slot: &mut Option<CompactionJob>, budget_bytes: usize) -> CompactionProgress {
+        let job = slot.as_mut().expect("step_compaction on an idle slot");
+        let arena = &self.arenas[job.tier as usize];
+        
+        // A reset or fast compact underneath us invalidates the plan
+        if arena.epoch.load(Ordering::SeqCst) != job.epoch {
+            walloc_event!(debug, tier = ?job.tier, "compaction abandoned after arena reset");
+            arena.abandon_compaction();
+            job.cursor = job.assets.len();
+            let progress = job.progress(0);
+            *slot = None;
+            return progress;
+        }
+        
+        let mut spent = 0;
+        while job.cursor < job.assets.len() && (spent == 0 || spent < budget_bytes) {
+            let (ref key, handle, size) = job.assets[job.cursor];
+            job.cursor += 1;
+            
+            // Skip survivors that were evicted or replaced since planning
+            if !self.assets.get(key).is_some_and(|metadata| metadata.handle == handle) {
+                continue;
+            }
+            
+            let src_local = handle.offset() - arena.base_offset;
+            let block_size = arena.align_size(size);
+            
+            // Borrowed assets, e.g. behind a read_view guard, are pinned where they are, and so
+            // is anything without a free run right below it
+            let borrow = self.borrows.acquire(handle.offset(), handle.offset() + size, true);
+            if let Some(dst_local) = arena.slide_target(src_local).filter(|_| borrow.is_ok()) {
+                let new_handle = MemoryHandle(arena.base_offset + dst_local);
+                asan_unpoison(new_handle.resolve(self.handle_base), size);
+                unsafe {
+                    SIMDOps::fast_move(handle.resolve(self.handle_base), new_handle.resolve(self.handle_base), size);
+                }
+                let vacated = handle.offset().max(new_handle.offset() + size);
+                asan_poison(MemoryHandle(vacated).resolve(self.handle_base), handle.offset() + size - vacated);
+                
+                if self.assets.update_handle(key, handle, new_handle) {
+                    #[cfg(feature = "track-callers")]
+                    arena.move_call_site(handle.offset(), new_handle.offset());
+                    arena.move_tag(handle.offset(), new_handle.offset());
+                    arena.move_block(handle.offset(), new_handle.offset());
+                    arena.commit_slide(src_local, dst_local, block_size);
+                    job.assets_moved += 1;
+                    job.bytes_moved += size;
+                    spent += size;
+                }
+            }
+        }
+        
+        if job.cursor < job.assets.len() {
+            walloc_event!(trace, tier = ?job.tier, moved = job.assets_moved, remaining = job.assets.len() - job.cursor, "compaction step");
+            return job.progress(0);
+        }
+        
+        let reclaimed = arena.end_compaction(job.start_head);
+        if reclaimed > 0 {
+            arena.wake_waiters();
+        }
+        
+        let progress = job.progress(reclaimed);
+        walloc_event!(
+            debug,
+            tier = ?job.tier,
+            assets_moved = progress.assets_moved,
+            bytes_moved = progress.bytes_moved,
+            bytes_reclaimed = progress.bytes_reclaimed,
+            "compaction complete"
+        );
+        *slot = None;
+        progress
+    }
+    
+    // Policies only ever slide registered assets, so only attach them to tiers whose live
+    // data is tracked through the asset registry.
+    pub fn set_compaction_policy(&self, tier: Tier, policy: Option<CompactionPolicy>) {
+        self.compaction_policies.write().unwrap()[tier as usize] = policy;
+    }
+    
+    // Call `callback` on the allocating (or freeing) thread when the tier's live bytes cross
+    // `threshold` (a fraction of capacity) in either direction. Several thresholds may be
+    // registered per tier, e.g. 0.75 to warn and 0.9 for critical.
+    pub fn on_watermark(&self, tier: Tier, threshold: f64, callback: impl Fn(&WatermarkEvent) + Send + Sync + 'static) {
+        self.watermarks[tier as usize].add(threshold, Arc::new(callback));
+        self.check_watermarks(tier);
+    }
+    
+    // Watermark events for `thresholds`, delivered through a channel instead of a callback.
+    // Events stop once the receiver is dropped.
+    pub fn watermark_channel(&self, tier: Tier, thresholds: &[f64]) -> std::sync::mpsc::Receiver<WatermarkEvent> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for &threshold in thresholds {
+            let sender = sender.clone();
+            self.on_watermark(tier, threshold, move |event| {
+                let _ = sender.send(*event);
+            });
+        }
+        receiver
+    }
+    
+    pub fn clear_watermarks(&self, tier: Tier) {
+        self.watermarks[tier as usize].clear();
+    }
+    
+    #[inline(always)]
+    fn check_watermarks(&self, tier: Tier) {
+        let watermarks = &self.watermarks[tier as usize];
+        if watermarks.active.load(Ordering::Acquire) {
+            let arena = &self.arenas[tier as usize];
+            watermarks.check(tier, arena.live_bytes(), arena.capacity());
+        }
+    }
+    
+    pub fn fragmentation(&self, tier: Tier) -> f64 {
+        self.arenas[tier as usize].fragmentation()
+    }
+    
+    pub fn fragmentation_stats(&self, tier: Tier) -> FragmentationStats {
+        self.arenas[tier as usize].fragmentation_stats()
+    }
+    
+    // Give each thread a private `slice_size` run of the tier so small allocations don't
+    // contend on the shared bump head. Requests over half a slice bypass slices, and threads
+    // steal from other slices once the tier is full. None turns slices off again.
+    pub fn set_thread_slices(&self, tier: Tier, slice_size: Option<usize>) {
+        self.arenas[tier as usize].set_slice_size(slice_size.unwrap_or(0));
+    }
+    
+    // Queue the tier's frees on per-arena shards that allocations drain, instead of having
+    // every deallocate CAS the shared freelists. Freelist stats and trimming lag until the
+    // next allocation or flush_free_queues; disabling drains everything queued.
+    pub fn set_free_queues(&self, tier: Tier, enabled: bool) {
+        self.arenas[tier as usize].set_free_queues(enabled);
+    }
+    
+    // Let contended size classes fall back to flat combining (see FLAT COMBINING): a class
+    // switches over for a while once a freelist pop or push needs `Some(retries)` CAS
+    // retries. Some(0) combines always, None never. Defaults to Some(4).
+    pub fn set_flat_combining(&self, tier: Tier, retries: Option<usize>) {
+        self.arenas[tier as usize].set_combining_threshold(retries);
+    }
+    
+    // Give each bounded size class of the tier its own `slab_size` runs to bump through, so
+    // same-sized objects are packed together instead of interleaved with every other size.
+    // Requests over a quarter slab and catch-all sizes bypass slabs, slab classes recycle
+    // only from their own freelist, and thread slices take precedence where both are on.
+    // None turns slabs off again and frees what the current slabs had left.
+    pub fn set_size_class_slabs(&self, tier: Tier, slab_size: Option<usize>) {
+        self.arenas[tier as usize].set_slab_size(slab_size.unwrap_or(0));
+    }
+    
+    // Drain the tier's free queues now, e.g. before reading fragmentation stats
+    pub fn flush_free_queues(&self, tier: Tier) -> usize {
+        self.arenas[tier as usize].drain_free_queues()
+    }
+    
+    // Per-class freelist hit rates, for tuning with_size_classes
+    pub fn size_class_stats(&self, tier: Tier) -> Vec<SizeClassStats> {
+        self.arenas[tier as usize].size_class_stats()
+    }
+    
+    // Debugging aid for use-after-free: freed blocks of the tier are filled with
+    // QUARANTINE_POISON and held back until `allocations` further allocations have been made
+    // (checked as later blocks are freed), so stale reads see the pattern instead of new data
+    // and stale writes are counted as violations on release. Zero turns it off and releases
+    // everything held.
+    pub fn set_quarantine(&self, tier: Tier, allocations: usize) {
+        self.arenas[tier as usize].set_quarantine(allocations);
+    }
+    
+    pub fn quarantine_stats(&self, tier: Tier) -> QuarantineStats {
+        self.arenas[tier as usize].quarantine_stats()
+    }
+    
+    // Size histograms are off by default; enabling one starts counting from that point
+    pub fn set_histogram_enabled(&self, tier: Tier, enabled: bool) {
+        self.arenas[tier as usize].histogram.enabled.store(enabled, Ordering::Relaxed);
+    }
+    
+    pub fn allocation_histogram(&self, tier: Tier) -> AllocationHistogram {
+        self.arenas[tier as usize].histogram.snapshot(tier)
+    }
+    
+    pub fn reset_allocation_histogram(&self, tier: Tier) {
+        self.arenas[tier as usize].histogram.clear();
+    }
+    
+    // Time every allocate and deallocate in the tier by the path it took, and count CAS
+    // retries. Off by default; like the histogram it keeps what it gathered when disabled.
+    pub fn set_latency_profiling(&self, tier: Tier, enabled: bool) {
+        self.arenas[tier as usize].latency.enabled.store(enabled, Ordering::Relaxed);
+    }
+    
+    pub fn latency_profile(&self, tier: Tier) -> LatencyProfile {
+        self.arenas[tier as usize].latency.snapshot()
+    }
+    
+    pub fn reset_latency_profile(&self, tier: Tier) {
+        self.arenas[tier as usize].latency.clear();
+    }
+    
+    // Cheap copy of the per-tier counters; diff two of these to measure a frame or a level load
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            tiers: std::array::from_fn(|i| {
+                let arena = &self.arenas[i];
+                let counters = &arena.counters;
+                let slices = arena.slice_totals();
+                TierSnapshot {
+                    allocations: counters.allocations().load(Ordering::Relaxed).wrapping_add(slices.allocations),
+                    frees: counters.frees().load(Ordering::Relaxed),
+                    bytes_allocated: counters.bytes_allocated().load(Ordering::Relaxed).wrapping_add(slices.requested),
+                    bytes_freed: counters.bytes_freed().load(Ordering::Relaxed),
+                    evictions: counters.evictions.load(Ordering::Relaxed),
+                    used: arena.usage(),
+                    live_allocations: arena.live_allocations(),
+                }
+            }),
+        }
+    }
+    
+    // Structured counterpart of the WASM wrapper's memory_stats, available on every platform
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut tiers = Vec::with_capacity(3);
+        let mut total_used = 0;
+        
+        for tier in [Tier::Top, Tier::Middle, Tier::Bottom] {
+            let (used, capacity, high_water_mark, total_allocated) = self.tier_stats(tier);
+            let frag = self.fragmentation_stats(tier);
+            let assets = self.assets.get_assets_by_tier(tier);
+            total_used += used;
+            
+            tiers.push(TierStats {
+                name: tier.name(),
+                used,
+                capacity,
+                high_water_mark,
+                total_allocated,
+                memory_saved: total_allocated.saturating_sub(used),
+                freelist_bytes: frag.freelist_bytes,
+                free_bytes: frag.free_bytes,
+                largest_free_block: frag.largest_free_block,
+                fragmentation: frag.fragmentation,
+                asset_count: assets.len(),
+                asset_bytes: assets.iter().map(|(_, metadata)| metadata.size).sum(),
+            });
+        }
+        
+        MemoryStats {
+            tiers,
+            total_used,
+            raw_memory_size: self.get_memory_limit(),
+            allocator_type: "lock-free-tiered",
+            memory_utilization: self.memory_utilization(),
+            asset_count: self.assets.len(),
+            fetch: self.fetch_stats.snapshot(),
+        }
+    }
+    
+    // Timing percentiles over recent loads, for diagnosing slow ones in the field
+    pub fn network_stats(&self) -> NetworkStats {
+        self.fetch_stats.timings.stats()
+    }
+    
+    pub fn stats_json(&self) -> String {
+        serde_json::to_string(&self.memory_stats()).expect("memory stats are always serializable")
+    }
+    
+    // Where the tier's live allocations came from. Registered assets count as live too.
+    #[cfg(feature = "track-callers")]
+    pub fn leak_report(&self, tier: Tier) -> LeakReport {
+        let sites = self.arenas[tier as usize].call_sites.lock().unwrap();
+        let mut grouped: HashMap<&'static std::panic::Location<'static>, LeakSite> = HashMap::new();
+        
+        for (&offset, site) in sites.iter() {
+            let entry = grouped.entry(site.location).or_insert_with(|| LeakSite {
+                location: site.location,
+                count: 0,
+                bytes: 0,
+                handles: Vec::new(),
+            });
+            entry.count += 1;
+            entry.bytes += site.size;
+            entry.handles.push(MemoryHandle(offset));
+        }
+        
+        let mut sites: Vec<_> = grouped.into_values().collect();
+        for site in &mut sites {
+            site.handles.sort_by_key(|handle| handle.offset());
+        }
+        sites.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.location.to_string().cmp(&b.location.to_string())));
+        
+        LeakReport {
+            tier,
+            total_count: sites.iter().map(|site| site.count).sum(),
+            total_bytes: sites.iter().map(|site| site.bytes).sum(),
+            sites,
+        }
+    }
+    
+    // Write a heap dump (format documented above HeapDump). Freelist contents are walked
+    // in place, so take dumps while the allocator is quiescent.
+    pub fn dump_heap(&self, write: impl Write) -> io::Result<()> {
+        let mut out = io::BufWriter::new(write);
+        out.write_all(HEAP_DUMP_MAGIC)?;
+        out.write_all(&HEAP_DUMP_VERSION.to_le_bytes())?;
+        out.write_all(&(self.get_memory_limit() as u64).to_le_bytes())?;
+        
+        for arena in &self.arenas {
+            let mut payload = vec![arena.tier as u8];
+            for value in [
+                arena.base_offset,
+                arena.capacity(),
+                arena.usage(),
+                arena.live_bytes(),
+                arena.live_allocations(),
+                arena.peak_usage.load(Ordering::Acquire),
+                arena.high_water_mark.load(Ordering::Acquire),
+                arena.total_allocated().load(Ordering::Acquire),
+                arena.compaction_limit.load(Ordering::Acquire),
+            ] {
+                put_u64(&mut payload, value);
+            }
+            write_dump_section(&mut out, DUMP_ARENA, &payload)?;
+            
+            let blocks = arena.free_blocks();
+            let mut payload = vec![arena.tier as u8];
+            put_u64(&mut payload, blocks.len());
+            for (class, handle, size) in blocks {
+                payload.push(class as u8);
+                put_u64(&mut payload, handle.offset());
+                put_u64(&mut payload, size);
+            }
+            write_dump_section(&mut out, DUMP_FREELIST, &payload)?;
+            
+            #[cfg(feature = "track-callers")]
+            {
+                let mut sites: Vec<_> = arena.call_sites.lock().unwrap()
+                    .iter()
+                    .map(|(&offset, &site)| (offset, site))
+                    .collect();
+                sites.sort_by_key(|&(offset, _)| offset);
+                
+                let mut payload = vec![arena.tier as u8];
+                put_u64(&mut payload, sites.len());
+                for (offset, site) in sites {
+                    put_u64(&mut payload, offset);
+                    put_u64(&mut payload, site.size);
+                    payload.extend_from_slice(&site.location.line().to_le_bytes());
+                    payload.extend_from_slice(&site.location.column().to_le_bytes());
+                    payload.extend_from_slice(&(site.location.file().len() as u32).to_le_bytes());
+                    payload.extend_from_slice(site.location.file().as_bytes());
+                }
+                write_dump_section(&mut out, DUMP_CALL_SITES, &payload)?;
+            }
+        }
+        
+        write_dump_section(&mut out, DUMP_ASSETS, &self.encode_assets(0))?;
+        
+        write_dump_section(&mut out, DUMP_END, &[])?;
+        out.flush()
+    }
+    
+    // Idle hook for the game loop (or requestIdleCallback): evaluates every tier's policy and
+    // performs the resulting compaction work.
+    pub fn on_idle(&self) -> Vec<CompactionProgress> {
+        let policies = *self.compaction_policies.read().unwrap();
+        
+        [Tier::Top, Tier::Middle, Tier::Bottom]
+            .into_iter()
+            .filter_map(|tier| {
+                let policy = policies[tier as usize]?;
+                self.apply_compaction_policy(tier, policy, true)
+            })
+            .collect()
+    }
+    
+    // Evaluated after frees: re-arms watermarks and plans jobs for policies that don't wait
+    // for idle. Nothing is moved here, since the freeing caller may still hold handles into
+    // the tier.
+    fn check_compaction_policy(&self, tier: Tier) {
+        self.check_watermarks(tier);
+        let policy = self.compaction_policies.read().unwrap()[tier as usize];
+        if let Some(policy) = policy
+            && !policy.only_when_idle
+        {
+            self.apply_compaction_policy(tier, policy, false);
+        }
+    }
+    
+    fn apply_compaction_policy(&self, tier: Tier, policy: CompactionPolicy, idle: bool) -> Option<CompactionProgress> {
+        if self.compaction_progress(tier).is_none() {
+            let arena = &self.arenas[tier as usize];
+            let over_threshold = arena.fragmentation() > policy.threshold &&
+                arena.free_bytes() >= policy.min_free_bytes;
+            
+            if !over_threshold || !self.begin_compaction(tier) {
+                return None;
+            }
+        }
+        
+        // Outside idle the job is only planned; idle ticks or compact_step do the work
+        match policy.mode {
+            _ if !idle => self.compaction_progress(tier),
+            CompactionMode::Synchronous => self.finish_compaction(tier),
+            CompactionMode::Incremental { budget_bytes } => self.compact_tier_step(tier, budget_bytes),
+        }
+    }
+    
+    // ================================
+    // === TIER RESIZING ===
+    // ================================
+
+    // Change a tier's capacity while its data stays live, returning the new capacity (rounded
+    // up to a cache line). Shrinking compacts the tier if its head is past the new end and
+    // hands the freed tail to the next tier. Growing takes the next tier's free space, sliding
+    // its contents along; Bottom grows into space it gave up earlier or that the backend can
+    // add. Registered assets in moved tiers are repointed; other handles into them are left
+    // dangling, as after compaction. A failed grow may already have compacted tiers.
+    pub fn resize_tier(&mut self, tier: Tier, new_size: usize) -> Result<usize, &'static str> {
+        #[cfg(unix)]
+        if self.shared_registry.is_some() {
+            return Err("Shared memory tiers cannot be resized");
+        }
+        if self.compaction.get_mut().unwrap().iter().any(Option::is_some) {
+            return Err("A compaction is in flight");
+        }
+        if !self.streams.get_mut().unwrap().is_empty() {
+            return Err("Streams are still writing into the tiers");
+        }
+
+        let index = tier as usize;
+        let new_size = new_size.next_multiple_of(CACHE_LINE_SIZE);
+        let old_size = self.arenas[index].capacity();
+        if new_size < old_size {
+            self.shrink_tier(index, new_size)?;
+        } else if new_size > old_size {
+            self.grow_tier(index, new_size)?;
+        }
+
+        walloc_event!(debug, ?tier, from = old_size, to = new_size, "resize_tier");
+        for arena in &self.arenas {
+            let usage = arena.usage();
+            asan_poison(unsafe { arena.base_ptr().add(usage) }, arena.capacity() - usage);
+            self.check_watermarks(arena.tier);
+        }
+        Ok(new_size)
+    }
+
+    fn shrink_tier(&mut self, index: usize, new_size: usize) -> Result<(), &'static str> {
+        let tier = self.arenas[index].tier;
+        if self.arenas[index].reserved.load(Ordering::Relaxed) > new_size {
+            return Err("New size is smaller than the tier's reserve");
+        }
+        if self.arenas[index].usage() > new_size && self.begin_compaction(tier) {
+            self.finish_compaction(tier);
+        }
+        if self.arenas[index].usage() > new_size {
+            return Err("Live data does not fit in the new size");
+        }
+
+        let arena = &mut self.arenas[index];
+        arena.retire_slices();
+        arena.size.store(new_size, Ordering::SeqCst);
+        let end = arena.base_offset + new_size;
+
+        // The next tier slides down into the freed tail; Bottom just leaves it unused
+        if let Some(next) = self.arenas.get(index + 1) {
+            let next_size = next.base_offset + next.capacity() - end;
+            self.relocate_tier(index + 1, end, next_size);
+        }
+        Ok(())
+    }
+
+    fn grow_tier(&mut self, index: usize, new_size: usize) -> Result<(), &'static str> {
+        let end = self.arenas[index].base_offset + new_size;
+        let bottom = &self.arenas[Tier::Bottom as usize];
+        let limit = match self.arenas.get(index + 1) {
+            Some(next) => next.base_offset,
+            None => (self.handle_space + self.memory_size).max(bottom.base_offset + bottom.capacity()),
+        };
+
+        if end > limit {
+            if index + 1 < self.arenas.len() {
+                self.yield_front(index + 1, end - limit)?;
+            } else if self.backend.grow(end - limit).is_none() {
+                return Err("Backing store cannot grow");
+            }
+        }
+
+        let arena = &self.arenas[index];
+        arena.size.store(new_size, Ordering::SeqCst);
+        arena.wake_waiters();
+        Ok(())
+    }
+
+    // Free `bytes` at the front of the tier by sliding its contents toward its end, growing
+    // the tier first when its free tail is too short
+    fn yield_front(&mut self, index: usize, bytes: usize) -> Result<(), &'static str> {
+        let tier = self.arenas[index].tier;
+        let needed = |arena: &LockFreeArena| arena.usage().max(arena.reserved.load(Ordering::Relaxed)) + bytes;
+        if needed(&self.arenas[index]) > self.arenas[index].capacity() && self.begin_compaction(tier) {
+            self.finish_compaction(tier);
+        }
+        let needed = needed(&self.arenas[index]);
+        if needed > self.arenas[index].capacity() {
+            self.grow_tier(index, needed.next_multiple_of(CACHE_LINE_SIZE))?;
+        }
+
+        let arena = &self.arenas[index];
+        self.relocate_tier(index, arena.base_offset + bytes, arena.capacity() - bytes);
+        Ok(())
+    }
+
+    // Move a whole tier and repoint the registered assets that live in it
+    fn relocate_tier(&mut self, index: usize, new_base: usize, new_size: usize) {
+        let arena = &self.arenas[index];
+        let old_base = arena.base_offset;
+        let moved: Vec<_> = self.assets.get_assets_by_tier(arena.tier)
+            .into_iter()
+            .filter(|(_, metadata)| arena.contains(metadata.handle))
+            .collect();
+
+        self.arenas[index].relocate(new_base, new_size);
+        for (key, metadata) in moved {
+            let new_handle = MemoryHandle(metadata.handle.offset() - old_base + new_base);
+            self.assets.update_handle(&key, metadata.handle, new_handle);
+        }
+    }
+
+    // ================================
+    // === DATA OPERATIONS ===
+    // ================================
+    
+    #[inline(always)]
+    fn get_memory_limit(&self) -> usize {
+        #[cfg(target_arch = "wasm32")]
+        {
+            core::arch::wasm32::memory_size(0) * 65536
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.memory_size
+        }
+    }
+    
+    // The range must end inside the block that owns `handle`; see check_range
+    pub fn write_data(&self, handle: MemoryHandle, data: &[u8]) -> Result<(), AccessError> {
+        self.check_range(handle, data.len())?;
+        unsafe {
+            SIMDOps::fast_copy(data.as_ptr(), handle.resolve(self.handle_base), data.len());
+        }
+        Ok(())
+    }
+    
+    pub fn read_data(&self, handle: MemoryHandle, length: usize) -> Result<Vec<u8>, AccessError> {
+        match self.check_range(handle, length) {
+            Err(AccessError::OutOfBounds) if self.is_mapped_range(handle, length) => {}
+            result => {
+                result?;
+            }
+        }
+        Ok(unsafe { self.copy_out(handle, length) })
+    }
+    
+    // Sparse access inside a block: bounds are checked from `handle`, so the range has to end
+    // inside the block that owns it rather than whatever `offset` lands in
+    pub fn write_at(&self, handle: MemoryHandle, offset: usize, data: &[u8]) -> Result<(), AccessError> {
+        let len = offset.checked_add(data.len()).ok_or(AccessError::OutOfBounds)?;
+        self.check_range(handle, len)?;
+        unsafe {
+            SIMDOps::fast_copy(data.as_ptr(), handle.advance(offset).resolve(self.handle_base), data.len());
+        }
+        Ok(())
+    }
+    
+    pub fn read_at(&self, handle: MemoryHandle, offset: usize, length: usize) -> Result<Vec<u8>, AccessError> {
+        let len = offset.checked_add(length).ok_or(AccessError::OutOfBounds)?;
+        match self.check_range(handle, len) {
+            Err(AccessError::OutOfBounds) if self.is_mapped_range(handle, len) => {}
+            result => {
+                result?;
+            }
+        }
+        Ok(unsafe { self.copy_out(handle.advance(offset), length) })
+    }
+    
+    // memmove inside one block, e.g. to compact a ring buffer; the ranges may overlap
+    pub fn copy_within(&self, handle: MemoryHandle, src_offset: usize, dst_offset: usize, len: usize) -> Result<(), AccessError> {
+        let end = src_offset.max(dst_offset).checked_add(len).ok_or(AccessError::OutOfBounds)?;
+        self.check_range(handle, end)?;
+        unsafe {
+            SIMDOps::fast_move(handle.advance(src_offset).resolve(self.handle_base), handle.advance(dst_offset).resolve(self.handle_base), len);
+        }
+        Ok(())
+    }
+    
+    /// # Safety
+    ///
+    /// Only checks that the range lies inside walloc memory, as write_data used to: it may
+    /// overwrite neighbouring allocations, assets or freelist links.
+    pub unsafe fn write_data_unchecked(&self, handle: MemoryHandle, data: &[u8]) -> Result<(), AccessError> {
+        if handle.is_null() {
+            return Err(AccessError::NullHandle);
+        }
+        if self.base_relative(handle).saturating_add(data.len()) > self.get_memory_limit() {
+            return Err(AccessError::OutOfBounds);
+        }
+        unsafe {
+            SIMDOps::fast_copy(data.as_ptr(), handle.resolve(self.handle_base), data.len());
+        }
+        Ok(())
+    }
+    
+    /// # Safety
+    ///
+    /// Only checks that the range lies inside walloc memory, as read_data used to: it may
+    /// read neighbouring allocations or memory that is being written concurrently.
+    pub unsafe fn read_data_unchecked(&self, handle: MemoryHandle, length: usize) -> Result<Vec<u8>, AccessError> {
+        if handle.is_null() {
+            return Err(AccessError::NullHandle);
+        }
+        if self.base_relative(handle).saturating_add(length) > self.get_memory_limit() {
+            return Err(AccessError::OutOfBounds);
+        }
+        Ok(unsafe { self.copy_out(handle, length) })
+    }
+    
+    unsafe fn copy_out(&self, handle: MemoryHandle, length: usize) -> Vec<u8> {
+        // Under Miri the buffer is zeroed first so no uninitialised length is ever exposed
+        #[cfg(miri)]
+        let mut buffer = vec![0u8; length];
+        #[cfg(not(miri))]
+        let mut buffer = Vec::with_capacity(length);
+        unsafe {
+            SIMDOps::fast_copy(handle.resolve(self.handle_base), buffer.as_mut_ptr(), length);
+            #[cfg(not(miri))]
+            buffer.set_len(length);
+        }
+        buffer
+    }
+    
+    #[cfg(not(unix))]
+    fn is_mapped_range(&self, _handle: MemoryHandle, _len: usize) -> bool {
+        false
+    }
+    
+    // The range must end inside the block that owns `handle`: the registered asset containing
+    // it, else the live allocation it starts in, narrowed to the user bytes of a headed one.
+    // Offsets in no asset or live block are refused, and no range may run into a registered
+    // asset, so a block sitting in front of one cannot reach it.
+    fn check_range(&self, handle: MemoryHandle, len: usize) -> Result<(usize, usize), AccessError> {
+        if handle.is_null() {
+            return Err(AccessError::NullHandle);
+        }
+        
+        let start = handle.offset();
+        let end = start.checked_add(len).ok_or(AccessError::OutOfBounds)?;
+        let arena = self.arenas.iter()
+            .find(|arena| arena.contains(handle))
+            .ok_or(if self.owns(handle) { AccessError::OutOfBounds } else { AccessError::WrongInstance })?;
+        
+        let assets = self.assets.assets.read().unwrap();
+        if assets.asset_starts_within(start, end) {
+            return Err(AccessError::OutOfBounds);
+        }
+        // Registered assets bound themselves, including those synced from a shared segment
+        // whose blocks were allocated by another process
+        let limit = match assets.asset_covering(start) {
+            Some((_, asset_end)) => asset_end,
+            None => {
+                drop(assets);
+                let (block_start, block_size) = arena.block_at(start).ok_or(AccessError::OutOfBounds)?;
+                let headed = block_start + arena.header_prefix() == start;
+                headed.then(|| arena.read_header(handle))
+                    .flatten()
+                    .filter(|header| header.magic == HEADER_LIVE && header.tier == arena.tier as u8)
+                    .map_or(block_start + block_size, |header| start + header.size as usize)
+            }
+        };
+        if end > limit {
+            return Err(AccessError::OutOfBounds);
+        }
+        
+        Ok((start, end))
+    }
+    
+    // Lend `len` bytes at `handle` as a mutable slice for the duration of `f`. Overlapping
+    // with_slice/with_slice_ref calls fail with Aliased instead of racing; raw pointer
+    // access through handle_ptr or write_data is not tracked.
+    pub fn with_slice<R>(&self, handle: MemoryHandle, len: usize, f: impl FnOnce(&mut [u8]) -> R) -> Result<R, AccessError> {
+        let (start, end) = self.check_range(handle, len)?;
+        let _guard = self.borrows.acquire(start, end, true)?;
+        let buf = unsafe { std::slice::from_raw_parts_mut(handle.resolve(self.handle_base), len) };
+        Ok(f(buf))
+    }
+    
+    // Shared variant: any number of readers may overlap, but not with a with_slice writer
+    pub fn with_slice_ref<R>(&self, handle: MemoryHandle, len: usize, f: impl FnOnce(&[u8]) -> R) -> Result<R, AccessError> {
+        let (start, end) = match self.check_range(handle, len) {
+            // Mapped files are read-only, so there is no writer to exclude
+            Err(AccessError::OutOfBounds) if self.is_mapped_range(handle, len) => {
+                return Ok(f(unsafe { std::slice::from_raw_parts(handle.resolve(self.handle_base) as *const u8, len) }));
+            }
+            result => result?,
+        };
+        let _guard = self.borrows.acquire(start, end, false)?;
+        let buf = unsafe { std::slice::from_raw_parts(handle.resolve(self.handle_base) as *const u8, len) };
+        Ok(f(buf))
+    }
+    
+    // Zero-copy read_data: the shared borrow lasts as long as the guard
+    pub fn read_view(&self, handle: MemoryHandle, len: usize) -> Result<ReadGuard<'_>, AccessError> {
+        let (start, end) = match self.check_range(handle, len) {
+            Err(AccessError::OutOfBounds) if self.is_mapped_range(handle, len) => {
+                return Ok(ReadGuard { handle, ptr: self.handle_ptr(handle), len, _borrow: None });
+            }
+            result => result?,
+        };
+        let borrow = self.borrows.acquire(start, end, false)?;
+        Ok(ReadGuard { handle, ptr: self.handle_ptr(handle), len, _borrow: Some(borrow) })
+    }
+    
+    // Byte length of `count` Ts at `handle`, once T's alignment is known to hold there. Tier
+    // memory also needs a tier alignment of at least T's, so neighbouring blocks stay castable.
+    fn cast_len<T: Pod>(&self, handle: MemoryHandle, count: usize) -> Result<usize, AccessError> {
+        if handle.is_null() {
+            return Err(AccessError::NullHandle);
+        }
+        let align = std::mem::align_of::<T>();
+        let tier_too_small = self.arenas.iter()
+            .find(|arena| arena.contains(handle))
+            .is_some_and(|arena| arena.tier.alignment() < align);
+        if std::mem::size_of::<T>() == 0 || tier_too_small || !handle.resolve(self.handle_base).cast::<T>().is_aligned() {
+            return Err(AccessError::Misaligned);
+        }
+        std::mem::size_of::<T>().checked_mul(count).ok_or(AccessError::OutOfBounds)
+    }
+    
+    // `count` Ts at `handle` as a shared slice, checked like read_view
+    pub fn cast_slice<T: Pod>(&self, handle: MemoryHandle, count: usize) -> Result<SliceRef<'_, T>, AccessError> {
+        let len = self.cast_len::<T>(handle, count)?;
+        Ok(SliceRef { bytes: self.read_view(handle, len)?, _marker: PhantomData })
+    }
+    
+    // Exclusive variant, checked like with_slice; other views of the range fail with Aliased
+    pub fn cast_slice_mut<T: Pod>(&self, handle: MemoryHandle, count: usize) -> Result<SliceMut<'_, T>, AccessError> {
+        let len = self.cast_len::<T>(handle, count)?;
+        let (start, end) = self.check_range(handle, len)?;
+        let borrow = self.borrows.acquire(start, end, true)?;
+        Ok(SliceMut { ptr: self.handle_ptr(handle), len, _borrow: borrow, _marker: PhantomData })
+    }
+    
+    // Same range checks as with_slice; the borrow lasts as long as the writer
+    pub fn handle_writer(&self, handle: MemoryHandle, capacity: usize) -> Result<HandleWriter<'_>, AccessError> {
+        let (start, end) = self.check_range(handle, capacity)?;
+        let borrow = self.borrows.acquire(start, end, true)?;
+        Ok(HandleWriter { handle, ptr: self.handle_ptr(handle), capacity, written: 0, _borrow: borrow })
+    }
+    
+    // None for null handles and offsets outside every tier. The freelist and registry lookups
+    // are linear scans, so this is meant for debugging rather than hot paths.
+    pub fn describe_handle(&self, handle: MemoryHandle) -> Option<HandleInfo> {
+        let arena = self.arenas.iter().find(|arena| arena.contains(handle))?;
+        let tier = arena.tier;
+        let offset = handle.offset();
+        let tier_offset = offset - arena.base_offset;
+        let address = handle.resolve(self.handle_base) as usize;
+        
+        let on_freelist = arena.free_blocks().iter()
+            .any(|&(_, block, size)| offset >= block.offset() && offset < block.offset() + size);
+        
+        let mut size = None;
+        let asset = self.assets.assets.read().unwrap().iter()
+            .find(|(_, metadata)| {
+                metadata.tier == tier && offset >= metadata.handle.offset()
+                    && offset < metadata.handle.offset() + metadata.size.max(1)
+            })
+            .map(|(key, metadata)| {
+                if metadata.handle == handle {
+                    size = Some(metadata.size);
+                }
+                key.clone()
+            });
+        
+        #[cfg(feature = "track-callers")]
+        if size.is_none() {
+            size = arena.call_sites.lock().unwrap().get(&offset).map(|site| site.size);
+        }
+        
+        if size.is_none() {
+            size = arena.read_header(handle)
+                .filter(|header| header.magic == HEADER_LIVE && header.tier == tier as u8)
+                .map(|header| header.size as usize);
+        }
+        if size.is_none() {
+            size = arena.block_at(offset).filter(|&(start, _)| start == offset).map(|(_, size)| size);
+        }
+        
+        Some(HandleInfo {
+            tier,
+            tier_name: tier.name(),
+            offset,
+            tier_offset,
+            in_live_range: tier_offset < arena.usage(),
+            block_aligned: tier_offset.is_multiple_of(tier.alignment().max(SIMD_ALIGNMENT)),
+            alignment: 1 << address.trailing_zeros().min(MAX_REPORTED_ALIGNMENT.trailing_zeros()),
+            on_freelist,
+            size,
+            asset,
+        })
+    }
+    
+    // Zero-copy view of a registered asset's bytes (e.g. a downloaded JSON payload)
+    pub fn with_asset<R>(&self, key: &str, f: impl FnOnce(&[u8]) -> R) -> Result<R, AccessError> {
+        let metadata = self.assets.get(key).ok_or(AccessError::UnknownAsset)?;
+        self.assets.touch(key);
+        self.with_slice_ref(metadata.handle, metadata.size, f)
+    }
+    
+    // Temporary Bottom-tier buffer lent to `f` and freed when it returns or unwinds. None if
+    // the tier cannot fit it; the contents start out as whatever the block last held.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn with_scratch<R>(&self, size: usize, f: impl FnOnce(&mut [u8]) -> R) -> Option<R> {
+        if size == 0 {
+            return Some(f(&mut []));
+        }
+        let handle = self.allocate(size, Tier::Bottom)?;
+        let _scratch = ScratchBlock { walloc: self, handle, size };
+        let buf = unsafe { std::slice::from_raw_parts_mut(handle.resolve(self.handle_base), size) };
+        Some(f(buf))
+    }
+    
+    pub fn allocate_typed<T: Pod>(&self, tier: Tier) -> Option<TypedHandle<'_, T>> {
+        self.allocate_typed_slice(1, tier)
+    }
+    
+    // Fails if the tier cannot satisfy T's alignment (e.g. 64-byte types in Bottom)
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_typed_slice<T: Pod>(&self, len: usize, tier: Tier) -> Option<TypedHandle<'_, T>> {
+        let size = std::mem::size_of::<T>().checked_mul(len).filter(|&size| size > 0)?;
+        let handle = self.allocate(size, tier)?;
+        
+        match self.typed_handle(handle, len) {
+            Ok(typed) => Some(typed),
+            Err(_) => {
+                self.arenas[tier as usize].deallocate(handle, size);
+                None
+            }
+        }
+    }
+    
+    // View an existing allocation as `len` Ts, checking alignment and that they fit the asset
+    // or live block `handle` points into (see check_range)
+    pub fn typed_handle<T: Pod>(&self, handle: MemoryHandle, len: usize) -> Result<TypedHandle<'_, T>, &'static str> {
+        if handle.is_null() {
+            return Err("Memory handle is null");
+        }
+        if std::mem::size_of::<T>() == 0 {
+            return Err("Zero-sized types are not supported");
+        }
+        if !handle.resolve(self.handle_base).cast::<T>().is_aligned() {
+            return Err("Memory handle is misaligned for type");
+        }
+        
+        let size = std::mem::size_of::<T>().checked_mul(len).ok_or("Memory access out of bounds")?;
+        self.check_range(handle, size).map_err(|_| "Memory access out of bounds")?;
+        
+        Ok(TypedHandle { walloc: self, handle, len, _marker: PhantomData })
+    }
+    
+    /// # Safety
+    /// See `SIMDOps::bulk_copy_optimized`: handles are not validated against their allocations.
+    pub unsafe fn bulk_copy(&self, operations: &[(MemoryHandle, MemoryHandle, usize)]) {
+        unsafe { SIMDOps::bulk_copy_optimized(self.handle_base, operations); }
+    }
+    
+    // ================================
+    // === ENHANCED ASSET MANAGEMENT ===
+    // ================================
+    
+    pub fn set_base_url(&self, url: String) {
+        self.set_base_urls(vec![url]);
+    }
+    
+    // Swaps the mirror list for loads that start afterwards; loads in flight keep theirs
+    pub fn set_base_urls(&self, urls: Vec<String>) {
+        self.update_loader(|loader| {
+            loader.mirrors = mirror_list(urls.iter().cloned());
+            loader.config.base_urls = urls;
+        });
+    }
+    
+    pub fn base_urls(&self) -> Arc<Vec<String>> {
+        self.loader().mirrors.clone()
+    }
+
+    // Rebuilds the HTTP client; fails, leaving the current one, if a header, proxy or
+    // certificate is malformed
+    pub fn set_http_config(&self, config: &HttpConfig) -> Result<(), String> {
+        let client = config.build_client()?;
+        self.update_loader(|loader| {
+            loader.client = client;
+            loader.config.http = config.clone();
+        });
+        Ok(())
+    }
+    
+    // Replace every loader setting at once
+    pub fn set_loader_config(&self, config: LoaderConfig) -> Result<(), String> {
+        let max_connections = config.max_connections_per_host;
+        *self.loader.write().unwrap() = Arc::new(Loader::new(config)?);
+        self.set_max_connections_per_host(max_connections);
+        Ok(())
+    }
+    
+    pub fn loader_config(&self) -> LoaderConfig {
+        let mut config = self.loader().config.clone();
+        config.max_connections_per_host = self.max_connections_per_host();
+        config
+    }
+    
+    pub fn set_max_attempts(&self, attempts: u32) {
+        self.update_loader(|loader| loader.config.max_attempts = attempts.max(1));
+    }
+    
+    fn loader(&self) -> Arc<Loader> {
+        self.loader.read().unwrap().clone()
+    }
+    
+    // Read-modify-write under the write lock, so concurrent setters don't undo each other
+    fn update_loader(&self, update: impl FnOnce(&mut Loader)) {
+        let mut current = self.loader.write().unwrap();
+        let mut next = Loader::clone(&current);
+        update(&mut next);
+        *current = Arc::new(next);
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_disk_cache(&mut self, cache: Option<DiskCache>) {
+        self.disk_cache = cache;
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn disk_cache(&self) -> Option<&DiskCache> {
+        self.disk_cache.as_ref()
+    }
+
+    // Credentials for every fetch that doesn't bring its own; None sends requests anonymously
+    pub fn set_auth(&self, auth: Option<AssetAuth>) {
+        *self.auth.write().unwrap() = auth;
+    }
+    
+    pub fn auth(&self) -> Option<AssetAuth> {
+        self.auth.read().unwrap().clone()
+    }
+    
+    // Called when a fetch using the global credentials is answered 401 or 403; the request is
+    // retried once with whatever it returns, which also replaces the global credentials
+    pub fn set_auth_refresh<F, Fut>(&self, refresh: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<AssetAuth, String>> + Send + 'static,
+    {
+        *self.auth_refresh.write().unwrap() = Some(Arc::new(move || Box::pin(refresh()) as AuthFuture));
+    }
+    
+    pub fn clear_auth_refresh(&self) {
+        *self.auth_refresh.write().unwrap() = None;
+    }
+    
+    pub fn add_middleware(&self, middleware: impl FetchMiddleware + 'static) {
+        self.middleware.write().unwrap().push(Arc::new(middleware));
+    }
+    
+    pub fn clear_middleware(&self) {
+        self.middleware.write().unwrap().clear();
+    }
+    
+    // Every fetched or source-loaded asset is treated as sealed with `key`
+    #[cfg(feature = "encryption")]
+    pub fn set_decryption_key(&self, key: AssetKey) {
+        self.set_key_provider(move |_| Some(key.clone()));
+    }
+    
+    // Chooses a key per asset path; assets it returns None for are loaded as they are
+    #[cfg(feature = "encryption")]
+    pub fn set_key_provider(&self, provider: impl Fn(&str) -> Option<AssetKey> + Send + Sync + 'static) {
+        *self.keys.write().unwrap() = Some(Arc::new(provider));
+    }
+    
+    #[cfg(feature = "encryption")]
+    pub fn clear_decryption_keys(&self) {
+        *self.keys.write().unwrap() = None;
+    }
+    
+    // Decrypts a sealed asset bundled with the application straight into `tier`
+    #[cfg(feature = "encryption")]
+    pub fn load_encrypted(&self, sealed: &[u8], key: &AssetKey, tier: Tier) -> Result<(MemoryHandle, usize), String> {
+        self.store_sealed("bundled asset", key, sealed, tier).map_err(|e| e.to_string())
+    }
+    
+    // Caps download bandwidth across all loads; 0 removes the limit. Loads made with
+    // LoadOptions::unthrottled ignore it.
+    pub fn set_bandwidth_limit(&self, bytes_per_sec: u64) {
+        self.fetch_stats.throttle.bytes_per_sec.store(bytes_per_sec, Ordering::Relaxed);
+    }
+    
+    pub fn bandwidth_limit(&self) -> u64 {
+        self.fetch_stats.throttle.bytes_per_sec.load(Ordering::Relaxed)
+    }
+    
+    // Requests in flight per host and port; further loads to that host wait for a slot,
+    // which is held until the body has been read. 0 removes the limit.
+    pub fn set_max_connections_per_host(&self, max: usize) {
+        self.fetch_stats.throttle.max_per_host.store(max, Ordering::Relaxed);
+        self.fetch_stats.throttle.wake_waiters();
+    }
+    
+    pub fn max_connections_per_host(&self) -> usize {
+        self.fetch_stats.throttle.max_per_host.load(Ordering::Relaxed)
+    }
+    
+    // Remember failed fetches for `cooldown` and fail repeat loads of the same path without a
+    // request until it passes. Zero, the default, disables the cache and forgets every entry.
+    pub fn set_failure_cooldown(&self, cooldown: Duration) {
+        let failures = &self.fetch_stats.failures;
+        failures.cooldown_ms.store(cooldown.as_millis() as u64, Ordering::Relaxed);
+        if cooldown.is_zero() {
+            failures.entries.lock().unwrap().clear();
+        }
+    }
+    
+    // Paths still cooling down, by path
+    pub fn failed_fetches(&self) -> Vec<FailedFetch> {
+        self.fetch_stats.failures.list()
+    }
+    
+    // Allows an immediate retry, e.g. after the asset has been uploaded
+    pub fn forget_failed_fetch(&self, path: &str) -> bool {
+        self.fetch_stats.failures.entries.lock().unwrap().remove(path).is_some()
+    }
+    
+    // Runs the refresh hook now, e.g. ahead of a known expiry. Ok(false) if none is set.
+    pub async fn refresh_auth(&self) -> Result<bool, String> {
+        self.try_refresh_auth().await.map_err(|e| e.to_string())
+    }
+    
+    async fn try_refresh_auth(&self) -> Result<bool, LoadError> {
+        let refresh = self.auth_refresh.read().unwrap().clone();
+        let Some(refresh) = refresh else {
+            return Ok(false);
+        };
+        let auth = refresh().await.map_err(LoadError::Auth)?;
+        walloc_event!(debug, "auth refreshed");
+        self.set_auth(Some(auth));
+        Ok(true)
+    }
+
+    // Past the tier's registry cap, victims chosen by the eviction policy are freed
+    pub fn register_asset(&self, key: String, metadata: AssetMetadata) -> bool {
+        let tier = metadata.tier;
+        let (inserted, victims) = self.assets.insert_capped(key, metadata);
+        if !victims.is_empty() {
+            let arena = &self.arenas[tier as usize];
+            for (_key, victim) in &victims {
+                let _ = arena.deallocate(victim.handle, victim.size);
+                arena.counters.evictions.fetch_add(1, Ordering::Relaxed);
+                walloc_event!(debug, ?tier, victim = %_key, size = victim.size, "evicted over registry cap");
+            }
+            self.check_compaction_policy(tier);
+        }
+        inserted
+    }
+    
+    pub fn set_eviction_policy(&self, policy: impl EvictionPolicy + 'static) {
+        self.assets.set_policy(Arc::new(policy));
+    }
+    
+    // Cap the registered asset bytes of a tier; registering past it evicts. 0 removes the cap.
+    pub fn set_registry_cap(&self, tier: Tier, bytes: usize) {
+        self.assets.set_cap(tier, bytes);
+    }
+    
+    // Another name for the same in-memory copy, e.g. a localized path or legacy ID
+    pub fn alias_asset(&self, existing_key: &str, alias: &str) -> bool {
+        self.assets.alias(existing_key, alias.to_string())
+    }
+    
+    pub fn rename_asset(&self, old: &str, new: &str) -> bool {
+        self.assets.rename(old, new.to_string())
+    }
+
+    // Enhanced: Evict asset with automatic compaction on supported platforms
+    pub fn evict_asset(&self, path: &str) -> bool {
+        let metadata_opt = self.assets.get(path);
+        
+        if let Some(metadata) = metadata_opt {
+            let handle = metadata.handle;
+            let size = metadata.size;
+            let tier = metadata.tier;
+            
+            if handle.is_null() || tier as usize >= self.arenas.len() {
+                return self.assets.remove_unpinned(path);
+            }
+            
+            // On WASM, always compact to reduce fragmentation
+            #[cfg(target_arch = "wasm32")]
+            {
+                let removed = self.assets.remove_unpinned(path);
+                
+                if removed {
+                    let _ = self.arenas[tier as usize].deallocate(handle, size);
+                    self.arenas[tier as usize].counters.evictions.fetch_add(1, Ordering::Relaxed);
                     
-                    // Allocate space for preserved data
-                    if let Some(new_handle) = self.allocate(preserve_buffer.len(), tier) {
-                        // Copy preserved data back
-                        unsafe {
-                            SIMDOps::fast_copy(
-                                preserve_buffer.as_ptr(),
-                                new_handle.to_ptr(),
-                                preserve_buffer.len()
-                            );
-                        }
-                        
-                        // Update asset registry with new offsets
-                        for (asset_path, offset_in_buffer, mut asset_meta) in new_offsets {
-                            let new_global_offset = new_handle.offset() + offset_in_buffer;
-                            asset_meta.handle = MemoryHandle(new_global_offset);
-                            asset_meta.offset = new_global_offset;
-                            self.assets.insert(asset_path, asset_meta);
-                        }
+                    // Slide the survivors down over the hole. In deferred mode the relocation
+                    // is left to compact_step so the eviction itself stays cheap.
+                    if self.begin_compaction(tier) && !self.deferred_compaction.load(Ordering::Relaxed) {
+                        self.finish_compaction(tier);
+                    }
+                }
+                
+                return removed;
+            }
+            
+            // On native platforms, just deallocate without compaction
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let removed = self.assets.remove_unpinned(path);
+                
+                if removed {
+                    let arena = &self.arenas[tier as usize];
+                    let _ = arena.deallocate(handle, size);
+                    arena.counters.evictions.fetch_add(1, Ordering::Relaxed);
+                    self.check_compaction_policy(tier);
+                }
+                
+                return removed;
+            }
+        }
+        
+        false
+    }
+    
+    pub fn evict_assets_batch(&self, paths: &[String]) -> usize {
+        #[cfg(target_arch = "wasm32")]
+        {
+            // On WASM, evict each asset with compaction
+            let mut evicted = 0;
+            for path in paths {
+                if self.evict_asset(path) {
+                    evicted += 1;
+                }
+            }
+            evicted
+        }
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            // On native, batch process without compaction for efficiency
+            let mut evicted = 0;
+            
+            let mut to_evict = Vec::with_capacity(paths.len());
+            
+            for path in paths {
+                if let Some(metadata) = self.assets.get(path) {
+                    to_evict.push((path.clone(), metadata.handle, metadata.size, metadata.tier));
+                }
+            }
+            
+            let mut touched = [false; 3];
+            
+            for (path, handle, size, tier) in to_evict {
+                if handle.is_null() || tier as usize >= self.arenas.len() {
+                    if self.assets.remove_unpinned(&path) {
+                        evicted += 1;
+                    }
+                    continue;
+                }
+                
+                if self.assets.remove_unpinned(&path) {
+                    let arena = &self.arenas[tier as usize];
+                    let _ = arena.deallocate(handle, size);
+                    arena.counters.evictions.fetch_add(1, Ordering::Relaxed);
+                    touched[tier as usize] = true;
+                    evicted += 1;
+                }
+            }
+            
+            for tier in [Tier::Top, Tier::Middle, Tier::Bottom] {
+                if touched[tier as usize] {
+                    self.check_compaction_policy(tier);
+                }
+            }
+            
+            evicted
+        }
+    }
+    
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "walloc.load_asset", level = "debug", skip(self)))]
+    pub async fn load_asset_unified(&self, path: String, asset_type: AssetType) -> Result<MemoryHandle, String> {
+        self.load_asset_with(path, asset_type, &LoadOptions::default()).await
+    }
+    
+    // Fetches with `auth` instead of the global credentials; the refresh hook is not consulted
+    pub async fn load_asset_with_auth(&self, path: String, asset_type: AssetType, auth: &AssetAuth) -> Result<MemoryHandle, String> {
+        self.load_asset_with(path, asset_type, &LoadOptions::new().with_auth(auth.clone())).await
+    }
+    
+    pub async fn load_asset_with(&self, path: String, asset_type: AssetType, options: &LoadOptions) -> Result<MemoryHandle, String> {
+        self.load_with_options(path, asset_type, options, None).await
+            .map(|(handle, _)| handle)
+            .map_err(|e| e.to_string())
+    }
+    
+    // The registered handle while it is fresh, otherwise whatever load_asset_unified returns.
+    // A stale asset is revalidated first and kept on 304; if revalidation fails the stale
+    // copy is still served, unless the server asked for must-revalidate.
+    pub async fn get_or_load(&self, path: &str, asset_type: AssetType) -> Result<MemoryHandle, String> {
+        let Some(current) = self.assets.get(path).filter(|asset| !asset.handle.is_null()) else {
+            return self.load_asset_unified(path.to_string(), asset_type).await;
+        };
+        self.assets.touch(path);
+        if !current.is_stale() {
+            return Ok(current.handle);
+        }
+        
+        match self.load_with_options(path.to_string(), asset_type, &LoadOptions::default(), Some(&current)).await {
+            Ok((handle, _)) => {
+                if handle != current.handle {
+                    let _ = self.deallocate(current.handle, current.size, current.tier);
+                }
+                Ok(handle)
+            }
+            Err(error) if current.freshness.as_ref().is_some_and(|freshness| freshness.must_revalidate) => Err(error.to_string()),
+            Err(_error) => {
+                walloc_event!(warn, error = %_error, "serving stale asset");
+                Ok(current.handle)
+            }
+        }
+    }
+    
+    // `current` is the registered copy being revalidated, if any
+    async fn load_with_options(&self, path: String, asset_type: AssetType, options: &LoadOptions, current: Option<&AssetMetadata>) -> Result<(MemoryHandle, usize), LoadError> {
+        let started = clock_ms();
+        walloc_event!(debug, "asset load started");
+        
+        let mut timing = LoadTiming {
+            path: path.clone(),
+            source: None,
+            status: None,
+            bytes: 0,
+            attempts: 0,
+            queued_ms: 0.0,
+            ttfb_ms: None,
+            total_ms: 0.0,
+        };
+        let result = self.fetch_asset(path, asset_type, options, current, &mut timing).await;
+        self.fetch_stats.record(&result);
+        match &result {
+            Ok((_, size)) => timing.bytes = *size,
+            Err(_) => timing.source = None,
+        }
+        timing.total_ms = clock_ms() - started;
+        self.fetch_stats.timings.record(timing);
+        
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok((handle, size)) => walloc_event!(
+                debug,
+                size,
+                offset = handle.offset(),
+                elapsed_ms = clock_ms() - started,
+                "asset load finished"
+            ),
+            Err(error) => walloc_event!(
+                warn,
+                error = %error,
+                elapsed_ms = clock_ms() - started,
+                "asset load failed"
+            ),
+        }
+        
+        result
+    }
+    
+    async fn fetch_asset(&self, path: String, asset_type: AssetType, options: &LoadOptions, current: Option<&AssetMetadata>, timing: &mut LoadTiming) -> Result<(MemoryHandle, usize), LoadError> {
+        let mirrors = match &options.base_urls {
+            Some(urls) => mirror_list(urls.iter().cloned()),
+            None => self.base_urls(),
+        };
+        let urls: Vec<String> = match mirrors.is_empty() {
+            true => vec![path.clone()],
+            false => mirrors.iter().map(|base| format!("{}{}", base, path)).collect(),
+        };
+        let full_url = &urls[0];  // Disk cache key, whichever mirror ends up serving it
+        
+        if let Some(error) = self.fetch_stats.failures.check(&path) {
+            return Err(error);
+        }
+        
+        // When revalidating, the in-arena copy plays the part of the disk cache
+        let cached = match current {
+            Some(_) => None,
+            None => self.disk_lookup(full_url),
+        };
+        if let Some(cached) = cached.as_ref().filter(|cached| cached.fresh) {
+            self.fetch_stats.disk_hits.fetch_add(1, Ordering::Relaxed);
+            timing.source = Some(LoadSource::DiskCache);
+            return self.register_fetched(path, asset_type, &cached.body, None);
+        }
+        
+        let previous = current.and_then(|current| current.freshness.as_ref());
+        let validators = match previous {
+            Some(previous) => validator_headers(previous.etag.as_deref(), previous.last_modified.as_deref()),
+            None => cached.as_ref().map(CachedResponse::validators).unwrap_or_default(),
+        };
+        let mut mirror = 0;
+        let (request, response, _permit) = loop {
+            let waited = clock_ms();
+            let permit = self.fetch_stats.throttle.acquire_host(&urls[mirror]).await;
+            let sent = clock_ms();
+            timing.queued_ms += sent - waited;
+            let (request, response) = self.send_with_middleware(&path, &urls[mirror], &validators, options.auth.as_ref()).await?;
+            timing.attempts += request.attempt + 1;
+            timing.status = response.as_ref().ok().map(|response| response.status().as_u16());
+            timing.ttfb_ms = response.is_ok().then(|| clock_ms() - sent);
+            mirror += 1;
+            if mirror == urls.len() || !should_fail_over(&response) {
+                break (request, response, permit);
+            }
+            walloc_event!(info, url = %request.url, "failing over to the next mirror");
+        };
+        
+        let not_modified = !validators.is_empty() && response.as_ref().is_ok_and(|response| response.status() == reqwest::StatusCode::NOT_MODIFIED);
+        let response = response
+            .and_then(|response| match response.status().is_success() || not_modified {
+                true => Ok(response),
+                false => Err(LoadError::Status { status: response.status().as_u16(), url: request.url }),
+            })
+            .inspect_err(|error| self.fetch_stats.failures.record(&path, error))?;
+        self.fetch_stats.failures.forget(&path);
+        
+        timing.source = Some(if not_modified { LoadSource::Revalidated } else { LoadSource::Network });
+        let mut freshness = Freshness::from_headers(response.headers());
+        if not_modified && let (Some(current), Some(previous)) = (current, previous) {
+            freshness.etag = freshness.etag.or_else(|| previous.etag.clone());
+            freshness.last_modified = freshness.last_modified.or_else(|| previous.last_modified.clone());
+            self.assets.set_freshness(&path, Some(freshness));
+            return Ok((current.handle, current.size));
+        }
+        if not_modified && let Some(cached) = cached {
+            // A 304 may omit the validators; the stored ones still identify the body
+            freshness.etag = freshness.etag.or(cached.etag);
+            freshness.last_modified = freshness.last_modified.or(cached.last_modified);
+            self.fetch_stats.disk_hits.fetch_add(1, Ordering::Relaxed);
+            return self.register_fetched(path, asset_type, &cached.body, Some(freshness));
+        }
+        
+        let content_length = response.content_length().unwrap_or(0) as usize;
+        
+        // Sealed bodies are smaller than their Content-Length once decrypted
+        if content_length > 1024 * 1024 && !self.is_sealed(&path) {
+            let handle = self.allocate(content_length, Tier::Middle)
+                .ok_or(LoadError::OutOfMemory { size: content_length })?;
+            
+            let bytes = self.read_body(response, options.unthrottled).await?;
+            self.disk_store(full_url, &freshness, &bytes);
+            
+            unsafe {
+                SIMDOps::fast_copy(bytes.as_ptr(), handle.resolve(self.handle_base), bytes.len());
+            }
+            
+            self.register_asset(path, AssetMetadata {
+                asset_type,
+                size: bytes.len(),
+                offset: handle.offset(),
+                tier: Tier::Middle,
+                handle,
+                tag: None,
+                priority: 0,
+                pinned: false,
+                freshness: Some(freshness),
+            });
+            
+            Ok((handle, bytes.len()))
+        } else {
+            let bytes = self.read_body(response, options.unthrottled).await?;
+            self.disk_store(full_url, &freshness, &bytes);
+            self.register_fetched(path, asset_type, &bytes, Some(freshness))
+        }
+    }
+    
+    fn register_fetched(&self, path: String, asset_type: AssetType, bytes: &[u8], freshness: Option<Freshness>) -> Result<(MemoryHandle, usize), LoadError> {
+        let (handle, size) = self.store_body(&path, bytes, Tier::Middle)?;
+        
+        self.register_asset(path, AssetMetadata {
+            asset_type,
+            size,
+            offset: handle.offset(),
+            tier: Tier::Middle,
+            handle,
+            tag: None,
+            priority: 0,
+            pinned: false,
+            freshness,
+        });
+        
+        Ok((handle, size))
+    }
+    
+    // Chunks count against the bandwidth limit as they arrive. WASM responses can't be read
+    // incrementally, so there the whole body is charged afterwards and delays the next read.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn read_body(&self, mut response: reqwest::Response, unthrottled: bool) -> Result<bytes::Bytes, LoadError> {
+        let throttle = &self.fetch_stats.throttle;
+        if unthrottled || throttle.bytes_per_sec.load(Ordering::Relaxed) == 0 {
+            return response.bytes().await.map_err(|e| LoadError::Body(e.to_string()));
+        }
+        
+        let mut body = Vec::with_capacity(response.content_length().unwrap_or(0).min(1 << 26) as usize);
+        while let Some(chunk) = response.chunk().await.map_err(|e| LoadError::Body(e.to_string()))? {
+            throttle.consume(chunk.len()).await;
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body.into())
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    async fn read_body(&self, response: reqwest::Response, unthrottled: bool) -> Result<bytes::Bytes, LoadError> {
+        let bytes = response.bytes().await.map_err(|e| LoadError::Body(e.to_string()))?;
+        if !unthrottled {
+            self.fetch_stats.throttle.consume(bytes.len()).await;
+        }
+        Ok(bytes)
+    }
+    
+    // Copies a body into `tier`, decrypting it in place when `path` has a key
+    fn store_body(&self, path: &str, bytes: &[u8], tier: Tier) -> Result<(MemoryHandle, usize), LoadError> {
+        #[cfg(feature = "encryption")]
+        if let Some(key) = self.asset_key(path) {
+            return self.store_sealed(path, &key, bytes, tier);
+        }
+        let _ = path;
+        
+        let handle = self.allocate(bytes.len(), tier)
+            .ok_or(LoadError::OutOfMemory { size: bytes.len() })?;
+        unsafe {
+            SIMDOps::fast_copy(bytes.as_ptr(), handle.resolve(self.handle_base), bytes.len());
+        }
+        Ok((handle, bytes.len()))
+    }
+    
+    #[cfg(feature = "encryption")]
+    fn store_sealed(&self, path: &str, key: &AssetKey, sealed: &[u8], tier: Tier) -> Result<(MemoryHandle, usize), LoadError> {
+        let size = sealed.len().checked_sub(SEALED_NONCE_LEN + SEALED_TAG_LEN)
+            .ok_or_else(|| LoadError::Decrypt { path: path.to_string(), reason: "Too short to be sealed" })?;
+        let handle = self.allocate(size, tier)
+            .ok_or(LoadError::OutOfMemory { size })?;
+        unsafe {
+            SIMDOps::fast_copy(sealed[SEALED_NONCE_LEN..].as_ptr(), handle.resolve(self.handle_base), size);
+        }
+        
+        let mut trailer = [0u8; SEALED_NONCE_LEN + SEALED_TAG_LEN];
+        trailer[..SEALED_NONCE_LEN].copy_from_slice(&sealed[..SEALED_NONCE_LEN]);
+        trailer[SEALED_NONCE_LEN..].copy_from_slice(&sealed[SEALED_NONCE_LEN + size..]);
+        self.open_sealed(path, key, handle, size, tier, &trailer)?;
+        Ok((handle, size))
+    }
+    
+    // Decrypts `size` bytes at `handle` in place; on failure they are zeroed and freed
+    #[cfg(feature = "encryption")]
+    fn open_sealed(&self, path: &str, key: &AssetKey, handle: MemoryHandle, size: usize, tier: Tier, trailer: &[u8]) -> Result<(), LoadError> {
+        let result = self.with_slice(handle, size, |ciphertext| {
+            let result = key.open_in_place(trailer, ciphertext);
+            if result.is_err() {
+                ciphertext.fill(0);
+            }
+            result
+        });
+        match result {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(reason)) => {
+                let _ = self.deallocate(handle, size, tier);
+                Err(LoadError::Decrypt { path: path.to_string(), reason })
+            }
+            Err(error) => {
+                let _ = self.deallocate(handle, size, tier);
+                Err(LoadError::Body(error.to_string()))
+            }
+        }
+    }
+    
+    #[cfg(feature = "encryption")]
+    fn asset_key(&self, path: &str) -> Option<AssetKey> {
+        self.keys.read().unwrap().as_ref().and_then(|provider| provider(path))
+    }
+    
+    #[cfg(feature = "encryption")]
+    fn is_sealed(&self, path: &str) -> bool {
+        self.asset_key(path).is_some()
+    }
+    
+    #[cfg(not(feature = "encryption"))]
+    fn is_sealed(&self, _path: &str) -> bool {
+        false
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    fn disk_lookup(&self, url: &str) -> Option<CachedResponse> {
+        self.disk_cache.as_ref()?.lookup(url)
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    fn disk_lookup(&self, _url: &str) -> Option<CachedResponse> {
+        None
+    }
+    
+    // A cache that can't be written only costs the next run a download
+    #[cfg(not(target_arch = "wasm32"))]
+    fn disk_store(&self, url: &str, freshness: &Freshness, body: &[u8]) {
+        if freshness.no_store {
+            return;
+        }
+        if let Some(cache) = &self.disk_cache && let Err(_error) = cache.store(url, freshness.etag.as_deref(), freshness.last_modified.as_deref(), body) {
+            walloc_event!(warn, error = %_error, "disk cache write failed");
+        }
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    fn disk_store(&self, _url: &str, _freshness: &Freshness, _body: &[u8]) {}
+
+    // One mirror's attempts, as many as middleware asks for; returns the last request sent
+    async fn send_with_middleware(&self, path: &str, url: &str, validators: &[(String, String)], auth: Option<&AssetAuth>) -> Result<(FetchRequest, Result<reqwest::Response, LoadError>), LoadError> {
+        let middleware = self.middleware.read().unwrap().clone();
+        let max_attempts = self.loader().config.max_attempts;
+        let mut attempt = 0;
+        loop {
+            let mut request = FetchRequest { path: path.to_string(), url: url.to_string(), headers: validators.to_vec(), attempt };
+            for layer in &middleware {
+                layer.on_request(&mut request).await.map_err(LoadError::Rejected)?;
+            }
+            
+            let result = self.send_authorized(&request, auth, None).await;
+            let observed = result.as_ref().map(FetchResponse::new).map_err(ToString::to_string);
+            let mut retry = false;
+            for layer in &middleware {
+                retry |= layer.on_response(&request, &observed).await == MiddlewareAction::Retry;
+            }
+            
+            attempt += 1;
+            if !retry || attempt >= max_attempts {
+                return Ok((request, result));
+            }
+            walloc_event!(debug, attempt, "fetch retried by middleware");
+        }
+    }
+    
+    // Explicit credentials are sent as given; the global ones get one refresh on 401/403
+    async fn send_authorized(&self, request: &FetchRequest, auth: Option<&AssetAuth>, upload: Option<(UploadMethod, &[u8])>) -> Result<reqwest::Response, LoadError> {
+        if auth.is_some() {
+            return self.send(request, auth, upload).await;
+        }
+        
+        let response = self.send(request, self.auth().as_ref(), upload).await?;
+        let rejected = matches!(response.status(), reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN);
+        if rejected && self.try_refresh_auth().await? {
+            self.send(request, self.auth().as_ref(), upload).await
+        } else {
+            Ok(response)
+        }
+    }
+    
+    // A GET, or the upload's PUT/POST with `body` as application/octet-stream
+    async fn send(&self, request: &FetchRequest, auth: Option<&AssetAuth>, upload: Option<(UploadMethod, &[u8])>) -> Result<reqwest::Response, LoadError> {
+        let client = self.loader().client.clone();
+        let mut builder = match upload {
+            None => client.get(&request.url),
+            Some((method, body)) => {
+                let builder = match method {
+                    UploadMethod::Put => client.put(&request.url),
+                    UploadMethod::Post => client.post(&request.url),
+                };
+                builder.header(reqwest::header::CONTENT_TYPE, "application/octet-stream").body(body.to_vec())
+            }
+        };
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        if let Some(auth) = auth {
+            builder = auth.apply(builder);
+        }
+        builder.send().await.map_err(|e| LoadError::Request(format!("Failed to fetch '{}': {}", request.url, e)))
+    }
+
+    pub async fn load_asset(&self, path: String, asset_type: AssetType) -> Result<MemoryHandle, String> {
+        self.load_asset_unified(path, asset_type).await
+    }
+    
+    // PUT `len` bytes at `handle` to base_url + path, returning the response status
+    pub async fn store_asset(&self, path: String, handle: MemoryHandle, len: usize) -> Result<u16, String> {
+        self.store_asset_with(path, handle, len, UploadMethod::Put, &LoadOptions::default()).await
+    }
+    
+    // Uploads go to the first mirror only, with the same credentials and refresh as fetches;
+    // middleware is not run. The bytes are copied once into the request body, since
+    // streaming from the arena would need reqwest's `stream` feature.
+    pub async fn store_asset_with(&self, path: String, handle: MemoryHandle, len: usize, method: UploadMethod, options: &LoadOptions) -> Result<u16, String> {
+        let body = self.with_slice_ref(handle, len, |bytes| bytes.to_vec()).map_err(|e| e.to_string())?;
+        let mirrors = match &options.base_urls {
+            Some(urls) => mirror_list(urls.iter().cloned()),
+            None => self.base_urls(),
+        };
+        let url = match mirrors.first() {
+            Some(base) => format!("{}{}", base, path),
+            None => path.clone(),
+        };
+        
+        let request = FetchRequest { path, url, headers: Vec::new(), attempt: 0 };
+        let response = self.send_authorized(&request, options.auth.as_ref(), Some((method, &body))).await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        walloc_event!(debug, status = status.as_u16(), bytes = len, "asset stored");
+        if !status.is_success() {
+            return Err(LoadError::Status { status: status.as_u16(), url: request.url }.to_string());
+        }
+        Ok(status.as_u16())
+    }
+    
+    // Applies one frame of an asset stream; Some once an End completes and registers an asset.
+    // A failed frame drops its asset's pending handle, so the next Begin starts over.
+    pub fn ingest_frame(&self, frame: StreamFrame) -> Result<Option<StreamedAsset>, String> {
+        let mut streams = self.streams.lock().unwrap();
+        match frame {
+            StreamFrame::Begin { path, asset_type, size } => {
+                if let Some(previous) = streams.remove(&path) {
+                    let _ = self.deallocate(previous.handle, previous.size, Tier::Middle);
+                }
+                let handle = self.allocate(size, Tier::Middle)
+                    .ok_or_else(|| LoadError::OutOfMemory { size }.to_string())?;
+                streams.insert(path, PendingStream { asset_type, handle, size, written: 0 });
+                Ok(None)
+            }
+            StreamFrame::Data { path, bytes } => {
+                let pending = streams.get_mut(&path)
+                    .ok_or_else(|| format!("No stream in progress for '{}'", path))?;
+                let written = pending.written;
+                let result = match written + bytes.len() <= pending.size {
+                    true => self.with_slice(pending.handle.advance(written), bytes.len(), |slice| slice.copy_from_slice(&bytes))
+                        .map_err(|e| e.to_string()),
+                    false => Err(format!("Stream for '{}' overran its {} bytes", path, pending.size)),
+                };
+                match result {
+                    Ok(()) => {
+                        pending.written += bytes.len();
+                        Ok(None)
+                    }
+                    Err(error) => {
+                        let pending = streams.remove(&path).unwrap();
+                        let _ = self.deallocate(pending.handle, pending.size, Tier::Middle);
+                        Err(error)
+                    }
+                }
+            }
+            StreamFrame::End { path } => {
+                let pending = streams.remove(&path)
+                    .ok_or_else(|| format!("No stream in progress for '{}'", path))?;
+                if pending.written != pending.size {
+                    let _ = self.deallocate(pending.handle, pending.size, Tier::Middle);
+                    return Err(format!("Stream for '{}' ended at {} of {} bytes", path, pending.written, pending.size));
+                }
+                // The previous copy goes the way of any eviction, which an AssetGuard holding it
+                // refuses; the stream then stays pending so End can be sent again later
+                if self.assets.get(&path).is_some() && !self.evict_asset(&path) {
+                    streams.insert(path.clone(), pending);
+                    return Err(format!("Previous copy of '{}' is in use", path));
+                }
+                drop(streams);
+                
+                self.register_asset(path.clone(), AssetMetadata::new(pending.asset_type, pending.handle, pending.size, Tier::Middle));
+                walloc_event!(debug, size = pending.size, "streamed asset registered");
+                Ok(Some(StreamedAsset { path, asset_type: pending.asset_type, handle: pending.handle, size: pending.size }))
+            }
+            StreamFrame::Abort { path } => {
+                if let Some(pending) = streams.remove(&path) {
+                    let _ = self.deallocate(pending.handle, pending.size, Tier::Middle);
+                }
+                Ok(None)
+            }
+        }
+    }
+    
+    pub fn abort_stream(&self, path: &str) -> bool {
+        let pending = self.streams.lock().unwrap().remove(path);
+        if let Some(pending) = &pending {
+            let _ = self.deallocate(pending.handle, pending.size, Tier::Middle);
+        }
+        pending.is_some()
+    }
+    
+    // Paths with a Begin but no End yet
+    pub fn pending_streams(&self) -> Vec<String> {
+        self.streams.lock().unwrap().keys().cloned().collect()
+    }
+    
+    // Follows the server-sent event stream at base_url + path until the server closes it,
+    // calling `on_asset` as each asset completes. Returns how many assets completed; assets
+    // this stream left unfinished are aborted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn stream_assets(&self, path: String, mut on_asset: impl FnMut(&StreamedAsset)) -> Result<usize, String> {
+        let urls: Vec<String> = match self.base_urls() {
+            mirrors if mirrors.is_empty() => vec![path.clone()],
+            mirrors => mirrors.iter().map(|base| format!("{}{}", base, path)).collect(),
+        };
+        let headers = [("Accept".to_string(), "text/event-stream".to_string())];
+        let mut mirror = 0;
+        let (request, response) = loop {
+            let sent = self.send_with_middleware(&path, &urls[mirror], &headers, None).await
+                .map_err(|e| e.to_string())?;
+            mirror += 1;
+            if mirror == urls.len() || !should_fail_over(&sent.1) {
+                break sent;
+            }
+        };
+        let mut response = response.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(LoadError::Status { status: response.status().as_u16(), url: request.url }.to_string());
+        }
+        
+        let mut begun = Vec::new();
+        let mut line = Vec::new();
+        let mut data = String::new();
+        let mut completed = 0;
+        let result = 'read: loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break Ok(completed),
+                Err(e) => break Err(LoadError::Body(e.to_string()).to_string()),
+            };
+            for &byte in chunk.iter() {
+                if byte != b'\n' {
+                    line.push(byte);
+                    continue;
+                }
+                let text = String::from_utf8_lossy(&line).trim_end_matches('\r').to_string();
+                line.clear();
+                
+                // Only data lines matter; a blank line ends the event
+                if let Some(value) = text.strip_prefix("data:") {
+                    data.push_str(value.strip_prefix(' ').unwrap_or(value));
+                    continue;
+                }
+                if !text.is_empty() || data.is_empty() {
+                    continue;
+                }
+                let frame = decode_base64(&data)
+                    .ok_or("Stream event is not base64")
+                    .and_then(|bytes| StreamFrame::decode(&bytes));
+                data.clear();
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(error) => break 'read Err(error.to_string()),
+                };
+                match self.ingest_streamed(frame, &mut begun) {
+                    Ok(Some(asset)) => {
+                        completed += 1;
+                        on_asset(&asset);
                     }
+                    Ok(None) => {}
+                    Err(error) => break 'read Err(error),
+                }
+            }
+        };
+        
+        self.abort_begun(&begun);
+        result
+    }
+    
+    // Follows the WebSocket at `url` (ws:// or wss://) until either side closes it, taking
+    // each binary message as one frame. Otherwise the same as stream_assets.
+    #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+    pub async fn stream_assets_ws(&self, url: &str, mut on_asset: impl FnMut(&StreamedAsset)) -> Result<usize, String> {
+        use futures::StreamExt;
+        use tokio_tungstenite::tungstenite::Message;
+        
+        let (mut socket, _) = tokio_tungstenite::connect_async(url).await.map_err(|e| e.to_string())?;
+        let mut begun = Vec::new();
+        let mut completed = 0;
+        let result = loop {
+            // Pings are answered by the socket itself as it is polled
+            let bytes = match socket.next().await {
+                Some(Ok(Message::Binary(bytes))) => bytes,
+                Some(Ok(Message::Close(_))) | None => break Ok(completed),
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => break Err(e.to_string()),
+            };
+            let frame = match StreamFrame::decode(&bytes) {
+                Ok(frame) => frame,
+                Err(error) => break Err(error.to_string()),
+            };
+            match self.ingest_streamed(frame, &mut begun) {
+                Ok(Some(asset)) => {
+                    completed += 1;
+                    on_asset(&asset);
+                }
+                Ok(None) => {}
+                Err(error) => break Err(error),
+            }
+        };
+        
+        self.abort_begun(&begun);
+        result
+    }
+    
+    // Remembers which paths a stream began, so abort_begun can drop the ones it left open
+    #[cfg(not(target_arch = "wasm32"))]
+    fn ingest_streamed(&self, frame: StreamFrame, begun: &mut Vec<String>) -> Result<Option<StreamedAsset>, String> {
+        if matches!(frame, StreamFrame::Begin { .. }) {
+            begun.push(frame.path().to_string());
+        }
+        self.ingest_frame(frame)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    fn abort_begun(&self, begun: &[String]) {
+        let pending = self.pending_streams();
+        for path in begun.iter().filter(|path| pending.contains(path)) {
+            self.abort_stream(path);
+        }
+    }
+    
+    // Loads `path` through `source` into Middle and registers it. The first part's response
+    // tells the object's size; any remaining parts are fetched PARALLEL_LOAD_FACTOR at a time.
+    // Base URLs, credentials and middleware don't apply, the source builds every request.
+    pub async fn load_from_source(&self, source: &dyn AssetSource, path: String, asset_type: AssetType) -> Result<MemoryHandle, String> {
+        let started = clock_ms();
+        let result = self.fetch_from_source(source, &path).await;
+        self.fetch_stats.record(&result);
+        self.fetch_stats.timings.record(LoadTiming {
+            path: path.clone(),
+            source: result.is_ok().then_some(LoadSource::Network),
+            status: None,
+            bytes: result.as_ref().map_or(0, |(_, size)| *size),
+            attempts: 1,
+            queued_ms: 0.0,
+            ttfb_ms: None,
+            total_ms: clock_ms() - started,
+        });
+        let (handle, size) = result.map_err(|e| e.to_string())?;
+        self.register_asset(path, AssetMetadata::new(asset_type, handle, size, Tier::Middle));
+        Ok(handle)
+    }
+    
+    async fn fetch_from_source(&self, source: &dyn AssetSource, path: &str) -> Result<(MemoryHandle, usize), LoadError> {
+        let part_size = source.part_size().max(1);
+        let client = self.loader().client.clone();
+        let client = &client;
+        let send_part = |range: (usize, usize)| async move {
+            let request = source.request(client, path, Some(range))?
+                .build()
+                .map_err(|e| LoadError::Request(format!("Failed to fetch '{}': {}", path, e)))?;
+            let permit = self.fetch_stats.throttle.acquire_host(request.url().as_str()).await;
+            let response = client.execute(request).await
+                .map_err(|e| LoadError::Request(format!("Failed to fetch '{}': {}", path, e)))?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(LoadError::Status { status: status.as_u16(), url: response.url().to_string() });
+            }
+            Ok((response, permit))
+        };
+        
+        let first = send_part((0, part_size - 1)).await?;
+        if first.0.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            // The whole object, ranges unsupported or ignored
+            let bytes = self.read_body(first.0, false).await?;
+            return self.store_body(path, &bytes, Tier::Middle);
+        }
+        
+        // A sealed object's nonce and tag are set aside; only the ciphertext goes to the handle
+        #[cfg(feature = "encryption")]
+        let key = self.asset_key(path);
+        #[cfg(feature = "encryption")]
+        let (head, tail) = key.as_ref().map_or((0, 0), |_| (SEALED_NONCE_LEN, SEALED_TAG_LEN));
+        #[cfg(not(feature = "encryption"))]
+        let (head, tail) = (0, 0);
+        
+        let total = content_range_total(&first.0)
+            .ok_or_else(|| LoadError::Body(format!("No Content-Range total for '{}'", path)))?;
+        let size = total.checked_sub(head + tail)
+            .ok_or_else(|| LoadError::Body(format!("'{}' is too short to be sealed", path)))?;
+        let handle = self.allocate(size, Tier::Middle)
+            .ok_or(LoadError::OutOfMemory { size })?;
+        let trailer = Mutex::new([0u8; SEALED_NONCE_LEN + SEALED_TAG_LEN]);
+        let trailer = &trailer;
+        let copy_part = |start: usize, (response, _permit): (reqwest::Response, HostPermit<'_>)| async move {
+            let bytes = self.read_body(response, false).await?;
+            let expected = part_size.min(total - start);
+            if bytes.len() != expected {
+                return Err(LoadError::Body(format!("Part at {} of '{}' has {} of {} bytes", start, path, bytes.len(), expected)));
+            }
+            let end = start + bytes.len();
+            if start < head || end > total - tail {
+                let mut trailer = trailer.lock().unwrap();
+                for offset in (start..end.min(head)).chain(start.max(total - tail)..end) {
+                    let slot = if offset < head { offset } else { head + offset - (total - tail) };
+                    trailer[slot] = bytes[offset - start];
+                }
+            }
+            let (body_start, body_end) = (start.max(head), end.min(total - tail));
+            if body_start >= body_end {
+                return Ok(());
+            }
+            self.with_slice(handle.advance(body_start - head), body_end - body_start, |slice| {
+                slice.copy_from_slice(&bytes[body_start - start..body_end - start])
+            })
+            .map_err(|e| LoadError::Body(e.to_string()))
+        };
+        
+        let mut result = copy_part(0, first).await;
+        if result.is_ok() {
+            result = stream::iter((part_size..total).step_by(part_size))
+                .map(|start| async move {
+                    let response = send_part((start, (start + part_size).min(total) - 1)).await?;
+                    copy_part(start, response).await
+                })
+                .buffer_unordered(PARALLEL_LOAD_FACTOR)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect();
+        }
+        
+        if let Err(error) = result {
+            let _ = self.deallocate(handle, size, Tier::Middle);
+            return Err(error);
+        }
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &key {
+            self.open_sealed(path, key, handle, size, Tier::Middle, &*trailer.lock().unwrap())?;
+        }
+        walloc_event!(debug, size, parts = total.div_ceil(part_size), "asset loaded from source");
+        Ok((handle, size))
+    }
+    
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "walloc.load_batch", level = "debug", skip_all, fields(count = requests.len())))]
+    pub async fn load_assets_batch(&self, requests: Vec<(String, AssetType)>) -> Vec<BatchLoadResult> {
+        // Still PARALLEL_LOAD_FACTOR loads in flight, but results come back in request order
+        stream::iter(requests)
+            .map(|(path, asset_type)| async move {
+                let started = clock_ms();
+                let result = self.load_with_options(path.clone(), asset_type, &LoadOptions::default(), None).await;
+                BatchLoadResult {
+                    bytes: result.as_ref().map_or(0, |(_, size)| *size),
+                    result: result.map(|(handle, _)| handle),
+                    duration: Duration::from_secs_f64((clock_ms() - started).max(0.0) / 1000.0),
+                    path,
+                    asset_type,
+                }
+            })
+            .buffered(PARALLEL_LOAD_FACTOR)
+            .collect()
+            .await
+    }
+    
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn load_asset_zero_copy(&self, data: &[u8], tier: Tier) -> Option<MemoryHandle> {
+        let handle = self.allocate(data.len(), tier)?;
+        
+        unsafe {
+            let ptr = handle.resolve(self.handle_base);
+            SIMDOps::fast_copy(data.as_ptr(), ptr, data.len());
+        }
+        
+        Some(handle)
+    }
+    
+    pub fn get_asset(&self, path: &str) -> Option<AssetMetadata> {
+        let metadata = self.assets.get(path);
+        if metadata.is_some() {
+            self.assets.touch(path);
+        }
+        metadata
+    }
+    
+    // The asset's bytes, pinned against eviction and compaction until the guard drops. None
+    // if the key isn't registered or its bytes are currently lent out for writing.
+    pub fn access_asset(&self, path: &str) -> Option<AssetGuard<'_>> {
+        let (key, metadata) = self.assets.pin(path)?;
+        let bytes = self.read_view(metadata.handle, metadata.size).ok()
+            // Relocated between the pin and the borrow: the old handle no longer holds it
+            .filter(|_| self.assets.get(&key).is_some_and(|current| current.handle == metadata.handle));
+        let Some(bytes) = bytes else {
+            self.assets.unpin(&key);
+            return None;
+        };
+        self.assets.touch(&key);
+        Some(AssetGuard { walloc: self, key, metadata, bytes })
+    }
+    
+    // A reference that resolves to None instead of reused memory once the asset moves or goes
+    pub fn get_asset_ref(&self, path: &str) -> Option<AssetRef<'_>> {
+        let (metadata, generation) = self.assets.get_with_generation(path)?;
+        if metadata.handle.is_null() {
+            return None;
+        }
+        self.assets.touch(path);
+        Some(AssetRef {
+            walloc: self,
+            key: path.to_string(),
+            handle: metadata.handle,
+            size: metadata.size,
+            tier: metadata.tier,
+            generation,
+            epoch: self.arenas[metadata.tier as usize].epoch.load(Ordering::SeqCst),
+        })
+    }
+    
+    pub fn set_asset_priority(&self, path: &str, priority: u8) -> bool {
+        self.assets.set_priority(path, priority)
+    }
+    
+    // Pinned assets are skipped by allocate_or_evict (explicit evict_asset still works)
+    pub fn pin_asset(&self, path: &str, pinned: bool) -> bool {
+        self.assets.set_pinned(path, pinned)
+    }
+    
+    // Attach (or clear) an attribution tag on an already registered asset
+    pub fn tag_asset(&self, path: &str, tag: Option<String>) -> bool {
+        self.assets.set_tag(path, tag)
+    }
+    
+    // Live registered bytes by asset type, key prefix and tag, per tier
+    pub fn attribution_report(&self) -> AttributionReport {
+        let mut by_asset_type = HashMap::new();
+        let mut by_prefix = HashMap::new();
+        let mut by_tag = HashMap::new();
+        let mut registered_bytes = [0; 3];
+        
+        for (key, metadata) in self.assets.assets.read().unwrap().iter() {
+            let arena = &self.arenas[metadata.tier as usize];
+            if !arena.contains(metadata.handle) {
+                continue;
+            }
+            
+            let tier = metadata.tier;
+            let bytes = arena.align_size(metadata.size);
+            registered_bytes[tier as usize] += bytes;
+            
+            add_to_group(&mut by_asset_type, &format!("{:?}", metadata.asset_type), tier, bytes);
+            add_to_group(&mut by_prefix, key_prefix(key), tier, bytes);
+            add_to_group(&mut by_tag, metadata.tag.as_deref().unwrap_or("(untagged)"), tier, bytes);
+        }
+        
+        // Tagged allocate_with_opts blocks that were never registered as assets
+        let registered: std::collections::HashSet<_> = self.assets.assets.read().unwrap().values()
+            .map(|metadata| metadata.handle.offset())
+            .collect();
+        let mut tagged_bytes = [0; 3];
+        for arena in &self.arenas {
+            for (offset, (bytes, tag)) in arena.tags.lock().unwrap().iter() {
+                if !registered.contains(offset) {
+                    tagged_bytes[arena.tier as usize] += bytes;
+                    add_to_group(&mut by_tag, tag, arena.tier, *bytes);
+                }
+            }
+        }
+        
+        AttributionReport {
+            by_asset_type: sorted_groups(by_asset_type),
+            by_prefix: sorted_groups(by_prefix),
+            by_tag: sorted_groups(by_tag),
+            registered_bytes,
+            unattributed_bytes: std::array::from_fn(|i| {
+                self.arenas[i].live_bytes().saturating_sub(registered_bytes[i] + tagged_bytes[i])
+            }),
+        }
+    }
+    
+    // Every AssetType in declaration order, including ones with nothing registered. Bytes
+    // are aligned footprints, as in attribution_report.
+    pub fn usage_by_asset_type(&self) -> Vec<AssetTypeUsage> {
+        let mut usage = [AssetType::Image, AssetType::Json, AssetType::Binary]
+            .map(|asset_type| AssetTypeUsage { asset_type, total: UsageCount::default(), tiers: [UsageCount::default(); 3] });
+        
+        for metadata in self.assets.assets.read().unwrap().values() {
+            let arena = &self.arenas[metadata.tier as usize];
+            if !arena.contains(metadata.handle) {
+                continue;
+            }
+            let bytes = arena.align_size(metadata.size);
+            let entry = &mut usage[metadata.asset_type as usize];
+            for counts in [&mut entry.total, &mut entry.tiers[metadata.tier as usize]] {
+                counts.count += 1;
+                counts.bytes += bytes;
+            }
+        }
+        usage.to_vec()
+    }
+    
+    // ================================
+    // === MANAGEMENT & STATS ===
+    // ================================
+    
+    pub fn reset_tier(&self, tier: Tier) {
+        // A job planned before the reset would only be abandoned on its next step
+        self.compaction.lock().unwrap()[tier as usize] = None;
+        self.arenas[tier as usize].reset();
+        self.check_watermarks(tier);
+    }
+    
+    pub fn tier_stats(&self, tier: Tier) -> (usize, usize, usize, usize) {
+        self.arenas[tier as usize].stats()
+    }
+    
+    pub fn mark(&self, tier: Tier) -> ArenaMark {
+        self.arenas[tier as usize].mark()
+    }
+    
+    pub fn rollback(&self, mark: &ArenaMark) -> Result<usize, &'static str> {
+        self.arenas[mark.tier as usize].rollback(mark)
+    }
+    
+    // Carve `size` bytes out of a tier for a subsystem-owned bump region
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn create_region(&self, tier: Tier, size: usize) -> Option<Region<'_>> {
+        if size == 0 {
+            return None;
+        }
+        let base = self.allocate(size, tier)?;
+        
+        Some(Region {
+            walloc: self,
+            tier,
+            inner: BumpRegion::new(base, size),
+        })
+    }
+    
+    // Carve `size` bytes out of a tier for a LIFO allocator with push/pop markers
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn stack_allocator(&self, tier: Tier, size: usize) -> Option<StackAllocator<'_>> {
+        if size == 0 {
+            return None;
+        }
+        let base = self.allocate(size, tier)?;
+        
+        Some(StackAllocator {
+            walloc: self,
+            tier,
+            inner: BumpRegion::new(base, size),
+            depth: 0,
+            #[cfg(debug_assertions)]
+            open: Vec::new(),
+        })
+    }
+    
+    // Reserve `regions` x `region_size` bytes of Bottom for a rotating frame allocator
+    pub fn frame_allocator(&self, region_size: usize, regions: usize) -> Option<FrameAllocator<'_>> {
+        let region_size = region_size.checked_next_multiple_of(CACHE_LINE_SIZE)?;
+        if region_size == 0 || regions == 0 {
+            return None;
+        }
+        
+        let block_size = region_size.checked_mul(regions)?;
+        let block = self.allocate(block_size, Tier::Bottom)?;
+        
+        Some(FrameAllocator {
+            walloc: self,
+            block,
+            block_size,
+            regions: (0..regions)
+                .map(|i| BumpRegion::new(block.advance(i * region_size), region_size))
+                .collect(),
+            current: 0,
+            frame: 0,
+        })
+    }
+    
+    // Reserve `size` bytes of Bottom for a staging ring whose frames retire after
+    // `frames_in_flight` further begin_frame calls
+    pub fn ring_allocator(&self, size: usize, frames_in_flight: usize) -> Option<RingAllocator<'_>> {
+        let size = size.checked_next_multiple_of(CACHE_LINE_SIZE)?;
+        if size == 0 || frames_in_flight == 0 {
+            return None;
+        }
+        let block = self.allocate(size, Tier::Bottom)?;
+        
+        let mut fences = std::collections::VecDeque::with_capacity(frames_in_flight);
+        fences.push_back(0);
+        Some(RingAllocator { walloc: self, block, size, head: AtomicUsize::new(0), tail: 0, fences, frames_in_flight, frame: 0 })
+    }
+    
+    // Per-frame temporaries: allocations made while the guard lives are released on drop
+    // All-or-nothing loading, e.g. of an asset pack: if `body` returns an error (or panics)
+    // everything it allocated and registered through the Transaction is undone.
+    pub fn transaction<T, E>(&self, tier: Tier, body: impl FnOnce(&mut Transaction<'_>) -> Result<T, E>) -> Result<T, E> {
+        let mut transaction = Transaction {
+            walloc: self,
+            mark: self.mark(tier),
+            allocations: Vec::new(),
+            registered: Vec::new(),
+            committed: false,
+        };
+        
+        let result = body(&mut transaction);
+        transaction.committed = result.is_ok();
+        result
+    }
+    
+    // Snapshot the tier's allocated bytes into a private copy. Writers aren't paused, so
+    // take the fork at a point where the live scene isn't mutating what the worker needs.
+    pub fn fork_tier(&self, tier: Tier) -> TierFork<'_> {
+        let arena = &self.arenas[tier as usize];
+        let mark = arena.mark();
+        let mut shadow = vec![0u8; mark.head];
+        unsafe {
+            copy_arena_bytes(arena.base_ptr(), shadow.as_mut_ptr(), mark.head);
+        }
+        
+        TierFork {
+            walloc: self,
+            mark,
+            base_offset: arena.base_offset,
+            shadow,
+            dirty: Vec::new(),
+        }
+    }
+    
+    pub fn frame_scope(&self, tier: Tier) -> FrameScope<'_> {
+        FrameScope {
+            walloc: self,
+            mark: self.mark(tier),
+            released: false,
+        }
+    }
+    
+    // Peaks are otherwise process-lifetime; reset them at level or scene boundaries to
+    // measure each one on its own
+    pub fn reset_peaks(&self, tier: Tier) {
+        self.arenas[tier as usize].reset_peaks();
+    }
+    
+    pub fn memory_utilization(&self) -> f64 {
+        let mut total_used = 0;
+        
+        for tier in [Tier::Top, Tier::Middle, Tier::Bottom] {
+            let (used, _, _, _) = self.tier_stats(tier);
+            total_used += used;
+        }
+        
+        let total_memory = self.get_memory_limit();
+        
+        if total_memory > 0 {
+            (total_used as f64 / total_memory as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn test_fetch_json(&self) -> Result<String, String> {
+        let test_url = "https://jsonplaceholder.typicode.com/todos/1";
+        
+        let response = self.loader().client.get(test_url).send().await
+            .map_err(|e| format!("Failed to fetch: {}", e))?;
+        
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+        
+        let text = response.text().await
+            .map_err(|e| format!("Failed to get text: {}", e))?;
+        
+        Ok(text)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub async fn test_fetch_json(&self) -> Result<String, String> {
+        let test_url = "https://jsonplaceholder.typicode.com/todos/1";
+        
+        #[cfg(target_arch = "wasm32")]
+        web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(&format!("Testing HTTP: {}", test_url)));
+        
+        let response = self.loader().client.get(test_url).send().await
+            .map_err(|e| format!("Failed to fetch: {}", e))?;
+        
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+        
+        let text = response.text().await
+            .map_err(|e| format!("Failed to get text: {}", e))?;
+        
+        Ok(text)
+    }
+}
+
+unsafe impl Send for Walloc {}
+unsafe impl Sync for Walloc {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Walloc {
+    fn drop(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = LATEST_HANDLE_BASE.compare_exchange(self.handle_base, std::ptr::null_mut(), Ordering::AcqRel, Ordering::Acquire);
+        if !self.memory_base.is_null() {
+            for arena in &self.arenas {
+                arena.reset();
+            }
+            
+            self.assets.clear();
+
+            std::sync::atomic::fence(std::sync::atomic::Ordering::SeqCst);
+            
+            unsafe {
+                asan_unpoison(self.memory_base, self.memory_size);
+                self.backend.release(self.memory_base, self.memory_size);
+            }
+        }
+    }
+}
+
+// ================================
+// === CONTAINERS ===
+// ================================
+
+// Containers borrow the Walloc they allocate from and free their block on drop. They are
+// not registered assets, so compaction treats their blocks as immovable and only slides
+// assets into space known to be free; resetting or fast-compacting their tier while one is
+// alive leaves it dangling, exactly like a raw MemoryHandle.
+
+// Zero-sized requests get a null handle and never touch the arena
+fn allocate_array<T>(walloc: &Walloc, tier: Tier, count: usize) -> Option<MemoryHandle> {
+    let size = std::mem::size_of::<T>().checked_mul(count)?;
+    if size == 0 {
+        return Some(MemoryHandle::null());
+    }
+    
+    let handle = walloc.allocate(size, tier)?;
+    if !walloc.handle_ptr(handle).cast::<T>().is_aligned() {
+        walloc.arenas[tier as usize].deallocate(handle, size);
+        return None;
+    }
+    Some(handle)
+}
+
+fn free_array<T>(walloc: &Walloc, tier: Tier, handle: MemoryHandle, count: usize) {
+    if !handle.is_null() {
+        walloc.arenas[tier as usize].deallocate(handle, std::mem::size_of::<T>() * count);
+    }
+}
+
+fn array_ptr<T>(walloc: &Walloc, handle: MemoryHandle) -> *mut T {
+    if handle.is_null() {
+        std::ptr::NonNull::dangling().as_ptr()
+    } else {
+        walloc.handle_ptr(handle) as *mut T
+    }
+}
+
+pub struct WVec<'a, T> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    handle: MemoryHandle,
+    len: usize,
+    capacity: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> WVec<'a, T> {
+    pub fn new_in(walloc: &'a Walloc, tier: Tier) -> Self {
+        Self {
+            walloc,
+            tier,
+            handle: MemoryHandle::null(),
+            len: 0,
+            capacity: if std::mem::size_of::<T>() == 0 { usize::MAX } else { 0 },
+            _marker: PhantomData,
+        }
+    }
+    
+    pub fn with_capacity_in(capacity: usize, walloc: &'a Walloc, tier: Tier) -> Option<Self> {
+        let mut vec = Self::new_in(walloc, tier);
+        vec.try_reserve(capacity).ok()?;
+        Some(vec)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    // Backing block; null until the first allocation
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+    
+    // Growth allocates a new block, copies, and frees the old one back to the tier
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), &'static str> {
+        let required = self.len.checked_add(additional).ok_or("Capacity overflow")?;
+        if required <= self.capacity {
+            return Ok(());
+        }
+        
+        let new_capacity = required.max(self.capacity.saturating_mul(2)).max(4);
+        let handle = allocate_array::<T>(self.walloc, self.tier, new_capacity)
+            .ok_or("Tier exhausted")?;
+        
+        if self.len > 0 {
+            unsafe {
+                SIMDOps::fast_copy(self.walloc.handle_ptr(self.handle), self.walloc.handle_ptr(handle), self.len * std::mem::size_of::<T>());
+            }
+        }
+        free_array::<T>(self.walloc, self.tier, self.handle, self.capacity);
+        
+        self.handle = handle;
+        self.capacity = new_capacity;
+        Ok(())
+    }
+    
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("WVec allocation in {:?} tier failed: {}", self.tier, e);
+        }
+    }
+    
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.try_reserve(1).is_err() {
+            return Err(value);
+        }
+        unsafe { array_ptr::<T>(self.walloc, self.handle).add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+    
+    pub fn push(&mut self, value: T) {
+        self.reserve(1);
+        unsafe { array_ptr::<T>(self.walloc, self.handle).add(self.len).write(value) };
+        self.len += 1;
+    }
+    
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        Some(unsafe { array_ptr::<T>(self.walloc, self.handle).add(self.len).read() })
+    }
+    
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let tail = self.len - len;
+        self.len = len;
+        unsafe {
+            std::ptr::drop_in_place(std::ptr::slice_from_raw_parts_mut(array_ptr::<T>(self.walloc, self.handle).add(len), tail));
+        }
+    }
+    
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T: Clone> WVec<'_, T> {
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.reserve(values.len());
+        for value in values {
+            self.push(value.clone());
+        }
+    }
+}
+
+impl<T: Copy> WVec<'_, T> {
+    // Single block copy instead of per-element clones
+    pub fn try_extend_from_slice(&mut self, values: &[T]) -> Result<(), &'static str> {
+        self.try_reserve(values.len())?;
+        unsafe {
+            SIMDOps::fast_copy(
+                values.as_ptr() as *const u8,
+                array_ptr::<T>(self.walloc, self.handle).add(self.len) as *mut u8,
+                std::mem::size_of_val(values),
+            );
+        }
+        self.len += values.len();
+        Ok(())
+    }
+}
+
+impl<T> std::ops::Deref for WVec<'_, T> {
+    type Target = [T];
+    
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(array_ptr::<T>(self.walloc, self.handle), self.len) }
+    }
+}
+
+impl<T> std::ops::DerefMut for WVec<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(array_ptr::<T>(self.walloc, self.handle), self.len) }
+    }
+}
+
+impl<T> Extend<T> for WVec<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for WVec<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Drop for WVec<'_, T> {
+    fn drop(&mut self) {
+        self.clear();
+        free_array::<T>(self.walloc, self.tier, self.handle, self.capacity);
+    }
+}
+
+pub struct WBox<'a, T> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    handle: MemoryHandle,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> WBox<'a, T> {
+    // Hands the value back if the tier is exhausted
+    pub fn try_new_in(value: T, walloc: &'a Walloc, tier: Tier) -> Result<Self, T> {
+        let Some(handle) = allocate_array::<T>(walloc, tier, 1) else {
+            return Err(value);
+        };
+        unsafe { array_ptr::<T>(walloc, handle).write(value) };
+        Ok(Self { walloc, tier, handle, _marker: PhantomData })
+    }
+    
+    pub fn new_in(value: T, walloc: &'a Walloc, tier: Tier) -> Self {
+        match Self::try_new_in(value, walloc, tier) {
+            Ok(boxed) => boxed,
+            Err(_) => panic!("WBox allocation in {:?} tier failed", tier),
+        }
+    }
+    
+    pub fn into_inner(self) -> T {
+        let value = unsafe { array_ptr::<T>(self.walloc, self.handle).read() };
+        free_array::<T>(self.walloc, self.tier, self.handle, 1);
+        std::mem::forget(self);
+        value
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+}
+
+impl<T> std::ops::Deref for WBox<'_, T> {
+    type Target = T;
+    
+    fn deref(&self) -> &T {
+        unsafe { &*array_ptr::<T>(self.walloc, self.handle) }
+    }
+}
+
+impl<T> std::ops::DerefMut for WBox<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *array_ptr::<T>(self.walloc, self.handle) }
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for WBox<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> Drop for WBox<'_, T> {
+    fn drop(&mut self) {
+        unsafe { std::ptr::drop_in_place(array_ptr::<T>(self.walloc, self.handle)) };
+        free_array::<T>(self.walloc, self.tier, self.handle, 1);
+    }
+}
+
+// Shared ownership of a value in a tier. The strong count sits in front of the value in
+// the same block, so cloning and dropping never touch the Rust heap; the last drop frees
+// the block back into its tier.
+#[repr(C)]
+struct WArcInner<T> {
+    count: AtomicUsize,
+    value: T,
+}
+
+pub struct WArc<'a, T> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    handle: MemoryHandle,
+    _marker: PhantomData<*const WArcInner<T>>,
+}
+
+// Clones on different threads share the value, so both directions need T: Send + Sync
+unsafe impl<T: Send + Sync> Send for WArc<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for WArc<'_, T> {}
+
+impl<'a, T> WArc<'a, T> {
+    // Hands the value back if the tier is exhausted
+    pub fn try_new_in(value: T, walloc: &'a Walloc, tier: Tier) -> Result<Self, T> {
+        let Some(handle) = allocate_array::<WArcInner<T>>(walloc, tier, 1) else {
+            return Err(value);
+        };
+        unsafe { array_ptr::<WArcInner<T>>(walloc, handle).write(WArcInner { count: AtomicUsize::new(1), value }) };
+        Ok(Self { walloc, tier, handle, _marker: PhantomData })
+    }
+    
+    pub fn new_in(value: T, walloc: &'a Walloc, tier: Tier) -> Self {
+        match Self::try_new_in(value, walloc, tier) {
+            Ok(shared) => shared,
+            Err(_) => panic!("WArc allocation in {:?} tier failed", tier),
+        }
+    }
+    
+    fn inner(&self) -> &WArcInner<T> {
+        unsafe { &*array_ptr::<WArcInner<T>>(self.walloc, self.handle) }
+    }
+    
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().count.load(Ordering::Acquire)
+    }
+    
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.handle == other.handle
+    }
+    
+    // Mutable access while this is the only reference
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if this.inner().count.load(Ordering::Acquire) != 1 {
+            return None;
+        }
+        Some(unsafe { &mut (*array_ptr::<WArcInner<T>>(this.walloc, this.handle)).value })
+    }
+    
+    // The value, if this was the last reference; otherwise the WArc is handed back
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this.inner().count.compare_exchange(1, 0, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(this);
+        }
+        let value = unsafe { std::ptr::addr_of!((*array_ptr::<WArcInner<T>>(this.walloc, this.handle)).value).read() };
+        free_array::<WArcInner<T>>(this.walloc, this.tier, this.handle, 1);
+        std::mem::forget(this);
+        Ok(value)
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    // Start of the block, i.e. the count rather than the value
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+}
+
+impl<T> Clone for WArc<'_, T> {
+    fn clone(&self) -> Self {
+        // Same overflow guard as std's Arc: a leaked clone loop must not wrap the count
+        if self.inner().count.fetch_add(1, Ordering::Relaxed) > isize::MAX as usize {
+            std::process::abort();
+        }
+        Self { walloc: self.walloc, tier: self.tier, handle: self.handle, _marker: PhantomData }
+    }
+}
+
+impl<T> std::ops::Deref for WArc<'_, T> {
+    type Target = T;
+    
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for WArc<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> Drop for WArc<'_, T> {
+    fn drop(&mut self) {
+        if self.inner().count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        std::sync::atomic::fence(Ordering::Acquire);
+        unsafe { std::ptr::drop_in_place(array_ptr::<WArcInner<T>>(self.walloc, self.handle)) };
+        free_array::<WArcInner<T>>(self.walloc, self.tier, self.handle, 1);
+    }
+}
+
+// Growable byte buffer in a tier, e.g. for serialized payloads (implements io::Write)
+pub struct WBytes<'a> {
+    bytes: WVec<'a, u8>,
+}
+
+impl<'a> WBytes<'a> {
+    pub fn new_in(walloc: &'a Walloc, tier: Tier) -> Self {
+        Self { bytes: WVec::new_in(walloc, tier) }
+    }
+    
+    pub fn with_capacity_in(capacity: usize, walloc: &'a Walloc, tier: Tier) -> Option<Self> {
+        WVec::with_capacity_in(capacity, walloc, tier).map(|bytes| Self { bytes })
+    }
+    
+    pub fn from_slice_in(data: &[u8], walloc: &'a Walloc, tier: Tier) -> Option<Self> {
+        let mut bytes = Self::with_capacity_in(data.len(), walloc, tier)?;
+        bytes.try_extend_from_slice(data).ok()?;
+        Some(bytes)
+    }
+    
+    pub fn try_extend_from_slice(&mut self, data: &[u8]) -> Result<(), &'static str> {
+        self.bytes.try_extend_from_slice(data)
+    }
+    
+    pub fn extend_from_slice(&mut self, data: &[u8]) {
+        if let Err(e) = self.bytes.try_extend_from_slice(data) {
+            panic!("WBytes allocation in {:?} tier failed: {}", self.bytes.tier(), e);
+        }
+    }
+    
+    pub fn push(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+    
+    pub fn truncate(&mut self, len: usize) {
+        self.bytes.truncate(len);
+    }
+    
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.bytes.tier()
+    }
+    
+    pub fn handle(&self) -> MemoryHandle {
+        self.bytes.handle()
+    }
+}
+
+impl std::ops::Deref for WBytes<'_> {
+    type Target = [u8];
+    
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl std::ops::DerefMut for WBytes<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+}
+
+impl std::fmt::Debug for WBytes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WBytes").field("len", &self.len()).field("tier", &self.tier()).finish()
+    }
+}
+
+impl Write for WBytes<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.try_extend_from_slice(buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::OutOfMemory, e))?;
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// UTF-8 string in a tier, for asset keys, shader sources and the like
+pub struct WString<'a> {
+    bytes: WVec<'a, u8>,
+}
+
+impl<'a> WString<'a> {
+    pub fn new_in(walloc: &'a Walloc, tier: Tier) -> Self {
+        Self { bytes: WVec::new_in(walloc, tier) }
+    }
+    
+    pub fn with_capacity_in(capacity: usize, walloc: &'a Walloc, tier: Tier) -> Option<Self> {
+        WVec::with_capacity_in(capacity, walloc, tier).map(|bytes| Self { bytes })
+    }
+    
+    // The arena counterpart of From<&str>, which cannot name a Walloc or tier
+    pub fn from_str_in(value: &str, walloc: &'a Walloc, tier: Tier) -> Option<Self> {
+        let mut string = Self::with_capacity_in(value.len(), walloc, tier)?;
+        string.try_push_str(value).ok()?;
+        Some(string)
+    }
+    
+    pub fn try_push_str(&mut self, value: &str) -> Result<(), &'static str> {
+        self.bytes.try_extend_from_slice(value.as_bytes())
+    }
+    
+    pub fn push_str(&mut self, value: &str) {
+        if let Err(e) = self.try_push_str(value) {
+            panic!("WString allocation in {:?} tier failed: {}", self.bytes.tier(), e);
+        }
+    }
+    
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+    
+    pub fn as_str(&self) -> &str {
+        unsafe { std::str::from_utf8_unchecked(&self.bytes) }
+    }
+    
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.bytes.capacity()
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.bytes.tier()
+    }
+    
+    pub fn handle(&self) -> MemoryHandle {
+        self.bytes.handle()
+    }
+    
+    pub fn into_bytes(self) -> WBytes<'a> {
+        WBytes { bytes: self.bytes }
+    }
+}
+
+impl std::ops::Deref for WString<'_> {
+    type Target = str;
+    
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for WString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::fmt::Debug for WString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl std::fmt::Write for WString<'_> {
+    fn write_str(&mut self, value: &str) -> std::fmt::Result {
+        self.try_push_str(value).map_err(|_| std::fmt::Error)
+    }
+}
+
+impl PartialEq<str> for WString<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for WString<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<&WString<'_>> for String {
+    fn from(value: &WString<'_>) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+// Block whose final size is unknown up front, e.g. a streamed download without a
+// Content-Length. grow extends the block in place while it is the last one bumped in its
+// tier; otherwise it moves to a block twice the size, copies the bytes so far and frees the
+// old one, reporting the move to the on_relocate callback.
+pub struct GrowableHandle<'a> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    handle: MemoryHandle,
+    len: usize,
+    capacity: usize,
+    on_relocate: Option<Box<dyn FnMut(MemoryHandle, MemoryHandle) + Send + 'a>>,
+}
+
+impl<'a> GrowableHandle<'a> {
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    // Called with the old and new handle after each relocation
+    pub fn on_relocate(&mut self, callback: impl FnMut(MemoryHandle, MemoryHandle) + Send + 'a) {
+        self.on_relocate = Some(Box::new(callback));
+    }
+    
+    // Bytes past the old length are uninitialized. Shrinking is a no-op.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn grow(&mut self, new_len: usize) -> Result<MemoryHandle, &'static str> {
+        if new_len <= self.capacity {
+            self.len = self.len.max(new_len);
+            return Ok(self.handle);
+        }
+        
+        let arena = &self.walloc.arenas[self.tier as usize];
+        if arena.grow_in_place(self.handle.offset(), self.capacity, new_len) {
+            walloc_event!(trace, tier = ?self.tier, size = new_len, offset = self.handle.offset(), "grow in place");
+            self.len = new_len;
+            self.capacity = new_len;
+            return Ok(self.handle);
+        }
+        
+        let new_capacity = new_len.max(self.capacity.saturating_mul(2));
+        let handle = self.walloc.allocate(new_capacity, self.tier).ok_or("Tier exhausted")?;
+        unsafe {
+            SIMDOps::fast_copy(self.walloc.handle_ptr(self.handle), self.walloc.handle_ptr(handle), self.len);
+        }
+        arena.deallocate(self.handle, self.capacity);
+        
+        let old = std::mem::replace(&mut self.handle, handle);
+        self.len = new_len;
+        self.capacity = new_capacity;
+        if let Some(callback) = self.on_relocate.as_mut() {
+            callback(old, handle);
+        }
+        Ok(handle)
+    }
+    
+    // Keep the block past this guard's lifetime, e.g. to register it as an asset. Returns the
+    // handle and the capacity to deallocate it with.
+    pub fn into_raw(self) -> (MemoryHandle, usize) {
+        let mut this = std::mem::ManuallyDrop::new(self);
+        this.on_relocate = None;
+        (this.handle, this.capacity)
+    }
+}
+
+impl Drop for GrowableHandle<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[self.tier as usize].deallocate(self.handle, self.capacity);
+    }
+}
+
+impl std::fmt::Debug for GrowableHandle<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrowableHandle")
+            .field("handle", &self.handle)
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("tier", &self.tier)
+            .finish()
+    }
+}
+
+// `len` uninitialised Ts from allocate_slice, freed when dropped. The block is borrowed like a
+// with_slice range for as long as the guard lives, so checked views of it fail with Aliased.
+pub struct UninitSlice<'a, T> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    handle: MemoryHandle,
+    len: usize,
+    borrow: Option<BorrowGuard<'a>>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> UninitSlice<'_, T> {
+    // Null for zero-sized slices, which own no block
+    pub fn handle(&self) -> MemoryHandle {
+        self.handle
+    }
+    
+    // Keep the block past this guard's lifetime, e.g. once every slot is initialised and it
+    // should be registered as an asset. Returns the handle and the size to deallocate it with.
+    pub fn into_raw(self) -> (MemoryHandle, usize) {
+        let mut this = std::mem::ManuallyDrop::new(self);
+        this.borrow = None;
+        (this.handle, std::mem::size_of::<T>() * this.len)
+    }
+}
+
+impl<T> std::ops::Deref for UninitSlice<'_, T> {
+    type Target = [std::mem::MaybeUninit<T>];
+    
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(array_ptr(self.walloc, self.handle), self.len) }
+    }
+}
+
+impl<T> std::ops::DerefMut for UninitSlice<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(array_ptr(self.walloc, self.handle), self.len) }
+    }
+}
+
+impl<T> Drop for UninitSlice<'_, T> {
+    fn drop(&mut self) {
+        // Released first, so the freed block can be borrowed again as soon as it is reused
+        self.borrow = None;
+        free_array::<T>(self.walloc, self.tier, self.handle, self.len);
+    }
+}
+
+impl<T> std::fmt::Debug for UninitSlice<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UninitSlice")
+            .field("type", &std::any::type_name::<T>())
+            .field("handle", &self.handle)
+            .field("len", &self.len)
+            .field("tier", &self.tier)
+            .finish()
+    }
+}
+
+impl Walloc {
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_growable(&self, initial: usize, tier: Tier) -> Option<GrowableHandle<'_>> {
+        let capacity = initial.max(1);
+        let handle = self.allocate(capacity, tier)?;
+        Some(GrowableHandle { walloc: self, tier, handle, len: initial, capacity, on_relocate: None })
+    }
+    
+    // `len` uninitialised Ts laid out as Layout::array::<T>(len). None if the layout
+    // overflows or T needs more alignment than the tier guarantees. The slice lives in a
+    // guard that frees the block on drop, so it cannot outlive its memory or alias a reuse.
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_slice<T>(&self, len: usize, tier: Tier) -> Option<UninitSlice<'_, T>> {
+        let layout = std::alloc::Layout::array::<T>(len).ok()?;
+        if layout.align() > tier.alignment() {
+            return None;
+        }
+        let handle = allocate_array::<T>(self, tier, len)?;
+        let borrow = match handle.is_null() {
+            true => None,
+            false => match self.borrows.acquire(handle.offset(), handle.offset() + layout.size(), true) {
+                Ok(borrow) => Some(borrow),
+                // A view of the block's previous occupant outlived its free
+                Err(_) => {
+                    free_array::<T>(self, tier, handle, len);
+                    return None;
                 }
-                
-                // Remove the target asset
-                return self.assets.remove(path);
+            },
+        };
+        Some(UninitSlice { walloc: self, tier, handle, len, borrow, _marker: PhantomData })
+    }
+}
+
+// ================================
+// === REGIONS ===
+// ================================
+
+// Bump allocator over a fixed slice of a tier, reset wholesale
+struct BumpRegion {
+    base: MemoryHandle,
+    size: usize,
+    head: AtomicUsize,
+}
+
+impl BumpRegion {
+    fn new(base: MemoryHandle, size: usize) -> Self {
+        Self { base, size, head: AtomicUsize::new(0) }
+    }
+    
+    fn allocate(&self, size: usize, align: usize) -> Option<MemoryHandle> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let start = (self.base.offset() + head).checked_next_multiple_of(align)? - self.base.offset();
+            let end = start.checked_add(size).filter(|&end| end <= self.size)?;
+            match self.head.compare_exchange_weak(head, end, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Some(self.base.advance(start)),
+                Err(current) => head = current,
             }
-            
-            // On native platforms, just deallocate without compaction
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                let removed = self.assets.remove(path);
-                
-                if removed {
-                    let arena = &self.arenas[tier as usize];
-                    let _ = arena.deallocate(handle, size);
+        }
+    }
+    
+    fn used(&self) -> usize {
+        self.head.load(Ordering::Relaxed)
+    }
+    
+    fn reset(&self) {
+        self.rewind(0);
+    }
+    
+    fn rewind(&self, head: usize) {
+        self.head.store(head, Ordering::Relaxed);
+    }
+    
+    fn contains(&self, handle: MemoryHandle) -> bool {
+        handle.offset() >= self.base.offset() && handle.offset() < self.base.offset() + self.size
+    }
+}
+
+// A subsystem's private slice of a tier with its own bump pointer. reset() recycles only
+// this region, so e.g. particles can be cleared without reset_tier invalidating unrelated
+// allocations. The slice is returned to the tier when the region is dropped.
+pub struct Region<'a> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    inner: BumpRegion,
+}
+
+impl Region<'_> {
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.inner.size
+    }
+    
+    pub fn used(&self) -> usize {
+        self.inner.used()
+    }
+    
+    pub fn remaining(&self) -> usize {
+        self.inner.size - self.inner.used()
+    }
+    
+    pub fn allocate(&self, size: usize) -> Option<MemoryHandle> {
+        self.allocate_aligned(size, SIMD_ALIGNMENT)
+    }
+    
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Option<MemoryHandle> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+        self.inner.allocate(size, align)
+    }
+    
+    // Invalidates every handle allocated from this region
+    pub fn reset(&self) {
+        self.inner.reset();
+    }
+    
+    pub fn contains(&self, handle: MemoryHandle) -> bool {
+        self.inner.contains(handle)
+    }
+}
+
+impl Drop for Region<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[self.tier as usize].deallocate(self.inner.base, self.inner.size);
+        self.walloc.check_compaction_policy(self.tier);
+    }
+}
+
+// Top of a StackAllocator, to pop back to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StackMarker {
+    top: usize,
+    depth: usize,
+}
+
+impl StackMarker {
+    pub fn top(&self) -> usize {
+        self.top
+    }
+    
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+// LIFO allocator over a private slice of a tier for strictly nested temporaries, e.g. parser
+// or pathfinding scratch. pop_to_marker releases everything allocated since the marker was
+// pushed. Debug builds also check that markers are popped innermost first.
+pub struct StackAllocator<'a> {
+    walloc: &'a Walloc,
+    tier: Tier,
+    inner: BumpRegion,
+    depth: usize,
+    #[cfg(debug_assertions)]
+    open: Vec<usize>,  // Tops of the markers not yet popped
+}
+
+impl StackAllocator<'_> {
+    pub fn tier(&self) -> Tier {
+        self.tier
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.inner.size
+    }
+    
+    pub fn used(&self) -> usize {
+        self.inner.used()
+    }
+    
+    // Markers currently pushed
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    
+    pub fn allocate(&self, size: usize) -> Option<MemoryHandle> {
+        self.allocate_aligned(size, SIMD_ALIGNMENT)
+    }
+    
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Option<MemoryHandle> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+        self.inner.allocate(size, align)
+    }
+    
+    pub fn push_marker(&mut self) -> StackMarker {
+        let marker = StackMarker { top: self.inner.used(), depth: self.depth };
+        self.depth += 1;
+        #[cfg(debug_assertions)]
+        self.open.push(marker.top);
+        marker
+    }
+    
+    // Returns the bytes released. Handles allocated since the marker are invalid afterwards.
+    pub fn pop_to_marker(&mut self, marker: StackMarker) -> Result<usize, &'static str> {
+        #[cfg(debug_assertions)]
+        if marker.depth + 1 != self.depth || self.open.last() != Some(&marker.top) {
+            return Err("Markers must be popped innermost first");
+        }
+        if marker.depth >= self.depth || marker.top > self.inner.used() {
+            return Err("Marker was already popped");
+        }
+        
+        #[cfg(debug_assertions)]
+        self.open.pop();
+        let released = self.inner.used() - marker.top;
+        self.inner.rewind(marker.top);
+        self.depth = marker.depth;
+        Ok(released)
+    }
+}
+
+impl Drop for StackAllocator<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[self.tier as usize].deallocate(self.inner.base, self.inner.size);
+        self.walloc.check_compaction_policy(self.tier);
+    }
+}
+
+// ================================
+// === FRAME ALLOCATOR ===
+// ================================
+
+// N equally sized regions carved from one Bottom-tier block. begin_frame moves to the next
+// region and clears it, so the previous N - 1 frames' data stays readable while the current
+// frame allocates.
+pub struct FrameAllocator<'a> {
+    walloc: &'a Walloc,
+    block: MemoryHandle,
+    block_size: usize,
+    regions: Vec<BumpRegion>,
+    current: usize,
+    frame: u64,
+}
+
+impl FrameAllocator<'_> {
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+    
+    pub fn region_count(&self) -> usize {
+        self.regions.len()
+    }
+    
+    pub fn region_size(&self) -> usize {
+        self.regions[0].size
+    }
+    
+    // Bytes used by the current frame
+    pub fn used(&self) -> usize {
+        self.regions[self.current].used()
+    }
+    
+    // Exclusive borrow, so no allocation can straddle the flip
+    pub fn begin_frame(&mut self) -> u64 {
+        self.current = (self.current + 1) % self.regions.len();
+        self.regions[self.current].reset();
+        self.frame += 1;
+        self.frame
+    }
+    
+    pub fn allocate(&self, size: usize) -> Option<MemoryHandle> {
+        self.allocate_aligned(size, SIMD_ALIGNMENT)
+    }
+    
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Option<MemoryHandle> {
+        if !align.is_power_of_two() {
+            return None;
+        }
+        self.regions[self.current].allocate(size, align)
+    }
+    
+    // How many frames ago `handle` was allocated, if its region has not been recycled since
+    pub fn frame_age(&self, handle: MemoryHandle) -> Option<usize> {
+        let count = self.regions.len();
+        (0..count).find(|&age| {
+            let region = &self.regions[(self.current + count - age) % count];
+            region.contains(handle) && handle.offset() < region.base.offset() + region.used()
+        })
+    }
+}
+
+impl Drop for FrameAllocator<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[Tier::Bottom as usize].deallocate(self.block, self.block_size);
+    }
+}
+
+// Upload staging ring over one Bottom-tier block. Allocations wrap around the block and are
+// released implicitly once `frames_in_flight` frames have begun since, so a frame's data
+// lives exactly as long as the GPU may still read it. Unlike FrameAllocator, frames share
+// the whole block instead of a fixed slice each.
+pub struct RingAllocator<'a> {
+    walloc: &'a Walloc,
+    block: MemoryHandle,
+    size: usize,
+    head: AtomicUsize,        // Positions count bytes ever allocated; the block offset is pos % size
+    tail: usize,
+    fences: std::collections::VecDeque<usize>,  // Head at the start of each live frame, oldest first
+    frames_in_flight: usize,
+    frame: u64,
+}
+
+impl RingAllocator<'_> {
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.size
+    }
+    
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames_in_flight
+    }
+    
+    // Bytes held by live frames, including padding skipped at the wrap
+    pub fn used(&self) -> usize {
+        self.head.load(Ordering::Relaxed) - self.tail
+    }
+    
+    // Retires the oldest frame once `frames_in_flight` are live; exclusive borrow, so no
+    // allocation can straddle the fence
+    pub fn begin_frame(&mut self) -> u64 {
+        self.fences.push_back(self.head.load(Ordering::Relaxed));
+        if self.fences.len() > self.frames_in_flight {
+            self.fences.pop_front();
+        }
+        self.tail = self.fences[0];
+        self.frame += 1;
+        self.frame
+    }
+    
+    pub fn allocate(&self, size: usize) -> Option<MemoryHandle> {
+        self.allocate_aligned(size, SIMD_ALIGNMENT)
+    }
+    
+    // None when the live frames leave no room; allocations never straddle the wrap
+    pub fn allocate_aligned(&self, size: usize, align: usize) -> Option<MemoryHandle> {
+        if !align.is_power_of_two() || size > self.size {
+            return None;
+        }
+        
+        let base = self.block.offset();
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let offset = head % self.size;
+            let pad = (base + offset).checked_next_multiple_of(align)? - (base + offset);
+            let start = if offset + pad + size <= self.size {
+                head + pad
+            } else {
+                // Skip the rest of the lap
+                let pad = base.checked_next_multiple_of(align)? - base;
+                if pad + size > self.size {
+                    return None;
                 }
-                
-                return removed;
+                head + (self.size - offset) + pad
+            };
+            let end = start + size;
+            if end - self.tail > self.size {
+                return None;
+            }
+            match self.head.compare_exchange_weak(head, end, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return Some(self.block.advance(start % self.size)),
+                Err(current) => head = current,
             }
         }
-        
-        false
     }
     
-    pub fn evict_assets_batch(&self, paths: &[String]) -> usize {
-        #[cfg(target_arch = "wasm32")]
-        {
-            // On WASM, evict each asset with compaction
-            let mut evicted = 0;
-            for path in paths {
-                if self.evict_asset(path) {
-                    evicted += 1;
-                }
-            }
-            evicted
-        }
+    // Whether `handle` lies in bytes held by a live frame
+    pub fn is_live(&self, handle: MemoryHandle) -> bool {
+        let base = self.block.offset();
+        if handle.offset() < base || handle.offset() >= base + self.size {
+            return false;
+        }
+        let offset = handle.offset() - base;
+        let head = self.head.load(Ordering::Relaxed);
+        if head - self.tail >= self.size {
+            return true;
+        }
+        let (tail, head) = (self.tail % self.size, head % self.size);
+        if tail <= head {
+            offset >= tail && offset < head
+        } else {
+            offset >= tail || offset < head
+        }
+    }
+}
+
+impl Drop for RingAllocator<'_> {
+    fn drop(&mut self) {
+        self.walloc.arenas[Tier::Bottom as usize].deallocate(self.block, self.size);
+    }
+}
+
+// ================================
+// === GEOMETRY LAYOUT ===
+// ================================
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryStream {
+    Positions,
+    Normals,
+    Uvs,
+    Indices,
+}
+
+// Mesh streams packed into one Top-tier block: [f32; 3] positions and normals, [f32; 2]
+// uvs and u32 indices. Every stream starts on the Top tier's 128-byte alignment unless
+// given its own with with_alignment.
+#[derive(Clone, Copy)]
+struct StreamPlan {
+    offset: usize,
+    stride: usize,
+    count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeometryLayout {
+    vertex_count: usize,
+    index_count: usize,
+    normals: bool,
+    uvs: bool,
+    alignments: [usize; 4],
+}
+
+impl GeometryLayout {
+    pub fn new(vertex_count: usize, index_count: usize) -> Self {
+        Self {
+            vertex_count,
+            index_count,
+            normals: false,
+            uvs: false,
+            alignments: [Tier::Top.alignment(); 4],
+        }
+    }
+    
+    pub fn with_normals(mut self) -> Self {
+        self.normals = true;
+        self
+    }
+    
+    pub fn with_uvs(mut self) -> Self {
+        self.uvs = true;
+        self
+    }
+    
+    // `align` must be a power of two; it is raised to the stream's element alignment
+    pub fn with_alignment(mut self, stream: GeometryStream, align: usize) -> Self {
+        self.alignments[stream as usize] = align;
+        self
+    }
+    
+    // Where each enabled stream lands in the block, and the block size
+    fn plan(&self) -> Result<([Option<StreamPlan>; 4], usize), &'static str> {
+        let streams = [
+            (GeometryStream::Positions, true, std::mem::size_of::<[f32; 3]>(), self.vertex_count),
+            (GeometryStream::Normals, self.normals, std::mem::size_of::<[f32; 3]>(), self.vertex_count),
+            (GeometryStream::Uvs, self.uvs, std::mem::size_of::<[f32; 2]>(), self.vertex_count),
+            (GeometryStream::Indices, true, std::mem::size_of::<u32>(), self.index_count),
+        ];
         
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // On native, batch process without compaction for efficiency
-            let mut evicted = 0;
-            
-            let mut to_evict = Vec::with_capacity(paths.len());
-            
-            for path in paths {
-                if let Some(metadata) = self.assets.get(path) {
-                    to_evict.push((path.clone(), metadata.handle, metadata.size, metadata.tier));
-                }
+        let mut plan = [None; 4];
+        let mut cursor = 0usize;
+        for (stream, enabled, stride, count) in streams {
+            if !enabled {
+                continue;
             }
-            
-            for (path, handle, size, tier) in to_evict {
-                if handle.is_null() || tier as usize >= self.arenas.len() {
-                    if self.assets.remove(&path) {
-                        evicted += 1;
-                    }
-                    continue;
-                }
-                
-                if self.assets.remove(&path) {
-                    let arena = &self.arenas[tier as usize];
-                    let _ = arena.deallocate(handle, size);
-                    evicted += 1;
-                }
+            let align = self.alignments[stream as usize];
+            if !align.is_power_of_two() {
+                return Err("Stream alignment must be a power of two");
             }
-            
-            evicted
+            if align > Tier::Top.alignment() {
+                return Err("Stream alignment exceeds the Top tier's");
+            }
+            let align = align.max(std::mem::align_of::<f32>());
+            let offset = cursor.checked_add(align - 1).ok_or("Geometry too large")? & !(align - 1);
+            cursor = stride.checked_mul(count)
+                .and_then(|bytes| offset.checked_add(bytes))
+                .ok_or("Geometry too large")?;
+            plan[stream as usize] = Some(StreamPlan { offset, stride, count });
         }
+        Ok((plan, cursor))
     }
     
-    pub async fn load_asset_unified(&self, path: String, asset_type: AssetType) -> Result<MemoryHandle, String> {
-        let full_url = if self.base_url.is_empty() {
-            path.clone()
-        } else {
-            format!("{}{}", self.base_url, path)
-        };
-        
-        let response = self.http_client
-            .get(&full_url)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch '{}': {}", full_url, e))?;
+    // Bytes the single allocation will take, padding included
+    pub fn size(&self) -> Result<usize, &'static str> {
+        self.plan().map(|(_, size)| size)
+    }
+}
+
+// One stream of a GeometryBuffers block, ready to bind as a vertex or index buffer
+#[derive(Clone, Copy, Debug)]
+pub struct GeometryView<'a, T: Pod> {
+    pub data: TypedHandle<'a, T>,
+    pub offset: usize,  // From the start of the block
+    pub stride: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GeometryBuffers<'a> {
+    pub block: MemoryHandle,
+    pub size: usize,
+    pub positions: GeometryView<'a, [f32; 3]>,
+    pub normals: Option<GeometryView<'a, [f32; 3]>>,
+    pub uvs: Option<GeometryView<'a, [f32; 2]>>,
+    pub indices: GeometryView<'a, u32>,
+}
+
+impl Walloc {
+    // One Top-tier allocation holding every stream of `layout`; release it with
+    // deallocate_geometry
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_geometry(&self, layout: &GeometryLayout) -> Result<GeometryBuffers<'_>, &'static str> {
+        let (plan, size) = layout.plan()?;
+        if size == 0 {
+            return Err("Geometry has no vertices or indices");
+        }
+        let block = self.allocate(size, Tier::Top).ok_or("Top tier cannot fit the geometry")?;
         
-        if !response.status().is_success() {
-            return Err(format!("HTTP error {}: {}", response.status(), full_url));
+        fn view<T: Pod>(walloc: &Walloc, block: MemoryHandle, stream: Option<StreamPlan>) -> Option<GeometryView<'_, T>> {
+            let StreamPlan { offset, stride, count } = stream?;
+            let data = walloc.typed_handle(block.advance(offset), count).ok()?;
+            Some(GeometryView { data, offset, stride })
         }
         
-        let content_length = response.content_length().unwrap_or(0) as usize;
+        let positions = view(self, block, plan[GeometryStream::Positions as usize]);
+        let indices = view(self, block, plan[GeometryStream::Indices as usize]);
+        let (Some(positions), Some(indices)) = (positions, indices) else {
+            self.arenas[Tier::Top as usize].deallocate(block, size);
+            return Err("Geometry stream is misaligned");
+        };
+        Ok(GeometryBuffers {
+            block,
+            size,
+            positions,
+            normals: view(self, block, plan[GeometryStream::Normals as usize]),
+            uvs: view(self, block, plan[GeometryStream::Uvs as usize]),
+            indices,
+        })
+    }
+    
+    pub fn deallocate_geometry(&self, buffers: &GeometryBuffers<'_>) -> Result<(), &'static str> {
+        self.deallocate(buffers.block, buffers.size, Tier::Top)
+    }
+}
+
+// ================================
+// === TEXTURES ===
+// ================================
+
+// A 2D image whose rows start every `pitch` bytes, as GPU upload buffers expect
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureAllocation {
+    pub handle: MemoryHandle,
+    pub tier: Tier,
+    pub width: usize,
+    pub height: usize,
+    pub bytes_per_pixel: usize,
+    pub pitch: usize,  // Row stride: width * bytes_per_pixel rounded up to the row alignment
+}
+
+impl TextureAllocation {
+    pub fn row_bytes(&self) -> usize {
+        self.width * self.bytes_per_pixel
+    }
+    
+    pub fn size(&self) -> usize {
+        self.pitch * self.height
+    }
+    
+    pub fn row(&self, y: usize) -> MemoryHandle {
+        self.handle.advance(y * self.pitch)
+    }
+}
+
+impl Walloc {
+    // `row_alignment` must be a power of two (e.g. 256 for D3D12/WebGPU buffer copies)
+    #[cfg_attr(feature = "track-callers", track_caller)]
+    pub fn allocate_texture(&self, width: usize, height: usize, bytes_per_pixel: usize, row_alignment: usize, tier: Tier) -> Option<TextureAllocation> {
+        if !row_alignment.is_power_of_two() {
+            return None;
+        }
+        let row_bytes = width.checked_mul(bytes_per_pixel).filter(|&bytes| bytes > 0)?;
+        let pitch = row_bytes.checked_add(row_alignment - 1)? & !(row_alignment - 1);
+        let size = pitch.checked_mul(height).filter(|&size| size > 0)?;
+        let handle = self.allocate(size, tier)?;
+        Some(TextureAllocation { handle, tier, width, height, bytes_per_pixel, pitch })
+    }
+    
+    // Copy tightly packed scanlines into the texture starting at `first_row`, leaving the
+    // padding at the end of each row untouched. Returns the number of rows written.
+    pub fn write_texture_rows(&self, texture: &TextureAllocation, first_row: usize, pixels: &[u8]) -> Result<usize, AccessError> {
+        let row_bytes = texture.row_bytes();
+        if row_bytes == 0 || !pixels.len().is_multiple_of(row_bytes) {
+            return Err(AccessError::OutOfBounds);
+        }
+        let rows = pixels.len() / row_bytes;
+        if first_row.checked_add(rows).is_none_or(|end| end > texture.height) {
+            return Err(AccessError::OutOfBounds);
+        }
+        self.check_range(texture.handle, texture.size())?;
         
-        if content_length > 1024 * 1024 {
-            let handle = self.allocate(content_length, Tier::Middle)
-                .ok_or_else(|| format!("Failed to allocate {} bytes", content_length))?;
-            
-            let bytes = response.bytes().await
-                .map_err(|e| format!("Failed to get bytes: {}", e))?;
-            
-            unsafe {
-                SIMDOps::fast_copy(bytes.as_ptr(), handle.to_ptr(), bytes.len());
-            }
-            
-            self.assets.insert(path, AssetMetadata {
-                asset_type,
-                size: bytes.len(),
-                offset: handle.offset(),
-                tier: Tier::Middle,
-                handle,
-            });
-            
-            Ok(handle)
-        } else {
-            let bytes = response.bytes().await
-                .map_err(|e| format!("Failed to get bytes: {}", e))?;
-            
-            let handle = self.allocate(bytes.len(), Tier::Middle)
-                .ok_or_else(|| format!("Failed to allocate {} bytes", bytes.len()))?;
-            
+        for (index, scanline) in pixels.chunks_exact(row_bytes).enumerate() {
             unsafe {
-                SIMDOps::fast_copy(bytes.as_ptr(), handle.to_ptr(), bytes.len());
+                SIMDOps::fast_copy(scanline.as_ptr(), texture.row(first_row + index).resolve(self.handle_base), row_bytes);
             }
-            
-            self.assets.insert(path, AssetMetadata {
-                asset_type,
-                size: bytes.len(),
-                offset: handle.offset(),
-                tier: Tier::Middle,
-                handle,
-            });
-            
-            Ok(handle)
         }
+        Ok(rows)
+    }
+    
+    pub fn deallocate_texture(&self, texture: &TextureAllocation) -> Result<(), &'static str> {
+        self.deallocate(texture.handle, texture.size(), texture.tier)
     }
+}
 
-    pub async fn load_asset(&self, path: String, asset_type: AssetType) -> Result<MemoryHandle, String> {
-        self.load_asset_unified(path, asset_type).await
+// ================================
+// === WASM BINDINGS ===
+// ================================
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub struct WallocWrapper {
+    inner: Arc<Walloc>,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl WallocWrapper {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<WallocWrapper, JsValue> {
+        Walloc::new()
+            .map(|walloc| WallocWrapper { inner: walloc.into_arc() })
+            .map_err(|e| JsValue::from_str(e))
     }
     
-    pub async fn load_assets_batch(&self, requests: Vec<(String, AssetType)>) -> Vec<Result<MemoryHandle, String>> {
-        stream::iter(requests)
-            .map(|(path, asset_type)| async move {
-                self.load_asset(path, asset_type).await
+    // New constructor with base URL
+    #[wasm_bindgen]
+    pub fn new_with_base_url(base_url: String) -> Result<WallocWrapper, JsValue> {
+        Walloc::new()
+            .map(|walloc| WallocWrapper { 
+                inner: walloc.with_base_url(base_url).into_arc() 
             })
-            .buffer_unordered(PARALLEL_LOAD_FACTOR)
-            .collect()
-            .await
+            .map_err(|e| JsValue::from_str(e))
     }
     
-    pub fn load_asset_zero_copy(&self, data: &[u8], tier: Tier) -> Option<MemoryHandle> {
-        let handle = self.allocate(data.len(), tier)?;
-        
-        unsafe {
-            let ptr = handle.to_ptr();
-            SIMDOps::fast_copy(data.as_ptr(), ptr, data.len());
-        }
-        
-        Some(handle)
+    // Mirrors in failover order; takes effect for loads that start afterwards
+    #[wasm_bindgen]
+    pub fn set_base_urls(&self, urls: Vec<String>) {
+        self.inner.set_base_urls(urls);
     }
     
-    pub fn get_asset(&self, path: &str) -> Option<AssetMetadata> {
-        self.assets.get(path)
+    // Global credentials for load_asset; pass null to fetch anonymously again
+    #[wasm_bindgen]
+    pub fn set_bearer_token(&self, token: Option<String>) {
+        self.inner.set_auth(token.map(AssetAuth::Bearer));
     }
     
-    // ================================
-    // === MANAGEMENT & STATS ===
-    // ================================
+    #[wasm_bindgen]
+    pub fn set_api_key(&self, header: String, key: String) {
+        self.inner.set_auth(Some(AssetAuth::ApiKey { header, key }));
+    }
     
-    pub fn reset_tier(&self, tier: Tier) {
-        self.arenas[tier as usize].reset();
+    // cipher: 0 = AES-128-GCM, 1 = AES-256-GCM, 2 = ChaCha20-Poly1305; applies to every fetched asset
+    #[cfg(feature = "encryption")]
+    #[wasm_bindgen]
+    pub fn set_decryption_key(&self, cipher: u8, key: &[u8]) -> Result<(), JsValue> {
+        let cipher = match cipher {
+            0 => Cipher::Aes128Gcm,
+            1 => Cipher::Aes256Gcm,
+            2 => Cipher::ChaCha20Poly1305,
+            _ => return Err(JsValue::from_str("Invalid cipher")),
+        };
+        self.inner.set_decryption_key(AssetKey::new(cipher, key).map_err(JsValue::from_str)?);
+        Ok(())
     }
     
-    pub fn tier_stats(&self, tier: Tier) -> (usize, usize, usize, usize) {
-        self.arenas[tier as usize].stats()
+    // 0 removes the limit
+    #[wasm_bindgen]
+    pub fn set_bandwidth_limit(&self, bytes_per_sec: f64) {
+        self.inner.set_bandwidth_limit(bytes_per_sec.max(0.0) as u64);
     }
     
-    pub fn memory_utilization(&self) -> f64 {
-        let mut total_used = 0;
-        
-        for tier in [Tier::Top, Tier::Middle, Tier::Bottom] {
-            let (used, _, _, _) = self.tier_stats(tier);
-            total_used += used;
-        }
-        
-        let total_memory = self.get_memory_limit();
-        
-        if total_memory > 0 {
-            (total_used as f64 / total_memory as f64) * 100.0
-        } else {
-            0.0
-        }
+    #[wasm_bindgen]
+    pub fn set_max_connections_per_host(&self, max: usize) {
+        self.inner.set_max_connections_per_host(max);
     }
     
-    #[cfg(not(target_arch = "wasm32"))]
-    pub async fn test_fetch_json(&self) -> Result<String, String> {
-        let test_url = "https://jsonplaceholder.typicode.com/todos/1";
-        
-        let response = self.http_client.get(test_url).send().await
-            .map_err(|e| format!("Failed to fetch: {}", e))?;
-        
-        if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()));
+    #[wasm_bindgen]
+    pub fn set_failure_cooldown(&self, cooldown_ms: f64) {
+        self.inner.set_failure_cooldown(Duration::from_millis(cooldown_ms.max(0.0) as u64));
+    }
+    
+    #[wasm_bindgen]
+    pub fn allocate(&self, size: usize, tier_number: u8) -> usize {
+        match (Tier::from_u8(tier_number), self.inner.allocate(size, Tier::from_u8(tier_number).unwrap_or(Tier::Bottom))) {
+            (Some(_), Some(handle)) => handle.offset(),
+            _ => usize::MAX,
         }
-        
-        let text = response.text().await
-            .map_err(|e| format!("Failed to get text: {}", e))?;
-        
-        Ok(text)
     }
 
-    #[cfg(target_arch = "wasm32")]
-    pub async fn test_fetch_json(&self) -> Result<String, String> {
-        let test_url = "https://jsonplaceholder.typicode.com/todos/1";
-        
-        #[cfg(target_arch = "wasm32")]
-        web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(&format!("Testing HTTP: {}", test_url)));
-        
-        let response = self.http_client.get(test_url).send().await
-            .map_err(|e| format!("Failed to fetch: {}", e))?;
-        
-        if !response.status().is_success() {
-            return Err(format!("HTTP error: {}", response.status()));
+    // Returns the new offset; registered assets are repointed
+    #[wasm_bindgen]
+    pub fn migrate(&self, offset: usize, size: usize, from_tier: u8, to_tier: u8) -> Result<usize, JsValue> {
+        let from = Tier::from_u8(from_tier).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        let to = Tier::from_u8(to_tier).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        self.inner.migrate(MemoryHandle(offset), size, from, to)
+            .map(|handle| handle.offset())
+            .map_err(JsValue::from_str)
+    }
+    
+    #[wasm_bindgen]
+    pub fn allocate_reserved(&self, size: usize, tier_number: u8) -> usize {
+        match Tier::from_u8(tier_number).and_then(|tier| self.inner.allocate_reserved(size, tier)) {
+            Some(handle) => handle.offset(),
+            None => usize::MAX,
         }
-        
-        let text = response.text().await
-            .map_err(|e| format!("Failed to get text: {}", e))?;
-        
-        Ok(text)
     }
-}
-
-unsafe impl Send for Walloc {}
-unsafe impl Sync for Walloc {}
-
-#[cfg(not(target_arch = "wasm32"))]
-impl Drop for Walloc {
-    fn drop(&mut self) {
-        if !self.memory_base.is_null() {
-            for arena in &self.arenas {
-                arena.reset();
-            }
-            
-            self.assets.clear();
+    
+    #[wasm_bindgen]
+    pub fn set_reserve(&self, tier_number: u8, bytes: usize) -> Result<(), JsValue> {
+        let tier = Tier::from_u8(tier_number).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        self.inner.set_reserve(tier, bytes).map_err(JsValue::from_str)
+    }
 
-            std::sync::atomic::fence(std::sync::atomic::Ordering::SeqCst);
-            
-            let layout = std::alloc::Layout::from_size_align(self.memory_size, 4096)
-                .unwrap_or_else(|_| std::alloc::Layout::from_size_align(self.memory_size, 8).unwrap());
-            
-            unsafe {
-                std::alloc::dealloc(self.memory_base, layout);
-                GLOBAL_MEMORY_BASE = std::ptr::null_mut();
-            }
+    #[wasm_bindgen]
+    pub fn resize_tier(&mut self, tier_number: u8, new_size: usize) -> Result<usize, JsValue> {
+        let tier = Tier::from_u8(tier_number).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        let walloc = Arc::get_mut(&mut self.inner).ok_or_else(|| JsValue::from_str("Walloc is shared"))?;
+        walloc.resize_tier(tier, new_size).map_err(JsValue::from_str)
+    }
+    
+    #[wasm_bindgen]
+    pub fn allocate_headed(&self, size: usize, tier_number: u8) -> usize {
+        match Tier::from_u8(tier_number).and_then(|tier| self.inner.allocate_headed(size, tier)) {
+            Some(handle) => handle.offset(),
+            None => usize::MAX,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn allocate_with_opts(&self, size: usize, tier_number: u8, align: usize, zeroed: bool, tag: Option<String>) -> usize {
+        let opts = AllocOpts { align, zeroed, tag };
+        match Tier::from_u8(tier_number).and_then(|tier| self.inner.allocate_with_opts(size, tier, opts)) {
+            Some(handle) => handle.offset(),
+            None => usize::MAX,
         }
     }
-}
-
-// ================================
-// === WASM BINDINGS ===
-// ================================
-
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub struct WallocWrapper {
-    inner: Arc<Walloc>,
-}
-
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-impl WallocWrapper {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Result<WallocWrapper, JsValue> {
-        Walloc::new()
-            .map(|walloc| WallocWrapper { inner: walloc.into_arc() })
-            .map_err(|e| JsValue::from_str(e))
+    
+    // Frees an allocate_headed offset without the caller tracking its size or tier
+    #[wasm_bindgen]
+    pub fn deallocate_headed(&self, offset: usize) -> Result<usize, JsValue> {
+        self.inner.deallocate_headed(MemoryHandle(offset)).map_err(JsValue::from_str)
     }
     
-    // New constructor with base URL
     #[wasm_bindgen]
-    pub fn new_with_base_url(base_url: String) -> Result<WallocWrapper, JsValue> {
-        Walloc::new()
-            .map(|walloc| WallocWrapper { 
-                inner: walloc.with_base_url(base_url).into_arc() 
-            })
-            .map_err(|e| JsValue::from_str(e))
+    pub fn deallocate(&self, offset: usize, size: usize, tier_number: u8) -> Result<(), JsValue> {
+        let tier = Tier::from_u8(tier_number).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        self.inner.deallocate(MemoryHandle(offset), size, tier).map_err(JsValue::from_str)
+    }
+    
+    #[wasm_bindgen]
+    pub fn deallocate_deferred(&self, offset: usize, size: usize, tier_number: u8, fence_value: f64) -> Result<(), JsValue> {
+        let tier = Tier::from_u8(tier_number).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+        self.inner.deallocate_deferred(MemoryHandle(offset), size, tier, fence_value as u64).map_err(JsValue::from_str)
     }
     
-    // Note: base_url must be set before creating WallocWrapper
-    // This method is removed as base_url is immutable after Arc conversion
+    #[wasm_bindgen]
+    pub fn flush_deferred(&self, completed_fence: f64) -> usize {
+        self.inner.flush_deferred(completed_fence as u64)
+    }
     
+    // Evicts low-priority / least recently used assets of the tier to make room
     #[wasm_bindgen]
-    pub fn allocate(&self, size: usize, tier_number: u8) -> usize {
-        match (Tier::from_u8(tier_number), self.inner.allocate(size, Tier::from_u8(tier_number).unwrap_or(Tier::Bottom))) {
-            (Some(_), Some(handle)) => handle.offset(),
-            _ => usize::MAX,
+    pub fn allocate_or_evict(&self, size: usize, tier_number: u8) -> usize {
+        match Tier::from_u8(tier_number).and_then(|tier| self.inner.allocate_or_evict(size, tier)) {
+            Some(handle) => handle.offset(),
+            None => usize::MAX,
         }
     }
 
@@ -1399,10 +12803,8 @@ impl WallocWrapper {
             js_sys::Reflect::set(&obj, &"offset".into(), &JsValue::from_f64(handle.offset() as f64)).unwrap();
             js_sys::Reflect::set(&obj, &"size".into(), &JsValue::from_f64(owner.total_size() as f64)).unwrap();
             
-            // Store the owner in a way that JS can track
-            let owner_box = Box::new(owner);
-            let owner_ptr = Box::into_raw(owner_box);
-            js_sys::Reflect::set(&obj, &"owner_ptr".into(), &JsValue::from_f64(owner_ptr as usize as f64)).unwrap();
+            // JS can't hold a borrowing owner, so the allocation outlives it until freed explicitly
+            owner.into_raw();
         } else {
             js_sys::Reflect::set(&obj, &"offset".into(), &JsValue::from_f64(usize::MAX as f64)).unwrap();
         }
@@ -1434,10 +12836,24 @@ impl WallocWrapper {
             offset: handle,
             tier,
             handle: MemoryHandle(handle),
+            tag: None,
+            priority: 0,
+            pinned: false,
+            freshness: None,
         };
         
         self.inner.register_asset(key, metadata)
     }
+    
+    #[wasm_bindgen]
+    pub fn alias_asset(&self, existing_key: String, alias: String) -> bool {
+        self.inner.alias_asset(&existing_key, &alias)
+    }
+    
+    #[wasm_bindgen]
+    pub fn rename_asset(&self, old: String, new: String) -> bool {
+        self.inner.rename_asset(&old, &new)
+    }
 
     #[wasm_bindgen]
     pub fn evict_asset(&self, path: String) -> bool {
@@ -1466,6 +12882,147 @@ impl WallocWrapper {
             .unwrap_or(usize::MAX)
     }
     
+    #[wasm_bindgen]
+    pub fn set_deferred_compaction(&self, deferred: bool) {
+        self.inner.set_deferred_compaction(deferred);
+    }
+    
+    #[wasm_bindgen]
+    pub fn begin_compaction(&self, tier_number: u8) -> bool {
+        match Tier::from_u8(tier_number) {
+            Some(tier) => self.inner.begin_compaction(tier),
+            None => false,
+        }
+    }
+    
+    // Returns true while compaction work remains, so JS can keep scheduling idle callbacks
+    #[wasm_bindgen]
+    pub fn compact_step(&self, budget_bytes: usize) -> bool {
+        match self.inner.compact_step(budget_bytes) {
+            Some(progress) => !progress.complete || [Tier::Top, Tier::Middle, Tier::Bottom]
+                .iter()
+                .any(|&tier| self.inner.compaction_progress(tier).is_some()),
+            None => false,
+        }
+    }
+    
+    // A budget of 0 selects synchronous compaction
+    #[wasm_bindgen]
+    pub fn set_compaction_policy(&self, tier_number: u8, threshold: f64, budget_bytes: usize, only_when_idle: bool) -> bool {
+        let tier = match Tier::from_u8(tier_number) {
+            Some(t) => t,
+            None => return false,
+        };
+        
+        let mode = if budget_bytes == 0 {
+            CompactionMode::Synchronous
+        } else {
+            CompactionMode::Incremental { budget_bytes }
+        };
+        
+        self.inner.set_compaction_policy(tier, Some(CompactionPolicy {
+            threshold,
+            min_free_bytes: 0,
+            only_when_idle,
+            mode,
+        }));
+        true
+    }
+    
+    #[wasm_bindgen]
+    pub fn clear_compaction_policy(&self, tier_number: u8) -> bool {
+        match Tier::from_u8(tier_number) {
+            Some(tier) => {
+                self.inner.set_compaction_policy(tier, None);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    // Returns true while policy-driven compaction work remains
+    #[wasm_bindgen]
+    pub fn on_idle(&self) -> bool {
+        self.inner.on_idle().iter().any(|progress| !progress.complete)
+    }
+    
+    #[wasm_bindgen]
+    pub fn size_class_stats(&self, tier_number: u8) -> JsValue {
+        let Some(tier) = Tier::from_u8(tier_number) else {
+            return JsValue::NULL;
+        };
+        let json = serde_json::to_string(&self.inner.size_class_stats(tier)).unwrap_or_default();
+        js_sys::JSON::parse(&json).unwrap_or(JsValue::NULL)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_quarantine(&self, tier_number: u8, allocations: usize) -> bool {
+        match Tier::from_u8(tier_number) {
+            Some(tier) => {
+                self.inner.set_quarantine(tier, allocations);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_histogram_enabled(&self, tier_number: u8, enabled: bool) -> bool {
+        match Tier::from_u8(tier_number) {
+            Some(tier) => {
+                self.inner.set_histogram_enabled(tier, enabled);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn allocation_histogram(&self, tier_number: u8) -> js_sys::Array {
+        let buckets = js_sys::Array::new();
+        
+        if let Some(tier) = Tier::from_u8(tier_number) {
+            for bucket in self.inner.allocation_histogram(tier).buckets {
+                let bucket_obj = js_sys::Object::new();
+                js_sys::Reflect::set(&bucket_obj, &"maxSize".into(), &JsValue::from_f64(bucket.max_size as f64)).unwrap();
+                js_sys::Reflect::set(&bucket_obj, &"count".into(), &JsValue::from_f64(bucket.count as f64)).unwrap();
+                js_sys::Reflect::set(&bucket_obj, &"bytes".into(), &JsValue::from_f64(bucket.bytes as f64)).unwrap();
+                buckets.push(&bucket_obj);
+            }
+        }
+        
+        buckets
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_latency_profiling(&self, tier_number: u8, enabled: bool) -> bool {
+        match Tier::from_u8(tier_number) {
+            Some(tier) => {
+                self.inner.set_latency_profiling(tier, enabled);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn latency_profile(&self, tier_number: u8) -> JsValue {
+        let Some(tier) = Tier::from_u8(tier_number) else {
+            return JsValue::NULL;
+        };
+        let json = serde_json::to_string(&self.inner.latency_profile(tier)).unwrap_or_default();
+        js_sys::JSON::parse(&json).unwrap_or(JsValue::NULL)
+    }
+    
+    // Heap dump bytes (see HeapDump) for saving from devtools and inspecting offline
+    #[wasm_bindgen]
+    pub fn dump_heap(&self) -> Result<js_sys::Uint8Array, JsValue> {
+        let mut bytes = Vec::new();
+        self.inner.dump_heap(&mut bytes)
+            .map_err(|e| JsValue::from_str(&format!("Heap dump failed: {}", e)))?;
+        Ok(js_sys::Uint8Array::from(bytes.as_slice()))
+    }
+    
     #[wasm_bindgen]
     pub fn reset_tier(&self, tier_number: u8) -> bool {
         if let Some(tier) = Tier::from_u8(tier_number) {
@@ -1476,6 +13033,64 @@ impl WallocWrapper {
         }
     }
     
+    // Opaque Float64Array marker for "load, use, unwind" temp usage; empty for a bad tier
+    #[wasm_bindgen]
+    pub fn checkpoint(&self, tier_number: u8) -> Vec<f64> {
+        Tier::from_u8(tier_number)
+            .map(|tier| self.inner.mark(tier).to_words().to_vec())
+            .unwrap_or_default()
+    }
+    
+    // Unwinds to a checkpoint; returns the bytes handed back
+    #[wasm_bindgen]
+    pub fn restore(&self, marker: &[f64]) -> Result<usize, JsValue> {
+        let mark = ArenaMark::from_words(marker).ok_or_else(|| JsValue::from_str("Invalid checkpoint"))?;
+        self.inner.rollback(&mark).map_err(JsValue::from_str)
+    }
+    
+    #[wasm_bindgen]
+    pub fn tag_asset(&self, path: String, tag: Option<String>) -> bool {
+        self.inner.tag_asset(&path, tag)
+    }
+    
+    #[wasm_bindgen]
+    pub fn set_asset_priority(&self, path: String, priority: u8) -> bool {
+        self.inner.set_asset_priority(&path, priority)
+    }
+    
+    #[wasm_bindgen]
+    pub fn pin_asset(&self, path: String, pinned: bool) -> bool {
+        self.inner.pin_asset(&path, pinned)
+    }
+    
+    // Debug info for an offset received from JS; null if it is outside every tier
+    #[wasm_bindgen]
+    pub fn describe_handle(&self, offset: usize) -> JsValue {
+        match self.inner.describe_handle(MemoryHandle(offset)) {
+            Some(info) => {
+                let json = serde_json::to_string(&info).unwrap_or_default();
+                js_sys::JSON::parse(&json).unwrap_or(JsValue::NULL)
+            }
+            None => JsValue::NULL,
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn attribution_report(&self) -> JsValue {
+        let json = serde_json::to_string(&self.inner.attribution_report()).unwrap_or_default();
+        js_sys::JSON::parse(&json).unwrap_or(JsValue::NULL)
+    }
+    
+    #[wasm_bindgen]
+    pub fn reset_peaks(&self, tier_number: u8) -> bool {
+        if let Some(tier) = Tier::from_u8(tier_number) {
+            self.inner.reset_peaks(tier);
+            true
+        } else {
+            false
+        }
+    }
+    
     #[wasm_bindgen]
     pub fn load_asset(&self, path: String, asset_type: u8) -> Promise {
         let inner = self.inner.clone();
@@ -1510,13 +13125,68 @@ impl WallocWrapper {
         })
     }
     
+    // Resolves with the HTTP status once `len` bytes at `handle` have been PUT to base_url + path
+    #[wasm_bindgen]
+    pub fn store_asset(&self, path: String, handle: usize, len: usize) -> Promise {
+        let inner = self.inner.clone();
+        
+        future_to_promise(async move {
+            inner.store_asset(path, MemoryHandle(handle), len).await
+                .map(|status| JsValue::from_f64(status as f64))
+                .map_err(|e| JsValue::from_str(&e))
+        })
+    }
+    
+    // Resolves with the asset's offset, fetching only when it is missing or stale
+    #[wasm_bindgen]
+    pub fn get_or_load(&self, path: String, asset_type: u8) -> Promise {
+        let inner = self.inner.clone();
+        
+        future_to_promise(async move {
+            let asset_type = match asset_type {
+                0 => AssetType::Image,
+                1 => AssetType::Json,
+                2 => AssetType::Binary,
+                _ => return Err(JsValue::from_str("Invalid asset type")),
+            };
+            inner.get_or_load(&path, asset_type).await
+                .map(|handle| JsValue::from_f64(handle.offset() as f64))
+                .map_err(|e| JsValue::from_str(&e))
+        })
+    }
+    
+    // One binary WebSocket message from a JS-owned socket, e.g.
+    // `ws.onmessage = e => walloc.ingest_frame(new Uint8Array(e.data))`.
+    // Returns the completed asset's offset, or null while it is still arriving.
+    #[wasm_bindgen]
+    pub fn ingest_frame(&self, frame: &[u8]) -> Result<JsValue, JsValue> {
+        let frame = StreamFrame::decode(frame).map_err(JsValue::from_str)?;
+        match self.inner.ingest_frame(frame).map_err(|e| JsValue::from_str(&e))? {
+            Some(asset) => Ok(JsValue::from_f64(asset.handle.offset() as f64)),
+            None => Ok(JsValue::NULL),
+        }
+    }
+    
+    // Resolves with the offset once space is available, rejects after timeout_ms
+    #[wasm_bindgen]
+    pub fn allocate_async(&self, size: usize, tier_number: u8, timeout_ms: u32) -> Promise {
+        let inner = self.inner.clone();
+        
+        future_to_promise(async move {
+            let tier = Tier::from_u8(tier_number).ok_or_else(|| JsValue::from_str("Invalid tier"))?;
+            inner.allocate_async(size, tier, Duration::from_millis(timeout_ms as u64)).await
+                .map(|handle| JsValue::from_f64(handle.offset() as f64))
+                .map_err(JsValue::from_str)
+        })
+    }
+    
     #[wasm_bindgen]
     pub fn get_asset_data(&self, path: String) -> Result<js_sys::Uint8Array, JsValue> {
         let metadata = self.inner.get_asset(&path)
             .ok_or_else(|| JsValue::from_str(&format!("WASM Asset not found: {}", path)))?;
         
         unsafe {
-            let ptr = metadata.handle.to_ptr();
+            let ptr = self.inner.handle_ptr(metadata.handle);
             let mem_slice = std::slice::from_raw_parts(ptr, metadata.size);
             Ok(js_sys::Uint8Array::from(mem_slice))
         }
@@ -1550,7 +13220,7 @@ impl WallocWrapper {
         }
         
         unsafe {
-            let ptr = handle.to_ptr();
+            let ptr = self.inner.handle_ptr(handle);
             SIMDOps::fast_copy(data_vec.as_ptr(), ptr, data_vec.len());
         }
         
@@ -1602,13 +13272,7 @@ impl WallocWrapper {
                 
                 total_in_use += used;
                 
-                let tier_name = match tier {
-                    Tier::Top => "render",
-                    Tier::Middle => "scene", 
-                    Tier::Bottom => "entity",
-                };
-                
-                js_sys::Reflect::set(&tier_obj, &"name".into(), &JsValue::from_str(tier_name)).unwrap();
+                js_sys::Reflect::set(&tier_obj, &"name".into(), &JsValue::from_str(tier.name())).unwrap();
                 js_sys::Reflect::set(&tier_obj, &"used".into(), &JsValue::from_f64(used as f64)).unwrap();
                 js_sys::Reflect::set(&tier_obj, &"capacity".into(), &JsValue::from_f64(capacity as f64)).unwrap();
                 js_sys::Reflect::set(&tier_obj, &"highWaterMark".into(), &JsValue::from_f64(high_water as f64)).unwrap();
@@ -1617,6 +13281,16 @@ impl WallocWrapper {
                 let saved = if total_allocated > used { total_allocated - used } else { 0 };
                 js_sys::Reflect::set(&tier_obj, &"memorySaved".into(), &JsValue::from_f64(saved as f64)).unwrap();
                 
+                let frag = self.inner.fragmentation_stats(tier);
+                let freelist_bytes = js_sys::Array::new();
+                for bytes in &frag.freelist_bytes {
+                    freelist_bytes.push(&JsValue::from_f64(*bytes as f64));
+                }
+                js_sys::Reflect::set(&tier_obj, &"freelistBytes".into(), &freelist_bytes).unwrap();
+                js_sys::Reflect::set(&tier_obj, &"freeBytes".into(), &JsValue::from_f64(frag.free_bytes as f64)).unwrap();
+                js_sys::Reflect::set(&tier_obj, &"largestFreeBlock".into(), &JsValue::from_f64(frag.largest_free_block as f64)).unwrap();
+                js_sys::Reflect::set(&tier_obj, &"fragmentation".into(), &JsValue::from_f64(frag.fragmentation)).unwrap();
+                
                 tiers.push(&tier_obj);
             }
         }
@@ -1628,21 +13302,242 @@ impl WallocWrapper {
         js_sys::Reflect::set(&obj, &"allocatorType".into(), &JsValue::from_str("lock-free-tiered")).unwrap();
         js_sys::Reflect::set(&obj, &"memoryUtilization".into(), &JsValue::from_f64(self.inner.memory_utilization())).unwrap();
         
+        let fetch = self.inner.fetch_stats.snapshot();
+        let fetch_obj = js_sys::Object::new();
+        js_sys::Reflect::set(&fetch_obj, &"requests".into(), &JsValue::from_f64(fetch.requests as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"succeeded".into(), &JsValue::from_f64(fetch.succeeded as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"failed".into(), &JsValue::from_f64(fetch.failed as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"bytesFetched".into(), &JsValue::from_f64(fetch.bytes_fetched as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"suppressed".into(), &JsValue::from_f64(fetch.suppressed as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"cachedFailures".into(), &JsValue::from_f64(fetch.cached_failures as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"throttledMs".into(), &JsValue::from_f64(fetch.throttled_ms as f64)).unwrap();
+        js_sys::Reflect::set(&fetch_obj, &"queuedMs".into(), &JsValue::from_f64(fetch.queued_ms as f64)).unwrap();
+        js_sys::Reflect::set(&obj, &"fetch".into(), &fetch_obj).unwrap();
+        
+        let network = serde_json::to_string(&self.inner.network_stats()).unwrap_or_default();
+        let network = js_sys::JSON::parse(&network).unwrap_or(JsValue::NULL);
+        js_sys::Reflect::set(&obj, &"network".into(), &network).unwrap();
+        
         obj
     }
 }
 
-impl Clone for Walloc {
-    fn clone(&self) -> Self {
-        // Deep clone creates a new Walloc instance
-        let mut new_walloc = Self::with_memory(self.memory_base, self.memory_size)
-            .expect("Failed to clone Walloc");
+// ================================
+// === CONFIGURATION ===
+// ================================
 
-        // Clone base_url
-        new_walloc.base_url = self.base_url.clone();
-        // Don't clone self_ref - it will be set when into_arc is called
-        
-        new_walloc
+// Deployment settings for server builds, so heap size, tier split, fetch concurrency, the
+// disk cache and mirrors can be tuned without recompiling. Environment variables:
+//
+//   WALLOC_MEMORY_SIZE          bytes, or with a K/M/G suffix ("256M")
+//   WALLOC_TIER_SPLIT           Top,Middle,Bottom percentages summing to 100 ("60,25,15")
+//   WALLOC_MAX_CONNECTIONS      requests in flight per host, 0 = unlimited
+//   WALLOC_CACHE_DIR            disk cache directory
+//   WALLOC_CACHE_MAX_BYTES      disk cache budget, same format as the memory size
+//   WALLOC_BASE_URL             mirrors in failover order, comma-separated
+//
+// A config file is JSON with the same settings: {"memory_size": "256M", "tier_split":
+// [60, 25, 15], "max_connections_per_host": 8, "cache_dir": "/var/cache/walloc",
+// "cache_max_bytes": "1G", "base_urls": ["https://cdn.example.com/"]}. Unset values keep
+// the defaults of Walloc::new.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WallocBuilder {
+    memory_size: usize,
+    tier_split: [usize; 3],
+    max_connections_per_host: usize,
+    cache_dir: Option<std::path::PathBuf>,
+    cache_max_bytes: u64,
+    base_urls: Vec<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_DISK_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    memory_size: Option<ConfigSize>,
+    tier_split: Option<[usize; 3]>,
+    max_connections_per_host: Option<usize>,
+    cache_dir: Option<std::path::PathBuf>,
+    cache_max_bytes: Option<ConfigSize>,
+    base_urls: Option<Vec<String>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ConfigSize {
+    Bytes(u64),
+    Text(String),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ConfigSize {
+    fn bytes(&self) -> Result<u64, String> {
+        match self {
+            ConfigSize::Bytes(bytes) => Ok(*bytes),
+            ConfigSize::Text(text) => parse_size(text),
+        }
+    }
+}
+
+// "4096", "64K", "256M", "2G" (binary multiples; a trailing "B" or "iB" is accepted)
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_size(text: &str) -> Result<u64, String> {
+    let trimmed = text.trim();
+    let upper = trimmed.to_ascii_uppercase();
+    let number = upper.trim_end_matches("IB").trim_end_matches('B');
+    let (digits, shift) = match number.chars().last() {
+        Some('K') => (&number[..number.len() - 1], 10),
+        Some('M') => (&number[..number.len() - 1], 20),
+        Some('G') => (&number[..number.len() - 1], 30),
+        _ => (number, 0),
+    };
+    digits.trim().parse::<u64>().ok()
+        .and_then(|value| value.checked_mul(1 << shift))
+        .ok_or_else(|| format!("Invalid size '{}'", trimmed))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for WallocBuilder {
+    fn default() -> Self {
+        Self {
+            memory_size: 64 * 1024 * 1024,
+            tier_split: DEFAULT_TIER_SPLIT,
+            max_connections_per_host: 0,
+            cache_dir: None,
+            cache_max_bytes: DEFAULT_DISK_CACHE_BYTES,
+            base_urls: Vec::new(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl WallocBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn from_env() -> Result<Self, String> {
+        Self::new().with_env()
+    }
+    
+    pub fn from_config(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        Self::new().with_config_str(&text)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+    
+    // Layer a JSON config over the current settings
+    pub fn with_config_str(mut self, json: &str) -> Result<Self, String> {
+        let file: ConfigFile = serde_json::from_str(json).map_err(|e| format!("Invalid config: {}", e))?;
+        if let Some(size) = file.memory_size {
+            self = self.with_memory_size(size.bytes()? as usize);
+        }
+        if let Some([top, middle, bottom]) = file.tier_split {
+            self = self.with_tier_split(top, middle, bottom)?;
+        }
+        if let Some(max) = file.max_connections_per_host {
+            self = self.with_max_connections_per_host(max);
+        }
+        if let Some(dir) = file.cache_dir {
+            self.cache_dir = Some(dir);
+        }
+        if let Some(bytes) = file.cache_max_bytes {
+            self.cache_max_bytes = bytes.bytes()?;
+        }
+        if let Some(urls) = file.base_urls {
+            self.base_urls = urls;
+        }
+        Ok(self)
+    }
+    
+    // Layer the WALLOC_* environment variables over the current settings, e.g. over a
+    // config file. Unset variables leave the setting alone.
+    pub fn with_env(mut self) -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        if let Some(size) = var("WALLOC_MEMORY_SIZE") {
+            self = self.with_memory_size(parse_size(&size)? as usize);
+        }
+        if let Some(split) = var("WALLOC_TIER_SPLIT") {
+            let parts: Vec<usize> = split.split(',')
+                .map(|part| part.trim().parse::<usize>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("Invalid WALLOC_TIER_SPLIT '{}'", split))?;
+            let [top, middle, bottom] = parts[..] else {
+                return Err(format!("WALLOC_TIER_SPLIT needs three percentages, got '{}'", split));
+            };
+            self = self.with_tier_split(top, middle, bottom)?;
+        }
+        if let Some(max) = var("WALLOC_MAX_CONNECTIONS") {
+            let max = max.trim().parse().map_err(|_| format!("Invalid WALLOC_MAX_CONNECTIONS '{}'", max))?;
+            self = self.with_max_connections_per_host(max);
+        }
+        if let Some(dir) = var("WALLOC_CACHE_DIR") {
+            self.cache_dir = Some(dir.into());
+        }
+        if let Some(bytes) = var("WALLOC_CACHE_MAX_BYTES") {
+            self.cache_max_bytes = parse_size(&bytes)?;
+        }
+        if let Some(urls) = var("WALLOC_BASE_URL") {
+            self.base_urls = urls.split(',').map(|url| url.trim().to_string()).collect();
+        }
+        Ok(self)
+    }
+    
+    pub fn with_memory_size(mut self, bytes: usize) -> Self {
+        self.memory_size = bytes;
+        self
+    }
+    
+    pub fn with_tier_split(mut self, top: usize, middle: usize, bottom: usize) -> Result<Self, String> {
+        if top == 0 || middle == 0 || bottom == 0 || top + middle + bottom != 100 {
+            return Err(format!("Tier split {}/{}/{} must be three non-zero percentages summing to 100", top, middle, bottom));
+        }
+        self.tier_split = [top, middle, bottom];
+        Ok(self)
+    }
+    
+    pub fn with_max_connections_per_host(mut self, max: usize) -> Self {
+        self.max_connections_per_host = max;
+        self
+    }
+    
+    pub fn with_disk_cache(mut self, dir: impl Into<std::path::PathBuf>, max_bytes: u64) -> Self {
+        self.cache_dir = Some(dir.into());
+        self.cache_max_bytes = max_bytes;
+        self
+    }
+    
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_urls.push(url.into());
+        self
+    }
+    
+    pub fn build(self) -> Result<Walloc, String> {
+        let mut walloc = Walloc::with_backend_split(SystemBackend, self.memory_size, self.tier_split)
+            .map_err(str::to_string)?;
+        if let Some(dir) = self.cache_dir {
+            let cache = DiskCache::new(&dir, self.cache_max_bytes)
+                .map_err(|e| format!("Failed to open disk cache '{}': {}", dir.display(), e))?;
+            walloc.set_disk_cache(Some(cache));
+        }
+        walloc.set_max_connections_per_host(self.max_connections_per_host);
+        walloc.set_base_urls(self.base_urls);
+        Ok(walloc)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Walloc {
+    // WallocBuilder::from_env()?.build()
+    pub fn from_env() -> Result<Self, String> {
+        WallocBuilder::from_env()?.build()
     }
 }
 